@@ -0,0 +1,143 @@
+//! Row and column access with each storage layout
+//!
+//! Run with `cargo bench --bench layout`. The column-major layouts should win the column scans and lose when sequences are added one at a time.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use multi_seq_align::conservation::{Conservation, Method};
+use multi_seq_align::{Alignment, Layout};
+
+const LAYOUTS: [Layout; 3] = [Layout::RowMajor, Layout::ColumnMajor, Layout::Lazy];
+
+const RESIDUES: &[u8] = b"ACDEFGHIKLMNPQRSTVWY-";
+
+/// Returns `n_sequences` pseudo-random protein sequences of `length` residues
+fn sequences(n_sequences: usize, length: usize) -> Vec<Vec<u8>> {
+    let mut state: u64 = 42;
+    (0..n_sequences)
+        .map(|_| {
+            (0..length)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    RESIDUES[(state >> 33) as usize % RESIDUES.len()]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the alignment with `layout`, its column-major copy already built
+fn alignment(sequences: &[Vec<u8>], layout: Layout) -> Alignment<u8> {
+    let mut align = Alignment::with_sequences(sequences).unwrap();
+    align.set_layout(layout);
+    let _ = align.column(0);
+    align
+}
+
+fn columns(c: &mut Criterion) {
+    let sequences = sequences(500, 2_000);
+    let mut group = c.benchmark_group("columns");
+
+    for &layout in &LAYOUTS {
+        let align = alignment(&sequences, layout);
+        let name = format!("{:?}", layout);
+
+        group.bench_with_input(
+            BenchmarkId::new("iter_columns", &name),
+            &align,
+            |b, align| {
+                b.iter(|| {
+                    align
+                        .iter_columns()
+                        .map(|column| column.iter().filter(|&&residue| residue == b'-').count())
+                        .sum::<usize>()
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("iter_positions", &name),
+            &align,
+            |b, align| {
+                b.iter(|| {
+                    align
+                        .iter_positions()
+                        .map(|position| position.len())
+                        .sum::<usize>()
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("conservation", &name),
+            &align,
+            |b, align| {
+                let conservation = Conservation::new(Method::ShannonEntropy);
+                b.iter(|| conservation.compute(align).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn rows(c: &mut Criterion) {
+    let sequences = sequences(500, 2_000);
+    let mut group = c.benchmark_group("rows");
+
+    for &layout in &LAYOUTS {
+        let align = alignment(&sequences, layout);
+
+        group.bench_with_input(
+            BenchmarkId::new("iter_rows", format!("{:?}", layout)),
+            &align,
+            |b, align| {
+                b.iter(|| {
+                    align
+                        .iter_rows()
+                        .map(|row| row.iter().filter(|&&residue| residue == b'-').count())
+                        .sum::<usize>()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn updates(c: &mut Criterion) {
+    let sequences = sequences(100, 1_000);
+    let mut group = c.benchmark_group("updates");
+
+    for &layout in &LAYOUTS {
+        let name = format!("{:?}", layout);
+
+        group.bench_with_input(
+            BenchmarkId::new("add", &name),
+            &sequences,
+            |b, sequences| {
+                b.iter(|| {
+                    let mut align = Alignment::new(1_000);
+                    align.set_layout(layout);
+                    for sequence in sequences {
+                        align.add(sequence.clone()).unwrap();
+                    }
+                    black_box(align)
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("set_layout", &name),
+            &sequences,
+            |b, sequences| {
+                let align = Alignment::with_sequences(sequences).unwrap();
+                b.iter(|| {
+                    let mut align = align.clone();
+                    align.set_layout(layout);
+                    black_box(align)
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, columns, rows, updates);
+criterion_main!(benches);
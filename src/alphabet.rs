@@ -0,0 +1,361 @@
+/*! Alphabets of residues
+
+An [`Alphabet`] tells which symbols are valid residues, which ones are gaps and which one stands for an unknown or missing residue. The built-in alphabets are [`Dna`], [`Rna`], [`Protein`] and [`Iupac`] (nucleotides with ambiguity codes). They are case insensitive.
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::alphabet::{Alphabet, Dna};
+assert!(Dna.is_valid(b'a'));
+assert!(Dna.is_gap(b'.'));
+assert!(!Dna.is_valid(b'U'));
+
+let error = Alignment::with_sequences_validated(&[b"ACGT".to_vec(), b"ACGU".to_vec()], &Dna)
+    .err()
+    .unwrap();
+assert_eq!(
+    error.to_string(),
+    "Invalid DNA symbols ['U'] at rows [1] and columns [3]"
+);
+```
+
+[`Alphabet`]: trait.Alphabet.html
+[`Dna`]: struct.Dna.html
+[`Rna`]: struct.Rna.html
+[`Protein`]: struct.Protein.html
+[`Iupac`]: struct.Iupac.html
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+
+/// Set of symbols allowed in the sequences of an alignment
+pub trait Alphabet {
+    /// Returns the name of the alphabet, used in error messages
+    fn name(&self) -> &'static str;
+
+    /// Returns the residue symbols, in uppercase unless the alphabet is case sensitive
+    fn symbols(&self) -> &[u8];
+
+    /// Returns the gap symbols, the first one being the one written by default
+    fn gaps(&self) -> &[u8] {
+        b"-."
+    }
+
+    /// Returns the symbol of an unknown or missing residue
+    fn unknown(&self) -> u8;
+
+    /// Returns `true` if lowercase and uppercase symbols are distinct
+    fn is_case_sensitive(&self) -> bool {
+        false
+    }
+
+    /// Returns the gap symbol written by default
+    fn gap(&self) -> u8 {
+        self.gaps().first().copied().unwrap_or(b'-')
+    }
+
+    /// Returns `symbol` in the case of the alphabet
+    fn normalize(&self, symbol: u8) -> u8 {
+        if self.is_case_sensitive() {
+            symbol
+        } else {
+            symbol.to_ascii_uppercase()
+        }
+    }
+
+    /// Returns `true` if `symbol` is a gap
+    fn is_gap(&self, symbol: u8) -> bool {
+        self.gaps().contains(&symbol)
+    }
+
+    /// Returns `true` if `symbol` is the unknown residue
+    fn is_unknown(&self, symbol: u8) -> bool {
+        self.normalize(symbol) == self.unknown()
+    }
+
+    /// Returns `true` if `symbol` is a residue of the alphabet
+    fn is_residue(&self, symbol: u8) -> bool {
+        self.symbols().contains(&self.normalize(symbol))
+    }
+
+    /// Returns `true` if `symbol` is a residue, a gap or the unknown residue
+    fn is_valid(&self, symbol: u8) -> bool {
+        self.is_residue(symbol) || self.is_gap(symbol) || self.is_unknown(symbol)
+    }
+}
+
+/// Nucleotides of DNA (`ACGT`), `N` being unknown
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Dna;
+
+impl Alphabet for Dna {
+    fn name(&self) -> &'static str {
+        "DNA"
+    }
+
+    fn symbols(&self) -> &[u8] {
+        b"ACGT"
+    }
+
+    fn unknown(&self) -> u8 {
+        b'N'
+    }
+}
+
+/// Nucleotides of RNA (`ACGU`), `N` being unknown
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Rna;
+
+impl Alphabet for Rna {
+    fn name(&self) -> &'static str {
+        "RNA"
+    }
+
+    fn symbols(&self) -> &[u8] {
+        b"ACGU"
+    }
+
+    fn unknown(&self) -> u8 {
+        b'N'
+    }
+}
+
+/// The 20 standard amino acids, `X` being unknown
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Protein;
+
+impl Alphabet for Protein {
+    fn name(&self) -> &'static str {
+        "protein"
+    }
+
+    fn symbols(&self) -> &[u8] {
+        b"ACDEFGHIKLMNPQRSTVWY"
+    }
+
+    fn unknown(&self) -> u8 {
+        b'X'
+    }
+}
+
+/// Nucleotides of DNA or RNA with the IUPAC ambiguity codes, `N` being unknown
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Iupac;
+
+impl Iupac {
+    /// Returns the nucleotides (among `ACGT`) represented by an IUPAC `code`, `U` standing for `T`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::alphabet::Iupac;
+    /// assert_eq!(Iupac::nucleotides(b'r'), b"AG");
+    /// assert_eq!(Iupac::nucleotides(b'N'), b"ACGT");
+    /// assert_eq!(Iupac::nucleotides(b'-'), b"");
+    /// ```
+    #[must_use]
+    pub const fn nucleotides(code: u8) -> &'static [u8] {
+        match code.to_ascii_uppercase() {
+            b'A' => b"A",
+            b'C' => b"C",
+            b'G' => b"G",
+            b'T' | b'U' => b"T",
+            b'R' => b"AG",
+            b'Y' => b"CT",
+            b'S' => b"CG",
+            b'W' => b"AT",
+            b'K' => b"GT",
+            b'M' => b"AC",
+            b'B' => b"CGT",
+            b'D' => b"AGT",
+            b'H' => b"ACT",
+            b'V' => b"ACG",
+            b'N' => b"ACGT",
+            _ => b"",
+        }
+    }
+
+    /// Returns the IUPAC code representing a set of `nucleotides` (among `ACGTU`, in any order and case), `None` if there is none
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::alphabet::Iupac;
+    /// assert_eq!(Iupac::code(b"GA"), Some(b'R'));
+    /// assert_eq!(Iupac::code(b"acgt"), Some(b'N'));
+    /// assert_eq!(Iupac::code(b"X"), None);
+    /// ```
+    #[must_use]
+    pub fn code(nucleotides: &[u8]) -> Option<u8> {
+        let mut mask = 0_u8;
+        for nucleotide in nucleotides {
+            mask |= match nucleotide.to_ascii_uppercase() {
+                b'A' => 1,
+                b'C' => 2,
+                b'G' => 4,
+                b'T' | b'U' => 8,
+                _ => return None,
+            };
+        }
+
+        b"-ACMGRSVTWYHKDBN"
+            .get(usize::from(mask))
+            .copied()
+            .filter(|&code| code != b'-')
+    }
+}
+
+impl Alphabet for Iupac {
+    fn name(&self) -> &'static str {
+        "IUPAC"
+    }
+
+    fn symbols(&self) -> &[u8] {
+        b"ACGTURYSWKMBDHVN"
+    }
+
+    fn unknown(&self) -> u8 {
+        b'N'
+    }
+}
+
+/// Validation of the residues against an [`Alphabet`](alphabet/trait.Alphabet.html)
+impl<T> Alignment<T> {
+    /// Create an `Alignment` from same length vectors sequences, checking every symbol against `alphabet`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the sequences have different lengths (based on the first sequence), or if some symbols are not valid in `alphabet`.
+    pub fn with_sequences_validated<A>(
+        sequences: &[Vec<T>],
+        alphabet: &A,
+    ) -> Result<Self, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+        A: Alphabet + ?Sized,
+    {
+        let alignment = Self::with_sequences(sequences)?;
+        alignment.validate(alphabet)?;
+        Ok(alignment)
+    }
+
+    /// Checks every symbol of `self` against `alphabet`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::alphabet::{Iupac, Protein};
+    /// let align = Alignment::with_sequences(&[b"ACGT".to_vec(), b"UBN-".to_vec()]).unwrap();
+    ///
+    /// assert!(align.validate(&Iupac).is_ok());
+    /// assert!(align.validate(&Protein).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error listing the invalid symbols along with their row and column.
+    pub fn validate<A>(&self, alphabet: &A) -> Result<(), MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+        A: Alphabet + ?Sized,
+    {
+        let mut symbols = Vec::new();
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+
+        for (index, residue) in self.sequences.iter().enumerate() {
+            let symbol: u8 = residue.clone().into();
+            if !alphabet.is_valid(symbol) {
+                symbols.push(char::from(symbol));
+                rows.push(index / self.length);
+                columns.push(index % self.length);
+            }
+        }
+
+        if symbols.is_empty() {
+            Ok(())
+        } else {
+            Err(MultiSeqAlignError::InvalidSymbols {
+                alphabet: alphabet.name(),
+                symbols,
+                rows,
+                columns,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builtin_alphabets() {
+        assert!(Dna.is_residue(b't'));
+        assert!(!Dna.is_residue(b'N'));
+        assert!(Dna.is_unknown(b'n'));
+        assert!(Rna.is_valid(b'U'));
+        assert!(!Rna.is_valid(b'T'));
+        assert!(Protein.is_valid(b'w'));
+        assert!(Protein.is_valid(b'X'));
+        assert!(!Protein.is_valid(b'B'));
+        assert!(Iupac.is_residue(b'y'));
+        assert_eq!(Protein.gap(), b'-');
+        assert!(Iupac.is_gap(b'.'));
+    }
+
+    #[test]
+    fn case_sensitive_alphabet() {
+        struct Uppercase;
+        impl Alphabet for Uppercase {
+            fn name(&self) -> &'static str {
+                "uppercase"
+            }
+            fn symbols(&self) -> &[u8] {
+                b"ACGT"
+            }
+            fn gaps(&self) -> &[u8] {
+                b"-"
+            }
+            fn unknown(&self) -> u8 {
+                b'N'
+            }
+            fn is_case_sensitive(&self) -> bool {
+                true
+            }
+        }
+
+        assert!(Uppercase.is_valid(b'A'));
+        assert!(!Uppercase.is_valid(b'a'));
+        assert!(!Uppercase.is_valid(b'.'));
+    }
+
+    #[test]
+    fn iupac_codes() {
+        for &code in Iupac.symbols() {
+            if code != b'U' {
+                assert_eq!(Iupac::code(Iupac::nucleotides(code)), Some(code));
+            }
+        }
+        assert_eq!(Iupac::code(b""), None);
+        assert_eq!(Iupac::code(b"UC"), Some(b'Y'));
+    }
+
+    #[test]
+    fn invalid_symbols() {
+        let align =
+            Alignment::with_sequences(&[b"ACGT".to_vec(), b"AC-T".to_vec(), b"EC.J".to_vec()])
+                .unwrap();
+        let error = align.validate(&Dna).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidSymbols {
+            alphabet: "DNA",
+            symbols: vec!['E', 'J'],
+            rows: vec![2, 2],
+            columns: vec![0, 3],
+        };
+        assert_eq!(error, expected);
+    }
+}
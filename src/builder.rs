@@ -0,0 +1,207 @@
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+
+/// Builds an [`Alignment`] one record at a time, appending residues straight into its flat buffer
+///
+/// Unlike [`with_sequences()`], no intermediate `Vec<Vec<T>>` is needed: residues can be appended as they are read with [`extend()`], and each record is checked against the length of the first one by [`finish_record()`]. Once the first record fixes the length, the buffer is allocated for the number of sequences given to [`with_capacity()`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::{Alignment, AlignmentBuilder};
+/// let mut builder = AlignmentBuilder::with_capacity(2);
+///
+/// builder.extend(b"ALKH".iter().copied());
+/// builder.extend(b"ITAN".iter().copied());
+/// builder.finish_record().unwrap();
+///
+/// builder.push(b"VLK-ITAN".iter().copied()).unwrap();
+///
+/// let align = builder.build().unwrap();
+/// assert_eq!(
+///     align,
+///     Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap()
+/// );
+/// ```
+///
+/// [`Alignment`]: struct.Alignment.html
+/// [`with_sequences()`]: struct.Alignment.html#method.with_sequences
+/// [`extend()`]: struct.AlignmentBuilder.html#method.extend
+/// [`finish_record()`]: struct.AlignmentBuilder.html#method.finish_record
+/// [`with_capacity()`]: struct.AlignmentBuilder.html#method.with_capacity
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct AlignmentBuilder<T> {
+    /// Residues of the finished records, followed by the ones of the current record
+    sequences: Vec<T>,
+    /// The number of finished records
+    n_sequences: usize,
+    /// The length of the alignment, fixed by the first record
+    length: Option<usize>,
+    /// The expected number of sequences
+    capacity: usize,
+}
+
+impl<T> Default for AlignmentBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AlignmentBuilder<T> {
+    /// Returns an empty `AlignmentBuilder`
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Returns an empty `AlignmentBuilder` expecting `n_sequences` records
+    ///
+    /// The buffer is allocated for all of them once the first record is finished.
+    #[must_use]
+    pub const fn with_capacity(n_sequences: usize) -> Self {
+        Self {
+            sequences: Vec::new(),
+            n_sequences: 0,
+            length: None,
+            capacity: n_sequences,
+        }
+    }
+
+    /// Returns the number of finished records
+    #[must_use]
+    pub const fn n_sequences(&self) -> &usize {
+        &self.n_sequences
+    }
+
+    /// Returns the length of the alignment, `None` until the first record is finished
+    #[must_use]
+    pub const fn length(&self) -> Option<usize> {
+        self.length
+    }
+
+    /// Returns the number of residues appended to the current record
+    fn current_length(&self) -> usize {
+        self.sequences.len() - self.n_sequences * self.length.unwrap_or_default()
+    }
+
+    /// Appends `residues` to the current record
+    pub fn extend<I>(&mut self, residues: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.sequences.extend(residues);
+        self
+    }
+
+    /// Finishes the current record
+    ///
+    /// The first record fixes the length of the alignment. A record of a different length is discarded, leaving the builder as it was before the record was started.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the length of the record is different from the one of the first record.
+    pub fn finish_record(&mut self) -> Result<&mut Self, MultiSeqAlignError> {
+        let found_length = self.current_length();
+
+        match self.length {
+            Some(length) if length != found_length => {
+                self.sequences.truncate(self.n_sequences * length);
+                return Err(MultiSeqAlignError::NewSequenceOfDifferentLength {
+                    expected_length: length,
+                    found_length,
+                });
+            }
+            Some(_) => {}
+            None => {
+                self.length = Some(found_length);
+                self.sequences
+                    .reserve_exact(found_length * self.capacity.saturating_sub(1));
+            }
+        }
+
+        self.n_sequences += 1;
+        Ok(self)
+    }
+
+    /// Appends a complete record, see [`extend()`] and [`finish_record()`]
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the length of `record` is different from the one of the first record.
+    ///
+    /// [`extend()`]: struct.AlignmentBuilder.html#method.extend
+    /// [`finish_record()`]: struct.AlignmentBuilder.html#method.finish_record
+    pub fn push<I>(&mut self, record: I) -> Result<&mut Self, MultiSeqAlignError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.extend(record).finish_record()
+    }
+
+    /// Returns the `Alignment` of the finished records, finishing the current one if residues were appended to it
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the current record has a different length from the first record.
+    pub fn build(mut self) -> Result<Alignment<T>, MultiSeqAlignError> {
+        if self.current_length() > 0 {
+            self.finish_record()?;
+        }
+
+        Ok(Alignment::from_parts(
+            self.sequences,
+            self.n_sequences,
+            self.length.unwrap_or_default(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn build_empty() {
+        let align = AlignmentBuilder::<u8>::new().build().unwrap();
+        assert_eq!(align, Alignment::default());
+    }
+
+    #[test]
+    fn preallocate_after_first_record() {
+        let mut builder = AlignmentBuilder::with_capacity(3);
+        builder.push(b"ELK".to_vec()).unwrap();
+
+        assert_eq!(builder.length(), Some(3));
+        assert!(builder.sequences.capacity() >= 9);
+
+        builder.push(b"ILK".to_vec()).unwrap();
+        builder.extend(b"VLK".to_vec());
+        assert_eq!(*builder.n_sequences(), 2);
+
+        let align = builder.build().unwrap();
+        assert_eq!(align.sequences, b"ELKILKVLK".to_vec());
+        assert_eq!(*align.n_sequences(), 3);
+    }
+
+    #[test]
+    fn discard_record_of_different_length() {
+        let mut builder = AlignmentBuilder::new();
+        builder.push(b"ELK".to_vec()).unwrap();
+
+        let error = builder.push(b"IL".to_vec()).err().unwrap();
+        let expected = MultiSeqAlignError::NewSequenceOfDifferentLength {
+            expected_length: 3,
+            found_length: 2,
+        };
+        assert_eq!(error, expected);
+
+        builder.extend(b"ILKAV".to_vec());
+        let error = builder.build().err().unwrap();
+        let expected = MultiSeqAlignError::NewSequenceOfDifferentLength {
+            expected_length: 3,
+            found_length: 5,
+        };
+        assert_eq!(error, expected);
+    }
+}
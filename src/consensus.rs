@@ -0,0 +1,346 @@
+/*! Consensus sequences
+
+The consensus of a column is the residue whose (weighted) frequency reaches a threshold, 50% by default. When no residue wins, the consensus is either an IUPAC ambiguity code covering the most frequent nucleotides (see [`ambiguity()`]) or a fallback symbol, `X` by default.
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::consensus::Consensus;
+let mut align = Alignment::with_sequences(&[
+    b"ACGTA".to_vec(),
+    b"ACGAC".to_vec(),
+    b"AC-AG".to_vec(),
+    b"TC-TT".to_vec(),
+])
+.unwrap();
+
+let consensus = Consensus::new().fallback(b'N').compute(&align).unwrap();
+assert_eq!(consensus, b"ACGNN".to_vec());
+
+let consensus = Consensus::new().ambiguity(true).compute(&align).unwrap();
+assert_eq!(consensus, b"ACGWN".to_vec());
+
+// the consensus can be added back to the alignment
+align.add_named("consensus", consensus).unwrap();
+```
+
+[`ambiguity()`]: struct.Consensus.html#method.ambiguity
+*/
+
+use crate::alphabet::Iupac;
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+
+/// Default frequency a residue must reach to be the consensus of a column
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// Symbol of the columns without consensus, gap-only columns excepted
+pub const DEFAULT_FALLBACK: u8 = b'X';
+
+/// Handling of the gaps (`-` or `.`) when computing frequencies
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Gaps {
+    /// Frequencies are computed over the residues only, the consensus of a column of gaps is a gap
+    #[default]
+    Ignore,
+    /// Gaps are counted like a residue, and can be the consensus of a column
+    Count,
+}
+
+/// Computes consensus sequences
+#[derive(Clone, PartialEq, Debug)]
+pub struct Consensus {
+    /// Frequency a residue must reach to be the consensus
+    threshold: f64,
+    /// Handling of gaps
+    gaps: Gaps,
+    /// Use IUPAC ambiguity codes when no nucleotide wins
+    ambiguity: bool,
+    /// Symbol of the columns without consensus
+    fallback: u8,
+    /// Weight of each sequence, `None` for equal weights
+    weights: Option<Vec<f64>>,
+}
+
+impl Default for Consensus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Consensus {
+    /// Returns a `Consensus` with a threshold of [`DEFAULT_THRESHOLD`], ignoring gaps and falling back to [`DEFAULT_FALLBACK`]
+    ///
+    /// [`DEFAULT_THRESHOLD`]: constant.DEFAULT_THRESHOLD.html
+    /// [`DEFAULT_FALLBACK`]: constant.DEFAULT_FALLBACK.html
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            gaps: Gaps::Ignore,
+            ambiguity: false,
+            fallback: DEFAULT_FALLBACK,
+            weights: None,
+        }
+    }
+
+    /// Sets the frequency (between 0 and 1) a residue must reach to be the consensus of a column
+    ///
+    /// A residue tied with another one never wins.
+    #[must_use]
+    pub const fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets whether gaps are ignored or counted
+    #[must_use]
+    pub const fn gaps(mut self, gaps: Gaps) -> Self {
+        self.gaps = gaps;
+        self
+    }
+
+    /// Sets whether columns without consensus get the IUPAC code of their most frequent nucleotides
+    ///
+    /// The nucleotides are taken by decreasing frequency until their cumulated frequency reaches the threshold. The fallback symbol is used if one of them is not a nucleotide.
+    #[must_use]
+    pub const fn ambiguity(mut self, ambiguity: bool) -> Self {
+        self.ambiguity = ambiguity;
+        self
+    }
+
+    /// Sets the symbol of the columns without consensus, usually `X` for proteins and `N` for nucleotides
+    #[must_use]
+    pub const fn fallback(mut self, fallback: u8) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Sets the weight of each sequence
+    #[must_use]
+    pub fn weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Returns the consensus sequence of `alignment`
+    ///
+    /// Residues are compared regardless of their case, the consensus being in uppercase.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of weights is different from the number of sequences, or if a weight is negative or not a finite number.
+    pub fn compute<T>(&self, alignment: &Alignment<T>) -> Result<Vec<T>, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8> + From<u8>,
+    {
+        if let Some(weights) = &self.weights {
+            utils::check_weights(weights, alignment.n_sequences)?;
+        }
+
+        Ok(self.compute_unchecked(alignment))
+    }
+
+    /// Returns the consensus sequence of `alignment`, the weights having been checked
+    fn compute_unchecked<T>(&self, alignment: &Alignment<T>) -> Vec<T>
+    where
+        T: Clone + Into<u8> + From<u8>,
+    {
+        let mut counts = [0.0_f64; 256];
+        (0..alignment.length)
+            .map(|column| {
+                counts = [0.0; 256];
+                for row in 0..alignment.n_sequences {
+                    let residue: u8 = alignment.sequences[row * alignment.length + column]
+                        .clone()
+                        .into();
+                    let symbol = if utils::is_gap(residue) {
+                        b'-'
+                    } else {
+                        residue.to_ascii_uppercase()
+                    };
+                    counts[usize::from(symbol)] +=
+                        self.weights.as_ref().map_or(1.0, |weights| weights[row]);
+                }
+                T::from(self.column_consensus(&counts))
+            })
+            .collect()
+    }
+
+    /// Returns the consensus of a column from the weighted count of each symbol, gaps being counted as `-`
+    fn column_consensus(&self, counts: &[f64; 256]) -> u8 {
+        let mut frequencies: Vec<(u8, f64)> = (0..=u8::MAX)
+            .filter(|&symbol| self.gaps == Gaps::Count || symbol != b'-')
+            .map(|symbol| (symbol, counts[usize::from(symbol)]))
+            .filter(|&(_, count)| count > 0.0)
+            .collect();
+        let total: f64 = frequencies.iter().map(|&(_, count)| count).sum();
+        if frequencies.is_empty() || total <= 0.0 {
+            return b'-';
+        }
+
+        for (_, count) in &mut frequencies {
+            *count /= total;
+        }
+        // stable sort, ties stay in the order of the symbols
+        frequencies.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (best, frequency) = frequencies[0];
+        let tied = frequencies.get(1).is_some_and(|&(_, f)| f >= frequency);
+        if frequency >= self.threshold && !tied {
+            return best;
+        }
+
+        if self.ambiguity {
+            if let Some(code) = self.ambiguity_code(&frequencies) {
+                return code;
+            }
+        }
+        self.fallback
+    }
+
+    /// Returns the IUPAC code of the most frequent symbols reaching the threshold, if they are all nucleotides
+    fn ambiguity_code(&self, frequencies: &[(u8, f64)]) -> Option<u8> {
+        let mut nucleotides = Vec::new();
+        let mut cumulated = 0.0;
+        let mut last = f64::INFINITY;
+
+        for &(symbol, frequency) in frequencies {
+            // symbols tied with the last one taken are taken too
+            if cumulated >= self.threshold && frequency < last {
+                break;
+            }
+            let represented = Iupac::nucleotides(symbol);
+            if represented.is_empty() {
+                return None;
+            }
+            nucleotides.extend_from_slice(represented);
+            cumulated += frequency;
+            last = frequency;
+        }
+
+        Iupac::code(&nucleotides)
+    }
+}
+
+impl<T> Alignment<T> {
+    /// Returns the majority-rule consensus of `self`, see [`Consensus`] for the other options
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let align = Alignment::with_sequences(&[
+    ///     b"ALKHITAN".to_vec(),
+    ///     b"VLK-ITAN".to_vec(),
+    ///     b"ALRYITAT".to_vec(),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(align.consensus(), b"ALKXITAN".to_vec());
+    /// ```
+    ///
+    /// [`Consensus`]: consensus/struct.Consensus.html
+    #[must_use]
+    pub fn consensus(&self) -> Vec<T>
+    where
+        T: Clone + Into<u8> + From<u8>,
+    {
+        Consensus::new().compute_unchecked(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn alignment() -> Alignment<u8> {
+        Alignment::with_sequences(&[
+            b"ACGT-".to_vec(),
+            b"ACGa-".to_vec(),
+            b"AC-A-".to_vec(),
+            b"TT-C-".to_vec(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn threshold() {
+        let align = alignment();
+
+        assert_eq!(align.consensus(), b"ACGA-".to_vec());
+        assert_eq!(
+            Consensus::new().threshold(0.75).compute(&align).unwrap(),
+            b"ACGX-".to_vec()
+        );
+        assert_eq!(
+            Consensus::new().threshold(0.8).compute(&align).unwrap(),
+            b"XXGX-".to_vec()
+        );
+    }
+
+    #[test]
+    fn count_gaps() {
+        let align = alignment();
+
+        assert_eq!(
+            Consensus::new().gaps(Gaps::Count).compute(&align).unwrap(),
+            b"ACXA-".to_vec()
+        );
+    }
+
+    #[test]
+    fn ambiguity_codes() {
+        let align = alignment();
+
+        assert_eq!(
+            Consensus::new()
+                .ambiguity(true)
+                .threshold(0.8)
+                .compute(&align)
+                .unwrap(),
+            b"WYGH-".to_vec()
+        );
+        // the gap is among the most frequent symbols
+        assert_eq!(
+            Consensus::new()
+                .ambiguity(true)
+                .gaps(Gaps::Count)
+                .fallback(b'N')
+                .compute(&align)
+                .unwrap(),
+            b"ACNA-".to_vec()
+        );
+    }
+
+    #[test]
+    fn weights() {
+        let align = alignment();
+
+        assert_eq!(
+            Consensus::new()
+                .weights(vec![0.1, 0.1, 0.1, 1.0])
+                .compute(&align)
+                .unwrap(),
+            b"TTGC-".to_vec()
+        );
+
+        let error = Consensus::new()
+            .weights(vec![1.0])
+            .compute(&align)
+            .err()
+            .unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfWeights {
+            n_sequences: 4,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+
+        let error = Consensus::new()
+            .weights(vec![1.0, f64::NAN, 1.0, 1.0])
+            .compute(&align)
+            .err()
+            .unwrap();
+        assert_eq!(error, MultiSeqAlignError::InvalidWeight { row: 1 });
+    }
+}
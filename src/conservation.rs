@@ -0,0 +1,533 @@
+/*! Conservation scores of the columns
+
+The scores follow Capra & Singh (2007), *Predicting functionally important residues from sequence conservation*, Bioinformatics 23(15):1875–1882:
+
+- [`Method::ShannonEntropy`]: one minus the entropy of the column, normalised to lie between 0 and 1
+- [`Method::PropertyEntropy`]: the same over classes of amino acids sharing physicochemical properties (Mirny & Shakhnovich, 1999)
+- [`Method::JensenShannon`]: Jensen–Shannon divergence between the column and a background distribution (BLOSUM62 amino acid frequencies by default)
+- [`Method::Valdar`]: Valdar (2002) weighted sum of pairs, using BLOSUM62 rescaled between 0 and 1 for amino acids and identity for other symbols, gaps scoring 0
+
+Higher scores mean more conserved columns. Scores are multiplied by the (weighted) fraction of non-gap residues unless the [`gap_penalty()`] is disabled, in which case the entropies count gaps as one more symbol, and can be smoothed over a window of neighbouring columns like Capra & Singh with [`window()`].
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::conservation::{Conservation, Method};
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"VLK-ITAN".to_vec(),
+    b"ALRYITAT".to_vec(),
+])
+.unwrap();
+
+let scores = Conservation::capra_singh().compute(&align).unwrap();
+assert_eq!(scores.len(), 8);
+// the leucine column is more conserved than the one with a gap
+assert!(scores[1] > scores[3]);
+```
+
+[`Method::ShannonEntropy`]: enum.Method.html#variant.ShannonEntropy
+[`Method::PropertyEntropy`]: enum.Method.html#variant.PropertyEntropy
+[`Method::JensenShannon`]: enum.Method.html#variant.JensenShannon
+[`Method::Valdar`]: enum.Method.html#variant.Valdar
+[`gap_penalty()`]: struct.Conservation.html#method.gap_penalty
+[`window()`]: struct.Conservation.html#method.window
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::substitution::{self, AMINO_ACIDS, BLOSUM62, BLOSUM62_BACKGROUND};
+use crate::{utils, Alignment};
+
+/// Default number of columns on each side of the scored column used by Capra & Singh
+pub const DEFAULT_WINDOW: usize = 3;
+
+/// Default weight of the neighbouring columns when smoothing over a window
+pub const DEFAULT_LAMBDA: f64 = 0.5;
+
+/// Pseudocount added to the frequency of each symbol
+const PSEUDOCOUNT: f64 = 1e-6;
+
+/// Classes of amino acids of the property entropy, as grouped by Capra & Singh
+const PROPERTY_CLASSES: [&[u8]; 6] = [b"AVLIMC", b"FWYH", b"STNQ", b"KR", b"DE", b"GP"];
+
+/// Conservation score
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Method {
+    /// One minus the normalised Shannon entropy of the column
+    ShannonEntropy,
+    /// One minus the normalised Shannon entropy of the classes of amino acids of the column
+    PropertyEntropy,
+    /// Jensen–Shannon divergence from the background distribution
+    JensenShannon,
+    /// Valdar's weighted sum of pairs of residue similarities
+    Valdar,
+}
+
+/// Computes a conservation score per column
+#[derive(Clone, PartialEq, Debug)]
+pub struct Conservation {
+    /// Score computed for each column
+    method: Method,
+    /// Multiply the scores by the fraction of non-gap residues
+    gap_penalty: bool,
+    /// Number of columns on each side used for smoothing, 0 for none
+    window: usize,
+    /// Weight of the neighbouring columns when smoothing
+    lambda: f64,
+    /// Symbols and their background frequencies, `None` for the BLOSUM62 amino acids
+    background: Option<Vec<(u8, f64)>>,
+    /// Weight of each sequence, `None` for equal weights
+    weights: Option<Vec<f64>>,
+}
+
+impl Conservation {
+    /// Returns a `Conservation` computing `method` with a gap penalty and no smoothing
+    #[must_use]
+    pub const fn new(method: Method) -> Self {
+        Self {
+            method,
+            gap_penalty: true,
+            window: 0,
+            lambda: DEFAULT_LAMBDA,
+            background: None,
+            weights: None,
+        }
+    }
+
+    /// Returns the Jensen–Shannon divergence smoothed over [`DEFAULT_WINDOW`] columns on each side, as recommended by Capra & Singh
+    ///
+    /// [`DEFAULT_WINDOW`]: constant.DEFAULT_WINDOW.html
+    #[must_use]
+    pub const fn capra_singh() -> Self {
+        Self::new(Method::JensenShannon).window(DEFAULT_WINDOW)
+    }
+
+    /// Sets whether scores are multiplied by the (weighted) fraction of non-gap residues
+    #[must_use]
+    pub const fn gap_penalty(mut self, gap_penalty: bool) -> Self {
+        self.gap_penalty = gap_penalty;
+        self
+    }
+
+    /// Sets the number of columns on each side of a column whose mean score is mixed with its own, 0 to disable smoothing
+    ///
+    /// The smoothed score is `(1 - lambda) * score + lambda * mean`, the window being truncated at both ends of the alignment.
+    #[must_use]
+    pub const fn window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the weight of the neighbouring columns when smoothing, [`DEFAULT_LAMBDA`] by default
+    ///
+    /// [`DEFAULT_LAMBDA`]: constant.DEFAULT_LAMBDA.html
+    #[must_use]
+    pub const fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Sets the symbols and their background frequencies, for example `A`, `C`, `G` and `T` at 0.25 for DNA
+    ///
+    /// The symbols also make up the alphabet used by the Shannon entropy: other symbols are not counted.
+    #[must_use]
+    pub fn background(mut self, background: Vec<(u8, f64)>) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Sets the weight of each sequence
+    #[must_use]
+    pub fn weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Returns the score of each column of `alignment`, indexed like [`nth_position()`]
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of weights is different from the number of sequences, or if a weight is negative or not a finite number.
+    ///
+    /// [`nth_position()`]: ../struct.Alignment.html#method.nth_position
+    pub fn compute<T>(&self, alignment: &Alignment<T>) -> Result<Vec<f64>, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+    {
+        let weights = match &self.weights {
+            Some(weights) => {
+                utils::check_weights(weights, alignment.n_sequences)?;
+                weights.clone()
+            }
+            None => vec![1.0; alignment.n_sequences],
+        };
+        let background = self.background.clone().unwrap_or_else(|| {
+            AMINO_ACIDS
+                .iter()
+                .copied()
+                .zip(BLOSUM62_BACKGROUND.iter().copied())
+                .collect()
+        });
+        let total: f64 = weights.iter().sum();
+
+        let scores: Vec<f64> = (0..alignment.length)
+            .map(|column| {
+                let mut counts = Counts::default();
+                let residues = alignment.column(column).into_iter().flatten();
+                for (residue, &weight) in residues.zip(&weights) {
+                    counts.add(residue.clone().into(), weight);
+                }
+
+                let score = match self.method {
+                    Method::ShannonEntropy => {
+                        let bins = background.iter().map(|&(symbol, _)| counts.weight(symbol));
+                        entropy(bins, &counts, self.gap_penalty, alignment.n_sequences)
+                    }
+                    Method::PropertyEntropy => {
+                        let bins = PROPERTY_CLASSES
+                            .iter()
+                            .map(|class| class.iter().map(|&symbol| counts.weight(symbol)).sum());
+                        entropy(bins, &counts, self.gap_penalty, alignment.n_sequences)
+                    }
+                    Method::JensenShannon => jensen_shannon(&counts, total, &background),
+                    Method::Valdar => valdar(&counts, &weights),
+                };
+
+                if self.gap_penalty && total > 0.0 {
+                    score * (1.0 - counts.weights[usize::from(b'-')] / total)
+                } else {
+                    score
+                }
+            })
+            .collect();
+
+        Ok(if self.window > 0 {
+            smooth(&scores, self.window, self.lambda)
+        } else {
+            scores
+        })
+    }
+}
+
+/// Weighted counts of the symbols of a column, in uppercase, gaps being counted as `-`
+struct Counts {
+    /// Sum of the weights of the sequences with each symbol
+    weights: [f64; 256],
+    /// Sum of the squared weights of the sequences with each symbol
+    squares: [f64; 256],
+}
+
+impl Default for Counts {
+    fn default() -> Self {
+        Self {
+            weights: [0.0; 256],
+            squares: [0.0; 256],
+        }
+    }
+}
+
+impl Counts {
+    fn add(&mut self, residue: u8, weight: f64) {
+        let symbol = if utils::is_gap(residue) {
+            b'-'
+        } else {
+            residue.to_ascii_uppercase()
+        };
+        self.weights[usize::from(symbol)] += weight;
+        self.squares[usize::from(symbol)] += weight * weight;
+    }
+
+    /// Returns the sum of the weights of the sequences with `symbol`
+    fn weight(&self, symbol: u8) -> f64 {
+        self.weights[usize::from(symbol)]
+    }
+
+    /// Returns the frequencies of the `symbols`, with a pseudocount, over `n_bins` possible symbols
+    #[allow(clippy::cast_precision_loss)]
+    fn frequencies<'a, I>(
+        &'a self,
+        symbols: I,
+        total: f64,
+        n_bins: usize,
+    ) -> impl Iterator<Item = f64> + 'a
+    where
+        I: IntoIterator<Item = u8> + 'a,
+    {
+        let denominator = (n_bins as f64).mul_add(PSEUDOCOUNT, total);
+        symbols
+            .into_iter()
+            .map(move |symbol| (self.weights[usize::from(symbol)] + PSEUDOCOUNT) / denominator)
+    }
+}
+
+/// One minus the entropy of the weights of the `bins`, normalised by its maximum and clamped between 0 and 1
+///
+/// Gaps are one more bin unless they are already accounted for by the `gap_penalty`.
+#[allow(clippy::cast_precision_loss)]
+fn entropy<I>(bins: I, counts: &Counts, gap_penalty: bool, n_sequences: usize) -> f64
+where
+    I: Iterator<Item = f64>,
+{
+    let gaps = if gap_penalty {
+        None
+    } else {
+        Some(counts.weight(b'-'))
+    };
+    let bins: Vec<f64> = bins.chain(gaps).collect();
+    let total: f64 = bins.iter().sum();
+    let denominator = (bins.len() as f64).mul_add(PSEUDOCOUNT, total);
+
+    let entropy: f64 = -bins
+        .iter()
+        .map(|weight| (weight + PSEUDOCOUNT) / denominator)
+        .map(|frequency| frequency * frequency.ln())
+        .sum::<f64>();
+
+    let max_entropy = (bins.len().min(n_sequences) as f64).ln();
+    if max_entropy > 0.0 {
+        (1.0 - entropy / max_entropy).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Jensen–Shannon divergence between the frequencies of the background symbols and the background distribution
+fn jensen_shannon(counts: &Counts, total: f64, background: &[(u8, f64)]) -> f64 {
+    let symbols = background.iter().map(|&(symbol, _)| symbol);
+    let frequencies: Vec<f64> = counts
+        .frequencies(symbols, total, background.len() + 1)
+        .collect();
+    let sum: f64 = frequencies.iter().sum();
+
+    let divergence: f64 = frequencies
+        .iter()
+        .zip(background)
+        .map(|(&frequency, &(_, expected))| {
+            let frequency = frequency / sum;
+            let mean = f64::midpoint(frequency, expected);
+            let relative_entropy = |p: f64| if p > 0.0 { p * (p / mean).log2() } else { 0.0 };
+            if mean > 0.0 {
+                relative_entropy(frequency) + relative_entropy(expected)
+            } else {
+                0.0
+            }
+        })
+        .sum();
+
+    divergence / 2.0
+}
+
+/// Weighted mean similarity of all the pairs of sequences of a column
+fn valdar(counts: &Counts, weights: &[f64]) -> f64 {
+    let symbols: Vec<u8> = (0..=u8::MAX)
+        .filter(|&symbol| symbol != b'-' && counts.weights[usize::from(symbol)] > 0.0)
+        .collect();
+
+    // sum over the pairs of distinct sequences, removing the pairs of a sequence with itself
+    let mut sum = 0.0;
+    for &a in &symbols {
+        for &b in &symbols {
+            sum +=
+                counts.weights[usize::from(a)] * counts.weights[usize::from(b)] * similarity(a, b);
+        }
+        sum -= counts.squares[usize::from(a)] * similarity(a, a);
+    }
+
+    let total: f64 = weights.iter().sum();
+    let squares: f64 = weights.iter().map(|weight| weight * weight).sum();
+    let pairs = total.mul_add(total, -squares);
+    if pairs > 0.0 {
+        sum / pairs
+    } else {
+        0.0
+    }
+}
+
+/// Similarity between 0 and 1 of two residues: BLOSUM62 rescaled for amino acids, identity otherwise
+fn similarity(a: u8, b: u8) -> f64 {
+    const MIN: f64 = -4.0;
+    const MAX: f64 = 11.0;
+
+    match (
+        substitution::amino_acid_index(a),
+        substitution::amino_acid_index(b),
+    ) {
+        (Some(i), Some(j)) => (f64::from(BLOSUM62[i][j]) - MIN) / (MAX - MIN),
+        _ if a == b => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Mixes each score with the mean of the scores of up to `window` columns on each side
+#[allow(clippy::cast_precision_loss)]
+fn smooth(scores: &[f64], window: usize, lambda: f64) -> Vec<f64> {
+    (0..scores.len())
+        .map(|i| {
+            let start = i.saturating_sub(window);
+            let end = (i + window + 1).min(scores.len());
+            let neighbours = end - start - 1;
+            if neighbours == 0 {
+                return scores[i];
+            }
+            let mean = (scores[start..end].iter().sum::<f64>() - scores[i]) / neighbours as f64;
+            (1.0 - lambda).mul_add(scores[i], lambda * mean)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::assert_close_within;
+    use pretty_assertions::assert_eq;
+
+    /// Margin of the expected scores, which ignore the pseudocounts
+    const TOLERANCE: f64 = 1e-4;
+
+    fn alignment() -> Alignment<u8> {
+        Alignment::with_sequences(&[
+            b"AAWA".to_vec(),
+            b"ACWA".to_vec(),
+            b"ADW-".to_vec(),
+            b"AEWA".to_vec(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn shannon_entropy() {
+        let scores = Conservation::new(Method::ShannonEntropy)
+            .compute(&alignment())
+            .unwrap();
+
+        // gaps only count through the gap penalty
+        assert_close_within(&scores, &[1.0, 0.0, 1.0, 0.75], TOLERANCE);
+
+        let unpenalised = Conservation::new(Method::ShannonEntropy)
+            .gap_penalty(false)
+            .compute(&alignment())
+            .unwrap();
+        // the entropy of the last column is the one of three A and a gap
+        let entropy = -0.75_f64.mul_add(0.75_f64.ln(), 0.25 * 0.25_f64.ln());
+        assert_close_within(&unpenalised[3..], &[1.0 - entropy / 4_f64.ln()], TOLERANCE);
+
+        // a column of gaps would otherwise be more disordered than the maximum entropy
+        let gaps = Alignment::with_sequences(&[b"-".to_vec(), b"-".to_vec()]).unwrap();
+        let scores = Conservation::new(Method::ShannonEntropy)
+            .compute(&gaps)
+            .unwrap();
+        assert_close_within(&scores, &[0.0], TOLERANCE);
+    }
+
+    #[test]
+    fn property_entropy() {
+        let align = Alignment::with_sequences(&[
+            b"AIDF".to_vec(),
+            b"VLEW".to_vec(),
+            b"LKD-".to_vec(),
+            b"MRE-".to_vec(),
+        ])
+        .unwrap();
+
+        let scores = Conservation::new(Method::PropertyEntropy)
+            .compute(&align)
+            .unwrap();
+        // the second column is half aliphatic, half basic: an entropy of ln(2) out of ln(4)
+        assert_close_within(&scores, &[1.0, 0.5, 1.0, 0.5], TOLERANCE);
+
+        let shannon = Conservation::new(Method::ShannonEntropy)
+            .compute(&align)
+            .unwrap();
+        assert!(shannon[0] < scores[0]);
+    }
+
+    #[test]
+    fn jensen_shannon() {
+        let scores = Conservation::new(Method::JensenShannon)
+            .compute(&alignment())
+            .unwrap();
+
+        // conserved rare residues diverge more from the background
+        assert!(scores[2] > scores[0]);
+        assert!(scores[0] > scores[1]);
+        assert!(scores.iter().all(|score| (0.0..=1.0).contains(score)));
+
+        let unpenalised = Conservation::new(Method::JensenShannon)
+            .gap_penalty(false)
+            .compute(&alignment())
+            .unwrap();
+        assert_close_within(&[scores[3]], &[unpenalised[3] * 0.75], TOLERANCE);
+    }
+
+    #[test]
+    fn dna_background() {
+        let align = Alignment::with_sequences(&[b"AC".to_vec(), b"AG".to_vec()]).unwrap();
+        let background = b"ACGT".iter().map(|&symbol| (symbol, 0.25)).collect();
+
+        let scores = Conservation::new(Method::JensenShannon)
+            .background(background)
+            .compute(&align)
+            .unwrap();
+
+        // half of the divergence of a point mass from a uniform distribution over 4 symbols
+        let expected = 0.5_f64.mul_add(1.6_f64.log2(), 0.125 * 0.4_f64.log2()) + 0.375;
+        assert!(scores[0] > scores[1]);
+        assert_close_within(&scores[..1], &[expected], TOLERANCE);
+    }
+
+    #[test]
+    fn valdar() {
+        let scores = Conservation::new(Method::Valdar)
+            .gap_penalty(false)
+            .compute(&alignment())
+            .unwrap();
+
+        assert_close_within(
+            &[scores[0], scores[2], scores[3]],
+            &[8.0 / 15.0, 1.0, 4.0 / 15.0],
+            TOLERANCE,
+        );
+
+        let weighted = Conservation::new(Method::Valdar)
+            .gap_penalty(false)
+            .weights(vec![1.0, 1.0, 0.0, 1.0])
+            .compute(&alignment())
+            .unwrap();
+        assert_close_within(&weighted[3..], &[8.0 / 15.0], TOLERANCE);
+    }
+
+    #[test]
+    fn window() {
+        assert_close_within(
+            &smooth(&[1.0, 0.0, 0.0, 1.0], 1, 0.5),
+            &[0.5, 0.25, 0.25, 0.5],
+            TOLERANCE,
+        );
+        assert_close_within(&smooth(&[1.0], 3, 0.5), &[1.0], TOLERANCE);
+    }
+
+    #[test]
+    fn invalid_weights() {
+        let error = Conservation::new(Method::Valdar)
+            .weights(vec![1.0, 1.0, -1.0, 1.0])
+            .compute(&alignment())
+            .err()
+            .unwrap();
+
+        assert_eq!(error, MultiSeqAlignError::InvalidWeight { row: 2 });
+    }
+
+    #[test]
+    fn wrong_number_of_weights() {
+        let error = Conservation::new(Method::Valdar)
+            .weights(vec![1.0])
+            .compute(&alignment())
+            .err()
+            .unwrap();
+
+        let expected = MultiSeqAlignError::WrongNumberOfWeights {
+            n_sequences: 4,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+    }
+}
@@ -0,0 +1,553 @@
+/*! Evolutionary distances
+
+Pairwise distances between the sequences of an alignment, corrected for multiple substitutions by a [`Model`]:
+
+- any residues: [`Model::PDistance`], the fraction of differing sites
+- nucleotides: [`Model::JukesCantor`], [`Model::Kimura2P`], [`Model::TamuraNei`] and [`Model::LogDet`]
+- amino acids: [`Model::Poisson`] and [`Model::KimuraProtein`]
+
+Only unambiguous residues are compared (`ACGT`, `U` standing for `T`, for nucleotides, the 20 standard amino acids for proteins, anything but `N`, `X` and `?` for the p-distance). Sites with gaps or other symbols are skipped for each pair, or for all pairs with [`Deletion::Complete`].
+
+When the observed differences are too many for the model, the correction diverges: the distance is infinite and the pair is listed in [`DistanceMatrix::saturated()`]. A pair without any site to compare has a NaN distance and is listed in [`DistanceMatrix::incomparable()`].
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::distance::Model;
+let align = Alignment::with_sequences(&[
+    b"ACGTACGT".to_vec(),
+    b"ACGTACGA".to_vec(),
+    b"TGCATGCA".to_vec(),
+])
+.unwrap();
+
+let distances = align.distance_matrix(Model::JukesCantor);
+assert_eq!(distances[(0, 0)], 0.0);
+assert!((distances[(0, 1)] - 0.136_741).abs() < 1e-6);
+assert_eq!(distances.saturated(), &[(2, 0), (2, 1)]);
+assert!(distances[(2, 0)].is_infinite());
+```
+
+[`Model`]: enum.Model.html
+[`Model::PDistance`]: enum.Model.html#variant.PDistance
+[`Model::JukesCantor`]: enum.Model.html#variant.JukesCantor
+[`Model::Kimura2P`]: enum.Model.html#variant.Kimura2P
+[`Model::TamuraNei`]: enum.Model.html#variant.TamuraNei
+[`Model::LogDet`]: enum.Model.html#variant.LogDet
+[`Model::Poisson`]: enum.Model.html#variant.Poisson
+[`Model::KimuraProtein`]: enum.Model.html#variant.KimuraProtein
+[`Deletion::Complete`]: enum.Deletion.html#variant.Complete
+[`DistanceMatrix::saturated()`]: struct.DistanceMatrix.html#method.saturated
+[`DistanceMatrix::incomparable()`]: struct.DistanceMatrix.html#method.incomparable
+*/
+
+use crate::matrix::SymmetricMatrix;
+use crate::{substitution, utils, Alignment};
+use std::convert::TryFrom;
+use std::ops::Index;
+
+/// Substitution model correcting the observed differences
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Model {
+    /// Fraction of differing sites `p`, without correction
+    PDistance,
+    /// Jukes & Cantor (1969): `-3/4 ln(1 - 4/3 p)`
+    JukesCantor,
+    /// Kimura (1980) two-parameter model, with transitions `P` and transversions `Q`: `-1/2 ln(1 - 2P - Q) - 1/4 ln(1 - 2Q)`
+    Kimura2P,
+    /// Tamura & Nei (1993), with distinct purine and pyrimidine transition rates and the base frequencies of each pair
+    TamuraNei,
+    /// `LogDet` / paralinear distance (Lockhart et al. 1994, Lake 1994), robust to unequal base compositions
+    LogDet,
+    /// Poisson correction for amino acids: `-ln(1 - p)`
+    Poisson,
+    /// Kimura (1983) empirical correction for amino acids: `-ln(1 - p - 0.2 p²)`
+    KimuraProtein,
+}
+
+impl Model {
+    /// Returns `true` if the model compares nucleotides
+    #[must_use]
+    pub const fn is_nucleotide(self) -> bool {
+        matches!(
+            self,
+            Self::JukesCantor | Self::Kimura2P | Self::TamuraNei | Self::LogDet
+        )
+    }
+
+    /// Returns the state of a residue compared by the model, `None` if it is skipped
+    fn state(self, residue: u8) -> Option<u8> {
+        let residue = residue.to_ascii_uppercase();
+        if utils::is_gap(residue) {
+            return None;
+        }
+        match self {
+            Self::PDistance => Some(residue).filter(|residue| !b"NX?".contains(residue)),
+            Self::Poisson | Self::KimuraProtein => {
+                substitution::amino_acid_index(residue).and_then(|index| u8::try_from(index).ok())
+            }
+            Self::JukesCantor | Self::Kimura2P | Self::TamuraNei | Self::LogDet => match residue {
+                b'A' => Some(0),
+                b'C' => Some(1),
+                b'G' => Some(2),
+                b'T' | b'U' => Some(3),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Sites skipped when a sequence has a gap or an ambiguous residue
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Deletion {
+    /// The site is only skipped for the pairs including that sequence
+    #[default]
+    Pairwise,
+    /// The site is skipped for all pairs
+    Complete,
+}
+
+/// Computes pairwise distances
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Distance {
+    /// Substitution model
+    model: Model,
+    /// Handling of gaps and ambiguous residues
+    deletion: Deletion,
+}
+
+impl Distance {
+    /// Returns a `Distance` under `model`, with pairwise deletion
+    #[must_use]
+    pub const fn new(model: Model) -> Self {
+        Self {
+            model,
+            deletion: Deletion::Pairwise,
+        }
+    }
+
+    /// Sets whether sites with gaps or ambiguous residues are skipped for each pair or for all pairs
+    #[must_use]
+    pub const fn deletion(mut self, deletion: Deletion) -> Self {
+        self.deletion = deletion;
+        self
+    }
+
+    /// Returns the distance between every pair of sequences of `alignment`, indexed by row
+    ///
+    /// The distance of each sequence to itself is 0, whatever the model.
+    #[must_use]
+    pub fn compute<T>(&self, alignment: &Alignment<T>) -> DistanceMatrix
+    where
+        T: Clone + Into<u8>,
+    {
+        let states: Vec<Option<u8>> = alignment
+            .sequences
+            .iter()
+            .map(|residue| self.model.state(residue.clone().into()))
+            .collect();
+        let length = alignment.length;
+        let row = |i: usize| &states[i * length..(i + 1) * length];
+
+        let columns: Vec<bool> = match self.deletion {
+            Deletion::Pairwise => vec![true; length],
+            Deletion::Complete => (0..length)
+                .map(|column| (0..alignment.n_sequences).all(|i| row(i)[column].is_some()))
+                .collect(),
+        };
+
+        let mut saturated = Vec::new();
+        let mut incomparable = Vec::new();
+        let distances = SymmetricMatrix::from_fn(alignment.n_sequences, |i, j| {
+            if i == j {
+                return 0.0;
+            }
+            match self.pair(row(i), row(j), &columns) {
+                // adding 0 turns the -0 of identical sequences into 0
+                Outcome::Distance(distance) => distance + 0.0,
+                Outcome::Saturated => {
+                    saturated.push((i, j));
+                    f64::INFINITY
+                }
+                Outcome::Incomparable => {
+                    incomparable.push((i, j));
+                    f64::NAN
+                }
+            }
+        });
+
+        DistanceMatrix {
+            distances,
+            saturated,
+            incomparable,
+        }
+    }
+
+    /// Returns the distance between two sequences of states, over the selected columns
+    #[allow(clippy::cast_precision_loss)]
+    fn pair(self, a: &[Option<u8>], b: &[Option<u8>], columns: &[bool]) -> Outcome {
+        let pairs = a
+            .iter()
+            .zip(b)
+            .zip(columns)
+            .filter_map(|((&x, &y), &selected)| Some((x?, y?)).filter(|_| selected));
+
+        if self.model.is_nucleotide() {
+            let mut counts = [[0.0; 4]; 4];
+            for (x, y) in pairs {
+                counts[usize::from(x)][usize::from(y)] += 1.0;
+            }
+            let sites: f64 = counts.iter().flatten().sum();
+            if sites == 0.0 {
+                return Outcome::Incomparable;
+            }
+            for row in &mut counts {
+                for count in row.iter_mut() {
+                    *count /= sites;
+                }
+            }
+            return nucleotide_distance(self.model, &counts);
+        }
+
+        let (sites, differences) = pairs
+            .fold((0_usize, 0_usize), |(sites, differences), (x, y)| {
+                (sites + 1, differences + usize::from(x != y))
+            });
+        if sites == 0 {
+            return Outcome::Incomparable;
+        }
+        let p = differences as f64 / sites as f64;
+        match self.model {
+            Model::Poisson => corrected(1.0 - p, |x| -x.ln()),
+            Model::KimuraProtein => corrected((0.2 * p).mul_add(-p, 1.0 - p), |x| -x.ln()),
+            _ => Outcome::Distance(p),
+        }
+    }
+}
+
+/// Distance between a pair of sequences
+enum Outcome {
+    /// Corrected distance
+    Distance(f64),
+    /// Too many differences for the model
+    Saturated,
+    /// No site to compare
+    Incomparable,
+}
+
+/// Returns `correction(argument)`, or saturation when the argument of the logarithm is not positive
+fn corrected<F>(argument: f64, correction: F) -> Outcome
+where
+    F: Fn(f64) -> f64,
+{
+    if argument > 0.0 {
+        Outcome::Distance(correction(argument))
+    } else {
+        Outcome::Saturated
+    }
+}
+
+/// Returns the distance under a nucleotide model from the frequencies of the pairs of states (`ACGT`)
+fn nucleotide_distance(model: Model, frequencies: &[[f64; 4]; 4]) -> Outcome {
+    const A: usize = 0;
+    const C: usize = 1;
+    const G: usize = 2;
+    const T: usize = 3;
+
+    let purines = frequencies[A][G] + frequencies[G][A];
+    let pyrimidines = frequencies[C][T] + frequencies[T][C];
+    let identical: f64 = (0..4).map(|x| frequencies[x][x]).sum();
+    let transversions = 1.0 - identical - purines - pyrimidines;
+
+    match model {
+        Model::JukesCantor => corrected((4.0 / 3.0_f64).mul_add(identical - 1.0, 1.0), |x| {
+            -0.75 * x.ln()
+        }),
+        Model::Kimura2P => {
+            let transitions = purines + pyrimidines;
+            match (
+                2.0_f64.mul_add(-transitions, 1.0) - transversions,
+                2.0_f64.mul_add(-transversions, 1.0),
+            ) {
+                (x, y) if x > 0.0 && y > 0.0 => {
+                    Outcome::Distance((-0.5_f64).mul_add(x.ln(), -0.25 * y.ln()))
+                }
+                _ => Outcome::Saturated,
+            }
+        }
+        Model::TamuraNei => {
+            let base = |x: usize| {
+                (0..4)
+                    .map(|y| frequencies[x][y] + frequencies[y][x])
+                    .sum::<f64>()
+                    / 2.0
+            };
+            tamura_nei(
+                [base(A), base(C), base(G), base(T)],
+                purines,
+                pyrimidines,
+                transversions,
+            )
+        }
+        Model::LogDet => log_det(frequencies),
+        _ => Outcome::Distance(1.0 - identical),
+    }
+}
+
+/// Tamura & Nei (1993) distance from the base frequencies (`ACGT`) and the fractions of purine transitions, pyrimidine transitions and transversions
+fn tamura_nei(bases: [f64; 4], purines: f64, pyrimidines: f64, transversions: f64) -> Outcome {
+    let [pi_a, pi_c, pi_g, pi_t] = bases;
+    let (pi_r, pi_y) = (pi_a + pi_g, pi_c + pi_t);
+    let (ag, ct) = (pi_a * pi_g, pi_c * pi_t);
+
+    // each term is `-coefficient * ln(argument)`, vanishing with its coefficient
+    let mut terms = Vec::with_capacity(3);
+    if ag > 0.0 {
+        terms.push((
+            2.0 * ag / pi_r,
+            1.0 - pi_r * purines / (2.0 * ag) - transversions / (2.0 * pi_r),
+        ));
+    }
+    if ct > 0.0 {
+        terms.push((
+            2.0 * ct / pi_y,
+            1.0 - pi_y * pyrimidines / (2.0 * ct) - transversions / (2.0 * pi_y),
+        ));
+    }
+    if pi_r * pi_y > 0.0 {
+        let coefficient = 2.0 * (pi_r.mul_add(pi_y, -ag * pi_y / pi_r) - ct * pi_r / pi_y);
+        terms.push((coefficient, 1.0 - transversions / (2.0 * pi_r * pi_y)));
+    }
+
+    let mut distance = 0.0;
+    for (coefficient, argument) in terms {
+        if argument <= 0.0 {
+            return Outcome::Saturated;
+        }
+        distance -= coefficient * argument.ln();
+    }
+    Outcome::Distance(distance)
+}
+
+/// Paralinear `LogDet` distance: `-1/4 [ln det F - 1/2 ln(det Πx det Πy)]`, `Πx` and `Πy` being the base frequencies of each sequence
+fn log_det(frequencies: &[[f64; 4]; 4]) -> Outcome {
+    let determinant = determinant(*frequencies);
+    let rows: f64 = frequencies
+        .iter()
+        .map(|row| row.iter().sum::<f64>())
+        .product();
+    let columns: f64 = (0..4)
+        .map(|y| frequencies.iter().map(|row| row[y]).sum::<f64>())
+        .product();
+
+    if determinant <= 0.0 || rows <= 0.0 || columns <= 0.0 {
+        return Outcome::Saturated;
+    }
+    Outcome::Distance(-0.25 * 0.5_f64.mul_add(-(rows * columns).ln(), determinant.ln()))
+}
+
+/// Returns the determinant of a 4 × 4 matrix, by Gaussian elimination with partial pivoting
+fn determinant(mut matrix: [[f64; 4]; 4]) -> f64 {
+    let mut determinant = 1.0;
+    for pivot in 0..4 {
+        let best = (pivot..4)
+            .max_by(|&x, &y| matrix[x][pivot].abs().total_cmp(&matrix[y][pivot].abs()))
+            .unwrap_or(pivot);
+        if matrix[best][pivot] == 0.0 {
+            return 0.0;
+        }
+        if best != pivot {
+            matrix.swap(best, pivot);
+            determinant = -determinant;
+        }
+        determinant *= matrix[pivot][pivot];
+        for row in pivot + 1..4 {
+            let factor = matrix[row][pivot] / matrix[pivot][pivot];
+            let pivot_row = matrix[pivot];
+            for (value, pivot_value) in matrix[row].iter_mut().zip(&pivot_row).skip(pivot) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    determinant
+}
+
+/// Pairwise distances, with the pairs whose distance could not be computed
+#[derive(Clone, PartialEq, Debug)]
+pub struct DistanceMatrix {
+    /// Distance between each pair of sequences
+    distances: SymmetricMatrix<f64>,
+    /// Pairs too divergent for the model
+    saturated: Vec<(usize, usize)>,
+    /// Pairs without any site to compare
+    incomparable: Vec<(usize, usize)>,
+}
+
+impl DistanceMatrix {
+    /// Returns the distances
+    #[must_use]
+    pub const fn distances(&self) -> &SymmetricMatrix<f64> {
+        &self.distances
+    }
+
+    /// Returns the distances, dropping the lists of saturated and incomparable pairs
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn into_distances(self) -> SymmetricMatrix<f64> {
+        self.distances
+    }
+
+    /// Returns the pairs `(i, j)` (with `i > j`) too divergent for the model, whose distance is infinite
+    #[must_use]
+    pub fn saturated(&self) -> &[(usize, usize)] {
+        &self.saturated
+    }
+
+    /// Returns the pairs `(i, j)` (with `i > j`) without any site to compare, whose distance is NaN
+    #[must_use]
+    pub fn incomparable(&self) -> &[(usize, usize)] {
+        &self.incomparable
+    }
+
+    /// Returns `true` if every distance is finite
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.saturated.is_empty() && self.incomparable.is_empty()
+    }
+}
+
+impl Index<(usize, usize)> for DistanceMatrix {
+    type Output = f64;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.distances[index]
+    }
+}
+
+impl<T> Alignment<T> {
+    /// Returns the distance between every pair of sequences of `self` under `model`, with pairwise deletion, see [`Distance`] for complete deletion
+    ///
+    /// [`Distance`]: distance/struct.Distance.html
+    #[must_use]
+    pub fn distance_matrix(&self, model: Model) -> DistanceMatrix
+    where
+        T: Clone + Into<u8>,
+    {
+        Distance::new(model).compute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::assert_close;
+    use pretty_assertions::assert_eq;
+
+    fn pair(a: &[u8], b: &[u8]) -> Alignment<u8> {
+        Alignment::with_sequences(&[a.to_vec(), b.to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn nucleotide_models() {
+        let align = pair(b"ACGTACGT", b"ACGTACGA");
+        assert_close(&[align.distance_matrix(Model::PDistance)[(0, 1)]], &[0.125]);
+        assert_close(
+            &[align.distance_matrix(Model::JukesCantor)[(0, 1)]],
+            &[-0.75 * (1.0 - 0.5 / 3.0_f64).ln()],
+        );
+
+        // one transition and one transversion out of 10 sites
+        let align = pair(b"AAAAAAAAAA", b"GAAAAAAAAC");
+        assert_close(
+            &[align.distance_matrix(Model::Kimura2P)[(0, 1)]],
+            &[(-0.5_f64).mul_add(0.7_f64.ln(), -0.25 * 0.8_f64.ln())],
+        );
+    }
+
+    #[test]
+    fn tamura_nei_equal_frequencies() {
+        // equal base frequencies and transition rates: Tamura-Nei is Kimura 2P
+        let align = pair(b"AGCTAGCTAGCTAGCT", b"GATCAGCTAGCTAGCT");
+        let expected = -0.5 * 0.5_f64.ln();
+
+        assert_close(
+            &[align.distance_matrix(Model::Kimura2P)[(0, 1)]],
+            &[expected],
+        );
+        assert_close(
+            &[align.distance_matrix(Model::TamuraNei)[(0, 1)]],
+            &[expected],
+        );
+    }
+
+    #[test]
+    fn log_det() {
+        let align = pair(b"AGCTAGCTAGCTAGCT", b"GATCAGCTAGCTAGCT");
+        let distances = align.distance_matrix(Model::LogDet);
+
+        assert_close(&[distances[(0, 0)]], &[0.0]);
+        assert!(distances[(0, 1)] > 0.0);
+
+        // a sequence without some nucleotide has a singular divergence matrix with itself
+        let align = pair(b"AAAACCCC", b"AAAACCCC");
+        let distances = align.distance_matrix(Model::LogDet);
+        assert_close(&[distances[(0, 0)], distances[(1, 1)]], &[0.0, 0.0]);
+        assert!(!distances.saturated().contains(&(0, 0)));
+        assert!(!distances.saturated().contains(&(1, 1)));
+        assert_close(
+            &[determinant([
+                [2.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 3.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])],
+            &[-6.0],
+        );
+    }
+
+    #[test]
+    fn protein_models() {
+        let align = pair(b"ALKHITAN", b"ALKHITAT");
+
+        assert_close(
+            &[align.distance_matrix(Model::Poisson)[(0, 1)]],
+            &[-(0.875_f64.ln())],
+        );
+        assert_close(
+            &[align.distance_matrix(Model::KimuraProtein)[(0, 1)]],
+            &[-(0.871_875_f64.ln())],
+        );
+    }
+
+    #[test]
+    fn saturated_and_incomparable() {
+        let align = Alignment::with_sequences(&[b"AC-".to_vec(), b"CA-".to_vec(), b"--A".to_vec()])
+            .unwrap();
+        let distances = align.distance_matrix(Model::JukesCantor);
+
+        assert_eq!(distances.saturated(), &[(1, 0)]);
+        assert_eq!(distances.incomparable(), &[(2, 0), (2, 1)]);
+        assert!(distances[(0, 1)].is_infinite());
+        assert!(distances[(2, 1)].is_nan());
+        assert!(!distances.is_complete());
+        assert_close(&[distances[(2, 2)]], &[0.0]);
+    }
+
+    #[test]
+    fn deletion() {
+        let align =
+            Alignment::with_sequences(&[b"ACGTN".to_vec(), b"ACGAA".to_vec(), b"AC-AA".to_vec()])
+                .unwrap();
+
+        let pairwise = Distance::new(Model::PDistance).compute(&align);
+        assert_close(&[pairwise[(1, 0)]], &[0.25]);
+        assert_close(&[pairwise[(2, 1)]], &[0.0]);
+
+        // the third and fifth columns are skipped for all pairs
+        let complete = Distance::new(Model::PDistance)
+            .deletion(Deletion::Complete)
+            .compute(&align);
+        assert_close(&[complete[(1, 0)]], &[1.0 / 3.0]);
+        assert!(complete.is_complete());
+        assert_eq!(*complete.distances().size(), 3);
+    }
+}
@@ -4,14 +4,17 @@ use thiserror::Error;
 // TODO: manual error handling (https://stevedonovan.github.io/rust-gentle-intro/6-error-handling.html): remove dependencies
 #[derive(Debug, Error, Display, PartialEq)]
 #[non_exhaustive]
+#[allow(clippy::doc_markdown)]
 /// Errors
 pub enum MultiSeqAlignError {
-    /// Expected aligned sequences of length {expected_length}, sequences of lengths: {found_lengths:?}
+    /// Expected aligned sequences of length {expected_length}, sequences of lengths: {found_lengths:?} (identifiers: {identifiers:?})
     MultipleSequencesOfDifferentLengths {
         /// Expected length
         expected_length: usize,
         /// Found lengths
         found_lengths: Vec<usize>,
+        /// Identifiers of the sequences of different lengths, empty if the sequences are not named
+        identifiers: Vec<String>,
     },
     /// Expected new aligned sequence of length {expected_length}, found sequence of length {found_length}
     NewSequenceOfDifferentLength {
@@ -20,4 +23,154 @@ pub enum MultiSeqAlignError {
         /// Found length
         found_length: usize,
     },
+    /// Expected {n_sequences} names, one per sequence, found {found}
+    WrongNumberOfNames {
+        /// Number of sequences
+        n_sequences: usize,
+        /// Number of names
+        found: usize,
+    },
+    /// Duplicate sequence identifier {identifier}
+    DuplicateIdentifier {
+        /// Identifier used by several sequences
+        identifier: String,
+    },
+    /// Invalid {alphabet} symbols {symbols:?} at rows {rows:?} and columns {columns:?}
+    InvalidSymbols {
+        /// Name of the alphabet
+        alphabet: &'static str,
+        /// Invalid symbols
+        symbols: Vec<char>,
+        /// Row of each invalid symbol
+        rows: Vec<usize>,
+        /// Column of each invalid symbol
+        columns: Vec<usize>,
+    },
+    /// Expected {n_sequences} weights, one per sequence, found {found}
+    WrongNumberOfWeights {
+        /// Number of sequences
+        n_sequences: usize,
+        /// Number of weights
+        found: usize,
+    },
+    /// The weight of sequence {row} is negative or not a finite number
+    InvalidWeight {
+        /// Row of the sequence
+        row: usize,
+    },
+    /// Expected {n_symbols} values, one per symbol, found {found}
+    WrongNumberOfSymbols {
+        /// Number of symbols
+        n_symbols: usize,
+        /// Number of values
+        found: usize,
+    },
+    /// The distance between sequences {row} and {column} is not a finite number
+    InvalidDistance {
+        /// Row of the distance
+        row: usize,
+        /// Column of the distance
+        column: usize,
+    },
+    /// Index {index} is out of bounds for {length} rows or columns
+    IndexOutOfBounds {
+        /// Requested index
+        index: usize,
+        /// Number of rows or columns
+        length: usize,
+    },
+    /// Range {start}..{end} is invalid for {length} rows or columns
+    InvalidRange {
+        /// First index of the range
+        start: usize,
+        /// Index after the last index of the range
+        end: usize,
+        /// Number of rows or columns
+        length: usize,
+    },
+    /// The header declares {expected_n_sequences} sequences of length {expected_length}, found {found_n_sequences} sequences of length {found_length}
+    DimensionsMismatch {
+        /// Number of sequences declared in the header
+        expected_n_sequences: usize,
+        /// Length declared in the header
+        expected_length: usize,
+        /// Number of sequences found
+        found_n_sequences: usize,
+        /// Length found
+        found_length: usize,
+    },
+    /// Name {name:?} is longer than {max_length} characters
+    NameTooLong {
+        /// The name
+        name: String,
+        /// Maximum length of a name in the format
+        max_length: usize,
+    },
+    /// Expected a mask of {expected_length} positions, found {found_length}
+    WrongMaskLength {
+        /// Length of the alignment
+        expected_length: usize,
+        /// Length of the mask
+        found_length: usize,
+    },
+    /// Expected {length} column states, one per position, found {found}
+    WrongNumberOfStates {
+        /// Length of the alignment
+        length: usize,
+        /// Number of column states
+        found: usize,
+    },
+    /// Invalid checksum for {identifier}: expected {expected}, found {found}
+    InvalidChecksum {
+        /// Identifier of the sequence, or `alignment` for the checksum of the whole alignment
+        identifier: String,
+        /// Checksum declared in the file
+        expected: usize,
+        /// Checksum computed from the sequences
+        found: usize,
+    },
+    /// Invalid {format} input at line {line}: {message}
+    InvalidFormat {
+        /// Name of the format
+        format: &'static str,
+        /// Line number (starting at 1)
+        line: usize,
+        /// What went wrong
+        message: String,
+    },
+    /// Invalid {format} annotations: {message}
+    InvalidAnnotations {
+        /// Name of the format
+        format: &'static str,
+        /// What went wrong
+        message: String,
+    },
+    /// Could not detect the format of the alignment: {reason}
+    UnknownFormat {
+        /// Why no format matched
+        reason: String,
+    },
+    /// Ambiguous alignment format, could be any of {candidates:?}: {reason}
+    AmbiguousFormat {
+        /// Names of the formats matching the input
+        candidates: Vec<&'static str>,
+        /// Why the formats could not be told apart
+        reason: String,
+    },
+    /// I/O error ({kind:?}): {message}
+    Io {
+        /// Kind of I/O error
+        kind: std::io::ErrorKind,
+        /// Error message
+        message: String,
+    },
+}
+
+impl From<std::io::Error> for MultiSeqAlignError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
 }
@@ -0,0 +1,342 @@
+//! Identifiers and descriptions of the sequences
+//!
+//! Both are optional: sequences added with [`add()`] or [`with_sequences()`] are unnamed, while the readers of [`io`] name the sequences they read. Identifiers are unique within an alignment, descriptions are free text.
+//!
+//! [`add()`]: ../struct.Alignment.html#method.add
+//! [`with_sequences()`]: ../struct.Alignment.html#method.with_sequences
+//! [`io`]: ../io/index.html
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+impl<T> Alignment<T> {
+    /// Create an `Alignment` from same length vectors sequences, naming each of them with one of `identifiers`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(align.identifier(1), Some("seq2"));
+    /// assert_eq!(align.sequence_named("seq2").unwrap()[3], &b'-');
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `identifiers` is different from the number of sequences, if the sequences have different lengths (based on the first sequence), or if an identifier is used more than once.
+    pub fn with_named_sequences<S>(
+        identifiers: &[S],
+        sequences: &[Vec<T>],
+    ) -> Result<Self, MultiSeqAlignError>
+    where
+        T: Clone,
+        S: AsRef<str>,
+    {
+        if identifiers.len() != sequences.len() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: sequences.len(),
+                found: identifiers.len(),
+            });
+        }
+        utils::check_unequal_named_lengths(
+            sequences,
+            identifiers,
+            utils::first_sequence_length(sequences),
+        )?;
+
+        let mut alignment = Self::with_sequences(sequences)?;
+        alignment.set_identifiers(identifiers)?;
+
+        Ok(alignment)
+    }
+
+    /// Add a sequence named `identifier` to `self`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let mut align = Alignment::new(8);
+    ///
+    /// align.add_named("seq1", b"ALKHITAN".to_vec()).unwrap();
+    /// align.add(b"VLK-ITAN".to_vec()).unwrap();
+    ///
+    /// assert_eq!(align.index_of("seq1"), Some(0));
+    /// assert_eq!(align.identifier(1), None);
+    /// assert!(align.add_named("seq1", b"ALRYITAT".to_vec()).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the length of `sequence` is different from the one of the alignment, or if `identifier` is already used.
+    pub fn add_named<S>(
+        &mut self,
+        identifier: S,
+        sequence: Vec<T>,
+    ) -> Result<&mut Self, MultiSeqAlignError>
+    where
+        S: Into<String>,
+    {
+        let identifier = identifier.into();
+        if self.index.contains_key(&identifier) {
+            return Err(MultiSeqAlignError::DuplicateIdentifier { identifier });
+        }
+
+        self.add(sequence)?;
+        self.index.insert(identifier.clone(), self.n_sequences - 1);
+        self.identifiers[self.n_sequences - 1] = Some(identifier);
+
+        Ok(self)
+    }
+
+    /// Names all the sequences of `self`, replacing their previous identifiers
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `identifiers` is different from the number of sequences, or if an identifier is used more than once. `self` is left unchanged on error.
+    pub fn set_identifiers<S>(&mut self, identifiers: &[S]) -> Result<&mut Self, MultiSeqAlignError>
+    where
+        S: AsRef<str>,
+    {
+        if identifiers.len() != self.n_sequences {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: self.n_sequences,
+                found: identifiers.len(),
+            });
+        }
+
+        let mut index = BTreeMap::new();
+        for (i, identifier) in identifiers.iter().enumerate() {
+            if index.insert(identifier.as_ref().to_string(), i).is_some() {
+                return Err(MultiSeqAlignError::DuplicateIdentifier {
+                    identifier: identifier.as_ref().to_string(),
+                });
+            }
+        }
+
+        self.identifiers = identifiers
+            .iter()
+            .map(|identifier| Some(identifier.as_ref().to_string()))
+            .collect();
+        self.index = index;
+
+        Ok(self)
+    }
+
+    /// Sets the identifier of the sequence at `index`, `None` to leave it unnamed
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `identifier` is already used by another sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater or equal to the `n_sequences` of the Alignment.
+    pub fn set_identifier<S>(
+        &mut self,
+        index: usize,
+        identifier: Option<S>,
+    ) -> Result<&mut Self, MultiSeqAlignError>
+    where
+        S: Into<String>,
+    {
+        assert!(index < self.n_sequences);
+        let identifier = identifier.map(Into::into);
+
+        if let Some(identifier) = &identifier {
+            if self.index.get(identifier).is_some_and(|&i| i != index) {
+                return Err(MultiSeqAlignError::DuplicateIdentifier {
+                    identifier: identifier.clone(),
+                });
+            }
+            self.index.insert(identifier.clone(), index);
+        }
+
+        if let Some(previous) = std::mem::replace(&mut self.identifiers[index], identifier) {
+            if self.identifiers[index].as_ref() != Some(&previous) {
+                self.index.remove(&previous);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the description of the sequence at `index`, `None` to remove it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let mut align = Alignment::with_sequences(&[b"ALKHITAN".to_vec()]).unwrap();
+    ///
+    /// align.set_description(0, Some("first sequence"));
+    ///
+    /// assert_eq!(align.description(0), Some("first sequence"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater or equal to the `n_sequences` of the Alignment.
+    pub fn set_description<S>(&mut self, index: usize, description: Option<S>) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        assert!(index < self.n_sequences);
+        self.descriptions[index] = description.map(Into::into);
+        self
+    }
+
+    /// Returns the identifier of the sequence at `index`, `None` if it is unnamed or out of range
+    #[must_use]
+    pub fn identifier(&self, index: usize) -> Option<&str> {
+        self.identifiers.get(index)?.as_deref()
+    }
+
+    /// Returns the description of the sequence at `index`, `None` if it has none or is out of range
+    #[must_use]
+    pub fn description(&self, index: usize) -> Option<&str> {
+        self.descriptions.get(index)?.as_deref()
+    }
+
+    /// Returns an Iterator over the identifiers of the sequences, `None` for unnamed sequences
+    pub fn identifiers(&self) -> impl ExactSizeIterator<Item = Option<&str>> {
+        self.identifiers.iter().map(Option::as_deref)
+    }
+
+    /// Returns the index of the sequence named `identifier`
+    #[must_use]
+    pub fn index_of(&self, identifier: &str) -> Option<usize> {
+        self.index.get(identifier).copied()
+    }
+
+    /// Returns all amino acids / bases of the sequence named `identifier`
+    #[must_use]
+    pub fn sequence_named(&self, identifier: &str) -> Option<Vec<&T>> {
+        self.nth_sequence(self.index_of(identifier)?)
+    }
+}
+
+impl<A, S> FromIterator<(S, Vec<A>)> for Alignment<A>
+where
+    A: Clone,
+    S: Into<String>,
+{
+    /// # Panics
+    ///
+    /// Panics if sequences are of different lengths, or if an identifier is used more than once
+    fn from_iter<I: IntoIterator<Item = (S, Vec<A>)>>(iter: I) -> Self {
+        let mut identifiers = Vec::new();
+        let mut alignment: Self = iter
+            .into_iter()
+            .map(|(identifier, sequence)| {
+                identifiers.push(identifier.into());
+                sequence
+            })
+            .collect();
+
+        alignment
+            .set_identifiers(&identifiers)
+            .expect("duplicate identifier");
+        alignment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn named_sequences() {
+        let align = Alignment::with_named_sequences(
+            &["seq1", "seq2"],
+            &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            align.identifiers().collect::<Vec<_>>(),
+            [Some("seq1"), Some("seq2")]
+        );
+        assert_eq!(align.index_of("seq2"), Some(1));
+        assert_eq!(align.index_of("seq3"), None);
+        assert_eq!(align.identifier(2), None);
+        assert_eq!(align.description(0), None);
+    }
+
+    #[test]
+    fn named_sequences_errors() {
+        let error = Alignment::with_named_sequences(&["seq1"], &[b"ALK".to_vec(), b"VLK".to_vec()])
+            .err()
+            .unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfNames {
+            n_sequences: 2,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+
+        let error =
+            Alignment::with_named_sequences(&["seq1", "seq2"], &[b"ALK".to_vec(), b"VL".to_vec()])
+                .err()
+                .unwrap();
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 3,
+            found_lengths: vec![2],
+            identifiers: vec!["seq2".to_string()],
+        };
+        assert_eq!(error, expected);
+
+        let error =
+            Alignment::with_named_sequences(&["seq1", "seq1"], &[b"ALK".to_vec(), b"VLK".to_vec()])
+                .err()
+                .unwrap();
+        let expected = MultiSeqAlignError::DuplicateIdentifier {
+            identifier: "seq1".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn rename() {
+        let mut align = Alignment::with_sequences(&[b"ALK".to_vec(), b"VLK".to_vec()]).unwrap();
+
+        align.set_identifier(0, Some("seq1")).unwrap();
+        align.set_identifier(1, Some("seq2")).unwrap();
+        assert!(align.set_identifier(1, Some("seq1")).is_err());
+
+        align.set_identifier(0, Some("first")).unwrap();
+        assert_eq!(align.index_of("seq1"), None);
+        assert_eq!(align.index_of("first"), Some(0));
+
+        align.set_identifier(1, None::<String>).unwrap();
+        assert_eq!(align.index_of("seq2"), None);
+        assert_eq!(
+            align.identifiers().collect::<Vec<_>>(),
+            [Some("first"), None]
+        );
+    }
+
+    #[test]
+    fn collect_named() {
+        let align: Alignment<u8> = vec![("seq1", b"ALK".to_vec()), ("seq2", b"VLK".to_vec())]
+            .into_iter()
+            .collect();
+
+        assert_eq!(align.sequence_named("seq2").unwrap(), [&b'V', &b'L', &b'K']);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate identifier")]
+    fn collect_duplicates() {
+        let _align: Alignment<u8> = vec![("seq1", b"ALK".to_vec()), ("seq1", b"VLK".to_vec())]
+            .into_iter()
+            .collect();
+    }
+}
@@ -0,0 +1,262 @@
+/*! Pairwise identity
+
+The identity of two aligned sequences is their number of identical residues (gaps excluded, case ignored) divided by a [`Denominator`]:
+
+- [`Denominator::AlignedPositions`]: the columns where both sequences have a residue
+- [`Denominator::ShorterLength`]: the ungapped length of the shorter sequence
+- [`Denominator::AlignmentLength`]: the length of the alignment
+- [`Denominator::MeanLength`]: the mean ungapped length of both sequences
+
+The identities are fractions between 0 and 1 (0 when the denominator is 0), stored as `f32` in a [`SymmetricMatrix`] to scale to large alignments. With the `rayon` feature, the rows can be computed in parallel (see [`PairwiseIdentity::parallel()`]).
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::identity::Denominator;
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"ALKHIT--".to_vec(),
+    b"VLR-ITGT".to_vec(),
+])
+.unwrap();
+
+let identities = align.identity_matrix(Denominator::AlignmentLength);
+assert_eq!(identities[(0, 1)], 0.75);
+assert_eq!(identities[(1, 1)], 0.75);
+
+let identities = align.identity_matrix(Denominator::AlignedPositions);
+assert_eq!(identities[(1, 0)], 1.0);
+assert_eq!(identities[(2, 0)], 3.0 / 7.0);
+```
+
+[`Denominator`]: enum.Denominator.html
+[`Denominator::AlignedPositions`]: enum.Denominator.html#variant.AlignedPositions
+[`Denominator::ShorterLength`]: enum.Denominator.html#variant.ShorterLength
+[`Denominator::AlignmentLength`]: enum.Denominator.html#variant.AlignmentLength
+[`Denominator::MeanLength`]: enum.Denominator.html#variant.MeanLength
+[`SymmetricMatrix`]: ../matrix/struct.SymmetricMatrix.html
+[`PairwiseIdentity::parallel()`]: struct.PairwiseIdentity.html#method.parallel
+*/
+
+use crate::matrix::SymmetricMatrix;
+use crate::{utils, Alignment};
+
+/// Number of positions the identical residues are divided by
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Denominator {
+    /// Columns where both sequences have a residue
+    #[default]
+    AlignedPositions,
+    /// Ungapped length of the shorter sequence
+    ShorterLength,
+    /// Length of the alignment
+    AlignmentLength,
+    /// Mean ungapped length of both sequences
+    MeanLength,
+}
+
+/// Computes the identity of every pair of sequences
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PairwiseIdentity {
+    /// Denominator of the identities
+    denominator: Denominator,
+    /// Compute the rows in parallel
+    parallel: bool,
+}
+
+impl PairwiseIdentity {
+    /// Returns a `PairwiseIdentity` over the aligned positions, computed on a single thread
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            denominator: Denominator::AlignedPositions,
+            parallel: false,
+        }
+    }
+
+    /// Sets the denominator of the identities
+    #[must_use]
+    pub const fn denominator(mut self, denominator: Denominator) -> Self {
+        self.denominator = denominator;
+        self
+    }
+
+    /// Sets whether the rows of the matrix are computed in parallel, on the global `rayon` thread pool
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub const fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Returns the identity of every pair of sequences of `alignment`, indexed by row
+    #[must_use]
+    pub fn compute<T>(&self, alignment: &Alignment<T>) -> SymmetricMatrix<f32>
+    where
+        T: Clone + Into<u8>,
+    {
+        let sequences = Sequences::new(alignment);
+        let mut matrix = SymmetricMatrix::new(alignment.n_sequences, 0.0);
+        let rows = matrix.rows_mut();
+        let fill = |(i, row): (usize, &mut [f32])| {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = sequences.identity(i, j, self.denominator);
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            if self.parallel {
+                rows.into_par_iter().enumerate().for_each(fill);
+                return matrix;
+            }
+        }
+        rows.into_iter().enumerate().for_each(fill);
+        matrix
+    }
+}
+
+/// Sequences in uppercase with `-` for gaps, and their ungapped lengths
+struct Sequences {
+    /// Symbols, row after row
+    symbols: Vec<u8>,
+    /// Length of the alignment
+    length: usize,
+    /// Number of residues of each sequence
+    residues: Vec<usize>,
+}
+
+impl Sequences {
+    fn new<T>(alignment: &Alignment<T>) -> Self
+    where
+        T: Clone + Into<u8>,
+    {
+        let symbols: Vec<u8> = alignment
+            .sequences
+            .iter()
+            .map(|residue| {
+                let residue: u8 = residue.clone().into();
+                if utils::is_gap(residue) {
+                    b'-'
+                } else {
+                    residue.to_ascii_uppercase()
+                }
+            })
+            .collect();
+        let residues = (0..alignment.n_sequences)
+            .map(|row| {
+                symbols[row * alignment.length..(row + 1) * alignment.length]
+                    .iter()
+                    .filter(|&&symbol| symbol != b'-')
+                    .count()
+            })
+            .collect();
+
+        Self {
+            symbols,
+            length: alignment.length,
+            residues,
+        }
+    }
+
+    fn row(&self, i: usize) -> &[u8] {
+        &self.symbols[i * self.length..(i + 1) * self.length]
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn identity(&self, i: usize, j: usize, denominator: Denominator) -> f32 {
+        let (mut identical, mut aligned) = (0_usize, 0_usize);
+        for (&a, &b) in self.row(i).iter().zip(self.row(j)) {
+            if a != b'-' && b != b'-' {
+                aligned += 1;
+                identical += usize::from(a == b);
+            }
+        }
+
+        let denominator = match denominator {
+            Denominator::AlignedPositions => aligned as f32,
+            Denominator::ShorterLength => self.residues[i].min(self.residues[j]) as f32,
+            Denominator::AlignmentLength => self.length as f32,
+            Denominator::MeanLength => (self.residues[i] + self.residues[j]) as f32 / 2.0,
+        };
+        if denominator > 0.0 {
+            identical as f32 / denominator
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<T> Alignment<T> {
+    /// Returns the identity of every pair of sequences of `self` over `denominator`, see [`PairwiseIdentity`] to compute them in parallel
+    ///
+    /// [`PairwiseIdentity`]: identity/struct.PairwiseIdentity.html
+    #[must_use]
+    pub fn identity_matrix(&self, denominator: Denominator) -> SymmetricMatrix<f32>
+    where
+        T: Clone + Into<u8>,
+    {
+        PairwiseIdentity::new()
+            .denominator(denominator)
+            .compute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn alignment() -> Alignment<u8> {
+        Alignment::with_sequences(&[
+            b"ACGTACGT".to_vec(),
+            b"acgtac--".to_vec(),
+            b"A-GAAC-T".to_vec(),
+            b"--------".to_vec(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn denominators() {
+        let align = alignment();
+
+        // sequences 0 and 2: 5 identical residues, 6 aligned, lengths 8 and 6
+        let expected = [
+            (Denominator::AlignedPositions, 5.0 / 6.0),
+            (Denominator::ShorterLength, 5.0 / 6.0),
+            (Denominator::AlignmentLength, 5.0 / 8.0),
+            (Denominator::MeanLength, 5.0 / 7.0),
+        ];
+        for &(denominator, identity) in &expected {
+            let matrix = align.identity_matrix(denominator);
+            assert!((matrix[(0, 2)] - identity).abs() < 1e-6);
+        }
+
+        // sequences 1 and 2: 4 identical residues, 5 aligned, lengths 6 and 6
+        let matrix = align.identity_matrix(Denominator::ShorterLength);
+        assert!((matrix[(2, 1)] - 4.0 / 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gaps_only_sequence() {
+        let matrix = alignment().identity_matrix(Denominator::MeanLength);
+
+        assert_eq!(matrix.row(3).copied().collect::<Vec<_>>(), vec![0.0; 4]);
+        assert_eq!(*matrix.size(), 4);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let align = alignment();
+
+        for &denominator in &[Denominator::AlignedPositions, Denominator::MeanLength] {
+            let identity = PairwiseIdentity::new().denominator(denominator);
+            assert_eq!(
+                identity.parallel(true).compute(&align),
+                identity.compute(&align)
+            );
+        }
+    }
+}
@@ -0,0 +1,556 @@
+/*! A2M and A3M formats
+
+Used by HMMER and HH-suite, these FASTA-like formats distinguish match states from insert states:
+
+- upper case residues and `-` are match states, the aligned columns of the alignment
+- lower case residues are insertions relative to the match states, they are not aligned
+- `.` are gaps in front of insertions of other sequences (A2M only)
+
+Any other symbol, like a `*` stop codon, is rejected.
+
+In A2M, all the sequences have the same length. In A3M, the gaps in front of insertions are omitted, so only the number of match states is the same in all sequences.
+
+```text
+>seq1
+ALKHITAN
+>seq2
+VLK-ItqTAN
+```
+
+When reading, insert states can either be dropped, leaving only the match columns, or expanded into columns padded with `.`, as in A2M. The state of each column is returned along with the alignment.
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "A3M";
+
+/// What to do with insert states when reading
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum InsertStates {
+    /// Keep only the match columns
+    Drop,
+    /// Keep insertions in their own columns, padded with `.` in the other sequences
+    Expand,
+}
+
+/// State of a column of the alignment
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ColumnState {
+    /// Aligned column
+    Match,
+    /// Column of insertions
+    Insert,
+}
+
+/// Flavour of the format when writing
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Dialect {
+    /// Gaps in insert columns are written as `.`
+    A2m,
+    /// Gaps in insert columns are omitted
+    A3m,
+}
+
+/// Reads A2M and A3M files
+///
+/// By default, insert states are dropped. Lines starting with `#` before the first sequence (like the HH-suite cardinality line) are ignored.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Reader {
+    /// What to do with insert states
+    insert_states: InsertStates,
+}
+
+impl Default for Reader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reader {
+    /// Returns a `Reader` dropping insert states
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            insert_states: InsertStates::Drop,
+        }
+    }
+
+    /// Sets whether insert states are dropped or expanded into their own columns
+    #[must_use]
+    pub const fn insert_states(mut self, insert_states: InsertStates) -> Self {
+        self.insert_states = insert_states;
+        self
+    }
+
+    /// Reads an A2M or A3M file into an `Alignment<u8>`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader` fails, if the input does not start with a header line, if a sequence contains symbols other than letters, `-` and `.`, if the sequences have different numbers of match states, or if an identifier is used more than once.
+    pub fn read<R>(&self, reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+    where
+        R: BufRead,
+    {
+        self.read_with_states(reader)
+            .map(|(_, alignment, _)| alignment)
+    }
+
+    /// Reads an A2M or A3M file, returning the header lines and the state of each column along with the `Alignment<u8>`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::io::a3m::{self, ColumnState, InsertStates};
+    /// let input = b">seq1\nALKHITAN\n>seq2\nVLK-ItqTAN\n>seq3\nALKHIsTAN\n";
+    ///
+    /// let (_, align, states) = a3m::Reader::new().read_with_states(&input[..]).unwrap();
+    /// assert_eq!(align.nth_sequence(1).unwrap(), [&b'V', &b'L', &b'K', &b'-', &b'I', &b'T', &b'A', &b'N']);
+    /// assert!(states.iter().all(|&state| state == ColumnState::Match));
+    ///
+    /// let (_, align, states) = a3m::Reader::new()
+    ///     .insert_states(InsertStates::Expand)
+    ///     .read_with_states(&input[..])
+    ///     .unwrap();
+    /// assert_eq!(align.nth_sequence(0).unwrap(), [&b'A', &b'L', &b'K', &b'H', &b'I', &b'.', &b'.', &b'T', &b'A', &b'N']);
+    /// assert_eq!(align.nth_sequence(2).unwrap(), [&b'A', &b'L', &b'K', &b'H', &b'I', &b's', &b'.', &b'T', &b'A', &b'N']);
+    /// assert_eq!(states[5], ColumnState::Insert);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader` fails, if the input does not start with a header line, if a sequence contains symbols other than letters, `-` and `.`, if the sequences have different numbers of match states, or if an identifier is used more than once.
+    #[allow(clippy::type_complexity)]
+    pub fn read_with_states<R>(
+        &self,
+        mut reader: R,
+    ) -> Result<(Vec<String>, Alignment<u8>, Vec<ColumnState>), MultiSeqAlignError>
+    where
+        R: BufRead,
+    {
+        skip_comments(&mut reader)?;
+        let (headers, sequences) = super::fasta::read_records(&mut reader, FORMAT)?;
+
+        check_symbols(&sequences)?;
+        let parsed: Vec<States> = sequences.iter().map(|s| States::parse(s)).collect();
+
+        let match_states: Vec<Vec<u8>> = parsed.iter().map(|s| s.matches.clone()).collect();
+        let identifiers: Vec<&str> = headers
+            .iter()
+            .map(|header| super::fasta::split_header(header).0)
+            .collect();
+        let n_matches = utils::first_sequence_length(&match_states);
+        utils::check_unequal_named_lengths(&match_states, &identifiers, n_matches)?;
+
+        match self.insert_states {
+            InsertStates::Drop => {
+                let alignment = super::fasta::with_headers(&headers, &match_states)?;
+                Ok((headers, alignment, vec![ColumnState::Match; n_matches]))
+            }
+            InsertStates::Expand => {
+                let widths: Vec<usize> = (0..=n_matches)
+                    .map(|slot| {
+                        parsed
+                            .iter()
+                            .map(|s| s.inserts[slot].len())
+                            .max()
+                            .unwrap_or_default()
+                    })
+                    .collect();
+
+                let mut states = Vec::with_capacity(n_matches + widths.iter().sum::<usize>());
+                for (slot, &width) in widths.iter().enumerate() {
+                    states.extend(std::iter::repeat_n(ColumnState::Insert, width));
+                    if slot < n_matches {
+                        states.push(ColumnState::Match);
+                    }
+                }
+
+                let rows: Vec<Vec<u8>> = parsed
+                    .iter()
+                    .map(|sequence| sequence.expand(&widths, states.len()))
+                    .collect();
+                let alignment = super::fasta::with_headers(&headers, &rows)?;
+                Ok((headers, alignment, states))
+            }
+        }
+    }
+}
+
+/// Reads an A2M or A3M file into an `Alignment<u8>` of the match states only
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::a3m;
+/// let input = b"#2\t1\n>seq1\nALKHITAN\n>seq2\nVLK-ItqTAN\n";
+/// let align = a3m::read(&input[..]).unwrap();
+///
+/// assert_eq!(*align.length(), 8);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the input does not start with a header line, if a sequence contains symbols other than letters, `-` and `.`, if the sequences have different numbers of match states, or if an identifier is used more than once.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    Reader::new().read(reader)
+}
+
+/// Consumes the lines starting with `#` at the beginning of `reader`
+fn skip_comments<R>(reader: &mut R) -> Result<(), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut line = Vec::new();
+    while reader.fill_buf()?.first() == Some(&b'#') {
+        super::read_line(reader, &mut line)?;
+    }
+    Ok(())
+}
+
+/// Returns an error listing the symbols of `sequences` that are neither letters, `-` nor `.`, along with their row and column
+fn check_symbols(sequences: &[Vec<u8>]) -> Result<(), MultiSeqAlignError> {
+    let mut symbols = Vec::new();
+    let mut rows = Vec::new();
+    let mut columns = Vec::new();
+
+    for (row, sequence) in sequences.iter().enumerate() {
+        for (column, &symbol) in sequence.iter().enumerate() {
+            if !(symbol.is_ascii_alphabetic() || symbol == b'-' || symbol == b'.') {
+                symbols.push(char::from(symbol));
+                rows.push(row);
+                columns.push(column);
+            }
+        }
+    }
+
+    if symbols.is_empty() {
+        Ok(())
+    } else {
+        Err(MultiSeqAlignError::InvalidSymbols {
+            alphabet: FORMAT,
+            symbols,
+            rows,
+            columns,
+        })
+    }
+}
+
+/// Match states of a sequence, and the insertions before each match state (and after the last one)
+struct States {
+    matches: Vec<u8>,
+    inserts: Vec<Vec<u8>>,
+}
+
+impl States {
+    fn parse(sequence: &[u8]) -> Self {
+        let mut matches = Vec::with_capacity(sequence.len());
+        let mut inserts = vec![Vec::new()];
+
+        for &residue in sequence {
+            if residue.is_ascii_uppercase() || residue == b'-' {
+                matches.push(residue);
+                inserts.push(Vec::new());
+            } else if residue.is_ascii_lowercase() {
+                if let Some(slot) = inserts.last_mut() {
+                    slot.push(residue);
+                }
+            }
+            // '.' are only padding in front of insertions, other symbols are rejected by `check_symbols`
+        }
+
+        Self { matches, inserts }
+    }
+
+    /// Returns the sequence with the insertions padded to `widths` with `.`
+    fn expand(&self, widths: &[usize], length: usize) -> Vec<u8> {
+        let mut row = Vec::with_capacity(length);
+        for (slot, (inserts, &width)) in self.inserts.iter().zip(widths).enumerate() {
+            row.extend_from_slice(inserts);
+            row.extend(std::iter::repeat_n(b'.', width - inserts.len()));
+            if let Some(&residue) = self.matches.get(slot) {
+                row.push(residue);
+            }
+        }
+        row
+    }
+}
+
+/// Writes A2M and A3M files
+///
+/// Residues of match columns are written in upper case with `-` as gap, residues of insert columns in lower case. Gaps in insert columns are written as `.` in A2M and omitted in A3M. Each sequence is written on a single line.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Flavour of the format
+    dialect: Dialect,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` for A3M files
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            dialect: Dialect::A3m,
+        }
+    }
+
+    /// Sets the flavour of the format
+    #[must_use]
+    pub const fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Writes `alignment` to `writer` using the `states` of the columns, the header of each sequence being its identifier (or its row index) followed by its description
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::a3m::{self, ColumnState::*, Dialect};
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALK-.HI".to_vec(), b"VLKTQ-I".to_vec()],
+    /// )
+    /// .unwrap();
+    /// let states = [Match, Match, Match, Insert, Insert, Match, Match];
+    ///
+    /// let mut output = Vec::new();
+    /// a3m::Writer::new().write(&mut output, &align, &states).unwrap();
+    /// assert_eq!(output, b">seq1\nALKHI\n>seq2\nVLKtq-I\n");
+    ///
+    /// let mut output = Vec::new();
+    /// a3m::Writer::new()
+    ///     .dialect(Dialect::A2m)
+    ///     .write_with_headers(&mut output, &align, &["a", "b"], &states)
+    ///     .unwrap();
+    /// assert_eq!(output, b">a\nALK..HI\n>b\nVLKtq-I\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `states` is different from the length of the alignment, or if `writer` fails.
+    pub fn write<W, T>(
+        &self,
+        writer: W,
+        alignment: &Alignment<T>,
+        states: &[ColumnState],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_headers(writer, alignment, &super::headers(alignment), states)
+    }
+
+    /// Writes `alignment` to `writer`, using one of `headers` (without the leading `>`) per sequence and the `states` of the columns
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `headers` is different from the number of sequences, if the number of `states` is different from the length of the alignment, or if `writer` fails.
+    pub fn write_with_headers<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        headers: &[S],
+        states: &[ColumnState],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if headers.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: headers.len(),
+            });
+        }
+        if states.len() != *alignment.length() {
+            return Err(MultiSeqAlignError::WrongNumberOfStates {
+                length: *alignment.length(),
+                found: states.len(),
+            });
+        }
+
+        let mut line = Vec::with_capacity(*alignment.length());
+        for (header, sequence) in headers.iter().zip(alignment.iter_sequences()) {
+            line.clear();
+            for (residue, state) in sequence.into_iter().zip(states) {
+                let residue: u8 = residue.clone().into();
+                match (state, utils::is_gap(residue), self.dialect) {
+                    (ColumnState::Match, true, _) => line.push(b'-'),
+                    (ColumnState::Match, false, _) => line.push(residue.to_ascii_uppercase()),
+                    (ColumnState::Insert, true, Dialect::A2m) => line.push(b'.'),
+                    (ColumnState::Insert, true, Dialect::A3m) => {}
+                    (ColumnState::Insert, false, _) => line.push(residue.to_ascii_lowercase()),
+                }
+            }
+
+            writeln!(writer, ">{}", header.as_ref())?;
+            writer.write_all(&line)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `alignment` to `writer` as A3M, all the columns being match states and the header of each sequence being its identifier (or its row index) followed by its description
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    write_with_headers(writer, alignment, &super::headers(alignment))
+}
+
+/// Writes `alignment` to `writer` as A3M, all the columns being match states, using one of `headers` (without the leading `>`) per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `headers` is different from the number of sequences, or if `writer` fails.
+pub fn write_with_headers<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    headers: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_headers(
+        writer,
+        alignment,
+        headers,
+        &vec![ColumnState::Match; *alignment.length()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const A3M: &[u8] = b"#3\t1\n>query\nMKVLAT\n>hit1 description\nMKaaVL-T\n>hit2\nMgKVLATs\n";
+
+    #[test]
+    fn read_drop_inserts() {
+        let (headers, align, states) = Reader::new().read_with_states(A3M).unwrap();
+
+        assert_eq!(headers, vec!["query", "hit1 description", "hit2"]);
+        let mut expected = Alignment::with_named_sequences(
+            &["query", "hit1", "hit2"],
+            &[b"MKVLAT".to_vec(), b"MKVL-T".to_vec(), b"MKVLAT".to_vec()],
+        )
+        .unwrap();
+        expected.set_description(1, Some("description"));
+        assert_eq!(align, expected);
+        assert_eq!(states, vec![ColumnState::Match; 6]);
+    }
+
+    #[test]
+    fn read_expand_inserts() {
+        use ColumnState::{Insert, Match};
+
+        let (_, align, states) = Reader::new()
+            .insert_states(InsertStates::Expand)
+            .read_with_states(A3M)
+            .unwrap();
+
+        assert_eq!(
+            align.iter_sequences().collect::<Vec<_>>(),
+            Alignment::with_sequences(&[
+                b"M.K..VLAT.".to_vec(),
+                b"M.KaaVL-T.".to_vec(),
+                b"MgK..VLATs".to_vec(),
+            ])
+            .unwrap()
+            .iter_sequences()
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            states,
+            vec![Match, Insert, Match, Insert, Insert, Match, Match, Match, Match, Insert]
+        );
+    }
+
+    #[test]
+    fn read_a2m() {
+        let input = b">query\nM.K..VLAT\n>hit1\nM.KaaVL-T\n";
+        let align = read(&input[..]).unwrap();
+
+        assert_eq!(align.nth_sequence(1).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn read_different_match_states() {
+        let input = b">query\nMKVLAT\n>hit1\nMKaaVL-\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 6,
+            found_lengths: vec![5],
+            identifiers: vec!["hit1".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_invalid_symbols() {
+        let input = b">query\nMKVLAT*\n>hit1\nMKaaVL-T*\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidSymbols {
+            alphabet: "A3M",
+            symbols: vec!['*', '*'],
+            rows: vec![0, 1],
+            columns: vec![6, 8],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_wrong_states() {
+        let align = Alignment::with_sequences(&[b"MKV".to_vec()]).unwrap();
+        let error = Writer::new()
+            .write(Vec::new(), &align, &[ColumnState::Match])
+            .err()
+            .unwrap();
+
+        let expected = MultiSeqAlignError::WrongNumberOfStates {
+            length: 3,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let (_, align, states) = Reader::new()
+            .insert_states(InsertStates::Expand)
+            .read_with_states(A3M)
+            .unwrap();
+
+        let mut output = Vec::new();
+        Writer::new().write(&mut output, &align, &states).unwrap();
+
+        assert_eq!(output, A3M[5..].to_vec());
+    }
+}
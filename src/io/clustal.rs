@@ -0,0 +1,585 @@
+/*! Clustal (`.aln`) format
+
+The file starts with a `CLUSTAL` header line, followed by blocks of interleaved sequences. Each sequence line contains the name of the sequence, a slice of the aligned sequence and optionally the cumulative number of residues. Each block may end with a conservation line (`*`, `:` and `.`), which is ignored on reading.
+
+```text
+CLUSTAL W (1.83) multiple sequence alignment
+
+
+seq1      ALKHI 5
+seq2      VLK-I 4
+          .** *
+
+seq1      TAN 8
+seq2      TAN 7
+          ***
+```
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "Clustal";
+
+/// Default number of residues per block
+pub const DEFAULT_BLOCK_WIDTH: usize = 60;
+
+/// Groups of amino acids with strongly similar properties, marked with `:`
+const STRONG_GROUPS: [&[u8]; 9] = [
+    b"STA", b"NEQK", b"NHQK", b"NDEQ", b"QHRK", b"MILV", b"MILF", b"HY", b"FYW",
+];
+
+/// Groups of amino acids with weakly similar properties, marked with `.`
+const WEAK_GROUPS: [&[u8]; 11] = [
+    b"CSA", b"ATV", b"SAG", b"STNK", b"STPA", b"SGND", b"SNDEQK", b"NDEQHK", b"NEQHRK", b"FVLIM",
+    b"HFY",
+];
+
+/// Reads a Clustal file into an `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::clustal;
+/// let input = b"CLUSTAL W (1.83) multiple sequence alignment
+///
+/// seq1      ALKHI 5
+/// seq2      VLK-I 4
+///           .** *
+///
+/// seq1      TAN 8
+/// seq2      TAN 7
+///           ***
+/// ";
+/// let align = clustal::read(&input[..]).unwrap();
+///
+/// assert_eq!(*align.n_sequences(), 2);
+/// assert_eq!(*align.length(), 8);
+/// assert_eq!(align.identifier(0), Some("seq1"));
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the `CLUSTAL` header is missing, or if the sequences have different lengths (based on the first sequence).
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_names(reader).map(|(_, alignment)| alignment)
+}
+
+/// Reads a Clustal file, returning the names of the sequences along with the `Alignment<u8>`
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the `CLUSTAL` header is missing, if a name appears twice in a block, or if the sequences have different lengths (based on the first sequence).
+pub fn read_with_names<R>(mut reader: R) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut names: Vec<String> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+    // names of the current block, to catch a name repeated within a block
+    let mut block: HashSet<String> = HashSet::new();
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+    let mut header_found = false;
+
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+
+        if super::is_blank(&line) {
+            block.clear();
+            continue;
+        }
+
+        if !header_found {
+            if !line.starts_with(b"CLUSTAL") {
+                return Err(MultiSeqAlignError::InvalidFormat {
+                    format: FORMAT,
+                    line: line_number,
+                    message: "expected a header line starting with 'CLUSTAL'".to_string(),
+                });
+            }
+            header_found = true;
+            continue;
+        }
+
+        // conservation lines start with spaces, sequence lines with the name of the sequence
+        if line[0].is_ascii_whitespace() {
+            block.clear();
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        let mut fields = text.split_whitespace();
+        let Some(name) = fields.next() else {
+            return Err(MultiSeqAlignError::InvalidFormat {
+                format: FORMAT,
+                line: line_number,
+                message: "expected a sequence name followed by residues".to_string(),
+            });
+        };
+        // a name without residues is an empty sequence, as written for alignments of length 0
+        let residues = fields.next().unwrap_or_default();
+        if let Some(count) = fields.next() {
+            if count.parse::<usize>().is_err() || fields.next().is_some() {
+                return Err(MultiSeqAlignError::InvalidFormat {
+                    format: FORMAT,
+                    line: line_number,
+                    message: format!("unexpected content after the residues of '{name}'"),
+                });
+            }
+        }
+
+        if !block.insert(name.to_string()) {
+            return Err(MultiSeqAlignError::DuplicateIdentifier {
+                identifier: name.to_string(),
+            });
+        }
+
+        let index = *indices.entry(name.to_string()).or_insert_with(|| {
+            names.push(name.to_string());
+            sequences.push(Vec::new());
+            names.len() - 1
+        });
+        sequences[index].extend(residues.bytes());
+    }
+
+    utils::check_unequal_named_lengths(
+        &sequences,
+        &names,
+        utils::first_sequence_length(&sequences),
+    )?;
+
+    let alignment = Alignment::with_named_sequences(&names, &sequences)?;
+
+    Ok((names, alignment))
+}
+
+/// Returns the Clustal conservation symbol of a column of residues
+///
+/// `*` if all the residues are identical, `:` if they all belong to one group of strongly similar amino acids, `.` for a group of weakly similar amino acids, and a space otherwise or if the column contains a gap.
+fn conservation_symbol(column: &[u8]) -> u8 {
+    if column.is_empty() || column.iter().any(|&residue| utils::is_gap(residue)) {
+        return b' ';
+    }
+
+    let column: Vec<u8> = column.iter().map(u8::to_ascii_uppercase).collect();
+    let in_one_group = |groups: &[&[u8]]| {
+        groups
+            .iter()
+            .any(|group| column.iter().all(|residue| group.contains(residue)))
+    };
+
+    if column.iter().all(|&residue| residue == column[0]) {
+        b'*'
+    } else if in_one_group(&STRONG_GROUPS) {
+        b':'
+    } else if in_one_group(&WEAK_GROUPS) {
+        b'.'
+    } else {
+        b' '
+    }
+}
+
+/// Returns the Clustal conservation line of `alignment`, one symbol per position
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::Alignment;
+/// # use multi_seq_align::io::clustal;
+/// let align = Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
+///
+/// assert_eq!(clustal::conservation(&align), b".** ****".to_vec());
+/// ```
+#[must_use]
+pub fn conservation<T>(alignment: &Alignment<T>) -> Vec<u8>
+where
+    T: Clone + Into<u8>,
+{
+    alignment
+        .iter_positions()
+        .map(|column| {
+            let column: Vec<u8> = column.into_iter().map(|x| x.clone().into()).collect();
+            conservation_symbol(&column)
+        })
+        .collect()
+}
+
+/// Writes Clustal files
+///
+/// Sequences are written in blocks of [`DEFAULT_BLOCK_WIDTH`] residues unless configured otherwise with [`block_width()`].
+///
+/// [`DEFAULT_BLOCK_WIDTH`]: constant.DEFAULT_BLOCK_WIDTH.html
+/// [`block_width()`]: struct.Writer.html#method.block_width
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Number of residues per block
+    block_width: usize,
+    /// Write the cumulative number of residues at the end of each line
+    residue_counts: bool,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` with blocks of [`DEFAULT_BLOCK_WIDTH`] residues and no residue counts
+    ///
+    /// [`DEFAULT_BLOCK_WIDTH`]: constant.DEFAULT_BLOCK_WIDTH.html
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            block_width: DEFAULT_BLOCK_WIDTH,
+            residue_counts: false,
+        }
+    }
+
+    /// Sets the number of residues per block, `0` writes a single block
+    #[must_use]
+    pub const fn block_width(mut self, block_width: usize) -> Self {
+        self.block_width = block_width;
+        self
+    }
+
+    /// Sets whether the cumulative number of residues is written at the end of each line
+    #[must_use]
+    pub const fn residue_counts(mut self, residue_counts: bool) -> Self {
+        self.residue_counts = residue_counts;
+        self
+    }
+
+    /// Writes `alignment` to `writer`, naming each sequence by its identifier (or its row index)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::clustal;
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// clustal::Writer::new()
+    ///     .block_width(5)
+    ///     .residue_counts(true)
+    ///     .write(&mut output, &align)
+    ///     .unwrap();
+    ///
+    /// let expected = [
+    ///     "CLUSTAL multiple sequence alignment",
+    ///     "",
+    ///     "",
+    ///     "seq1      ALKHI 5",
+    ///     "seq2      VLK-I 4",
+    ///     "          .** *",
+    ///     "",
+    ///     "seq1      TAN 8",
+    ///     "seq2      TAN 7",
+    ///     "          ***",
+    /// ];
+    /// assert_eq!(String::from_utf8(output).unwrap().lines().collect::<Vec<_>>(), expected);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer` fails.
+    pub fn write<W, T>(&self, writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_names(writer, alignment, &super::names(alignment))
+    }
+
+    /// Writes `alignment` to `writer`, using one of `names` per sequence
+    ///
+    /// Names should not contain whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `names` is different from the number of sequences, or if `writer` fails.
+    pub fn write_with_names<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        names: &[S],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if names.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: names.len(),
+            });
+        }
+
+        writeln!(writer, "CLUSTAL multiple sequence alignment\n\n")?;
+
+        let name_width = names
+            .iter()
+            .map(|name| name.as_ref().len())
+            .max()
+            .unwrap_or_default()
+            + 6;
+        let block_width = if self.block_width == 0 {
+            usize::MAX
+        } else {
+            self.block_width
+        };
+
+        let rows: Vec<Vec<u8>> = alignment
+            .iter_sequences()
+            .map(|sequence| sequence.into_iter().map(|x| x.clone().into()).collect())
+            .collect();
+        let conservation = conservation(alignment);
+        let mut counts = vec![0_usize; rows.len()];
+
+        // an alignment of length 0 is written as one empty block, to keep the names
+        let blocks: Vec<&[u8]> = if conservation.is_empty() {
+            vec![&[]]
+        } else {
+            conservation.chunks(block_width).collect()
+        };
+
+        for (block, symbols) in blocks.into_iter().enumerate() {
+            if block > 0 {
+                writeln!(writer)?;
+            }
+            let start = block * block_width;
+            let end = start + symbols.len();
+
+            for ((name, row), count) in names.iter().zip(&rows).zip(counts.iter_mut()) {
+                let residues = &row[start..end];
+                write!(writer, "{:<width$}", name.as_ref(), width = name_width)?;
+                writer.write_all(residues)?;
+                if self.residue_counts {
+                    *count += residues.iter().filter(|&&x| !utils::is_gap(x)).count();
+                    write!(writer, " {count}")?;
+                }
+                writeln!(writer)?;
+            }
+
+            write!(writer, "{:width$}", "", width = name_width)?;
+            writer.write_all(symbols)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `alignment` to `writer` in the Clustal format with the default [`Writer`] settings
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment)
+}
+
+/// Writes `alignment` to `writer` in the Clustal format with the default [`Writer`] settings, using one of `names` per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `names` is different from the number of sequences, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write_with_names<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    names: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_names(writer, alignment, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CLUSTAL_OMEGA: &[u8] = b"CLUSTAL O(1.2.4) multiple sequence alignment
+
+
+sp|P02668|CASK_BOVIN      PAPISKWQSMP
+sp|P07498|CASK_HUMAN      HAQIPQRQYLP
+sp|P02670|CASK_SHEEP      PAQILQWQVLS
+                           **  : :  .
+
+sp|P02668|CASK_BOVIN      AQE
+sp|P07498|CASK_HUMAN      AQE
+sp|P02670|CASK_SHEEP      -QE
+                           **
+";
+
+    #[test]
+    fn read_interleaved() {
+        let (names, align) = read_with_names(CLUSTAL_OMEGA).unwrap();
+
+        assert_eq!(
+            names,
+            vec![
+                "sp|P02668|CASK_BOVIN",
+                "sp|P07498|CASK_HUMAN",
+                "sp|P02670|CASK_SHEEP"
+            ]
+        );
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(
+                &names,
+                &[
+                    b"PAPISKWQSMPAQE".to_vec(),
+                    b"HAQIPQRQYLPAQE".to_vec(),
+                    b"PAQILQWQVLS-QE".to_vec(),
+                ]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn read_residue_counts_crlf() {
+        let input = b"CLUSTAL W (1.83) multiple sequence alignment\r\n\r\nseq1 ALK 3\r\nseq2 V-K 2\r\n     . *\r\n\r\nseq1 HI 5\r\nseq2 HI 4\r\n";
+        let align = read(&input[..]).unwrap();
+
+        assert_eq!(
+            align.nth_sequence(1).unwrap(),
+            vec![&b'V', &b'-', &b'K', &b'H', &b'I']
+        );
+    }
+
+    #[test]
+    fn read_missing_header() {
+        let error = read(&b"seq1 ALK\nseq2 VLK\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "Clustal",
+            line: 1,
+            message: "expected a header line starting with 'CLUSTAL'".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_different_lengths() {
+        let input = b"CLUSTAL\n\nseq1 ALK\nseq2 VLK\n\nseq1 HI\nseq2 H\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 5,
+            found_lengths: vec![4],
+            identifiers: vec!["seq2".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_invalid_count() {
+        let input = b"CLUSTAL\n\nseq1 ALK 3\nseq2 VLK three\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "Clustal",
+            line: 4,
+            message: "unexpected content after the residues of 'seq2'".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_duplicate_name() {
+        let input = b"CLUSTAL\n\nseq1 ALK\nseq2 VLK\nseq1 HIT\n\nseq1 HI\nseq2 HI\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DuplicateIdentifier {
+            identifier: "seq1".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn conservation_groups() {
+        assert_eq!(conservation_symbol(b"AAA"), b'*');
+        assert_eq!(conservation_symbol(b"aAa"), b'*');
+        assert_eq!(conservation_symbol(b"ST"), b':');
+        assert_eq!(conservation_symbol(b"ILVM"), b':');
+        assert_eq!(conservation_symbol(b"CSA"), b'.');
+        assert_eq!(conservation_symbol(b"AW"), b' ');
+        assert_eq!(conservation_symbol(b"A-"), b' ');
+        assert_eq!(conservation_symbol(b"--"), b' ');
+    }
+
+    #[test]
+    fn write_single_block() {
+        let align =
+            Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
+        let mut output = Vec::new();
+        Writer::new()
+            .block_width(0)
+            .write_with_names(&mut output, &align, &["a", "bb"])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "CLUSTAL multiple sequence alignment\n\n\na       ALKHITAN\nbb      VLK-ITAN\n        .** ****\n"
+        );
+    }
+
+    #[test]
+    fn write_empty_alignment() {
+        let align = Alignment::<u8>::with_named_sequences(&["a", "bb"], &[vec![], vec![]]).unwrap();
+        let mut output = Vec::new();
+        write(&mut output, &align).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "CLUSTAL multiple sequence alignment\n\n\na       \nbb      \n        \n"
+        );
+        assert_eq!(
+            read_with_names(&output[..]).unwrap(),
+            (vec!["a".to_string(), "bb".to_string()], align)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let (names, align) = read_with_names(CLUSTAL_OMEGA).unwrap();
+
+        let mut output = Vec::new();
+        Writer::new()
+            .block_width(4)
+            .residue_counts(true)
+            .write(&mut output, &align)
+            .unwrap();
+
+        assert_eq!(read_with_names(&output[..]).unwrap(), (names, align));
+    }
+}
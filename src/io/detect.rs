@@ -0,0 +1,620 @@
+/*! Detection of the format of alignment files
+
+Only the bytes already buffered by the reader are inspected (the capacity of a `BufReader`, 8 KiB by default), so detection never consumes any input. As the buffer may end in the middle of the last record, [`read`] reads the whole input when the buffered records are ambiguous.
+
+[`read`]: fn.read.html
+*/
+
+use super::pir::SequenceType;
+use super::{a3m, clustal, fasta, msf, nexus, phylip, pir, stockholm};
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::fmt;
+use std::io::BufRead;
+
+/// Alignment formats recognised by [`detect`]
+///
+/// [`detect`]: fn.detect.html
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Format {
+    /// Aligned FASTA, see [`fasta`](fasta/index.html)
+    Fasta,
+    /// Clustal, see [`clustal`](clustal/index.html)
+    Clustal,
+    /// Stockholm, see [`stockholm`](stockholm/index.html)
+    Stockholm,
+    /// Relaxed PHYLIP, sequential or interleaved, see [`phylip`](phylip/index.html)
+    Phylip,
+    /// Strict PHYLIP, with names of 10 characters, sequential or interleaved, see [`phylip`](phylip/index.html)
+    StrictPhylip,
+    /// NEXUS, see [`nexus`](nexus/index.html)
+    Nexus,
+    /// GCG MSF, see [`msf`](msf/index.html)
+    Msf,
+    /// A3M, see [`a3m`](a3m/index.html)
+    A3m,
+    /// PIR / NBRF, see [`pir`](pir/index.html)
+    Pir,
+}
+
+impl Format {
+    /// Returns the name of the format, as used in the errors of its reader
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fasta => "FASTA",
+            Self::Clustal => "Clustal",
+            Self::Stockholm => "Stockholm",
+            Self::Phylip | Self::StrictPhylip => "PHYLIP",
+            Self::Nexus => "NEXUS",
+            Self::Msf => "MSF",
+            Self::A3m => "A3M",
+            Self::Pir => "PIR",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Detects the format of the alignment buffered in `reader`, without consuming it
+///
+/// FASTA and A3M are told apart by the lowercase residues (insertions in A3M): the format is the one for which all the rows have the same length. The last record is only expected to be a prefix of a complete one, as the buffer may end in the middle of it.
+///
+/// PHYLIP files are read with strict and relaxed names, sequential and interleaved: the input is ambiguous if several of these readings give different alignments. When none of them succeeds, as the buffer may end in the middle of a sequence, strict PHYLIP is told apart from relaxed PHYLIP by the first sequence line, when it cannot be read with a relaxed name.
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::{self, Format};
+/// let mut input = &b"CLUSTAL W (1.83) multiple sequence alignment\n\nseq1  ALKHITAN\nseq2  VLK-ITAN\n"[..];
+/// assert_eq!(io::detect(&mut input).unwrap(), Format::Clustal);
+///
+/// let mut input = &b">seq1\nALKHITAN\n>seq2\nVLK-iITAN\n"[..];
+/// assert_eq!(io::detect(&mut input).unwrap(), Format::A3m);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the input does not look like any supported format, or if it is ambiguous.
+pub fn detect<R>(reader: &mut R) -> Result<Format, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    detect_buffer(reader.fill_buf()?, false)
+}
+
+/// Detects the format of `buffer`, the last record being complete only if `complete` is `true`
+fn detect_buffer(buffer: &[u8], complete: bool) -> Result<Format, MultiSeqAlignError> {
+    let lines: Vec<&[u8]> = lines(buffer).collect();
+
+    let first = lines
+        .iter()
+        .find(|line| !super::is_blank(line))
+        .map(|line| line.trim_ascii_start())
+        .ok_or_else(|| MultiSeqAlignError::UnknownFormat {
+            reason: "empty input".to_string(),
+        })?;
+
+    if starts_with_ignore_case(first, b"# STOCKHOLM") {
+        Ok(Format::Stockholm)
+    } else if starts_with_ignore_case(first, b"#NEXUS") {
+        Ok(Format::Nexus)
+    } else if starts_with_ignore_case(first, b"CLUSTAL") {
+        Ok(Format::Clustal)
+    } else if first.starts_with(b"!!") && contains(first, b"MULTIPLE_ALIGNMENT") {
+        Ok(Format::Msf)
+    } else if first.starts_with(b">") {
+        detect_records(&lines, complete)
+    } else if first.starts_with(b"#") && lines.iter().any(|line| line.starts_with(b">")) {
+        // only A3M files start with comment lines before the records
+        Ok(Format::A3m)
+    } else if phylip_header(first).is_some() {
+        detect_phylip(buffer)
+    } else if lines.iter().any(|line| msf_header(line)) {
+        Ok(Format::Msf)
+    } else {
+        Err(MultiSeqAlignError::UnknownFormat {
+            reason: format!(
+                "unrecognised first line '{}'",
+                String::from_utf8_lossy(first).trim_end()
+            ),
+        })
+    }
+}
+
+/// Detects the format of the alignment in `reader`, then reads it with the matching parser
+///
+/// If the buffered records are ambiguous (see [`detect`]), the whole input is read into memory to detect the format from complete records.
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::{self, Format};
+/// let input = b"# STOCKHOLM 1.0\nseq1 ALKHITAN\nseq2 VLK-ITAN\n//\n";
+/// let (align, format) = io::read(&input[..]).unwrap();
+///
+/// assert_eq!(format, Format::Stockholm);
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'-']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if the format cannot be detected (see [`detect`]), or if the parser of the detected format fails.
+///
+/// [`detect`]: fn.detect.html
+pub fn read<R>(mut reader: R) -> Result<(Alignment<u8>, Format), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    match detect(&mut reader) {
+        Ok(format) => read_format(reader, format),
+        Err(MultiSeqAlignError::AmbiguousFormat { .. }) => {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer)?;
+            let format = detect_buffer(&buffer, true)?;
+            read_format(&buffer[..], format)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads the alignment in `reader` with the parser of `format`
+fn read_format<R>(
+    mut reader: R,
+    format: Format,
+) -> Result<(Alignment<u8>, Format), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let alignment = match format {
+        Format::Fasta => fasta::read(reader),
+        Format::Clustal => clustal::read(reader),
+        Format::Stockholm => stockholm::read(reader),
+        Format::Phylip | Format::StrictPhylip => {
+            let strict = format == Format::StrictPhylip;
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer)?;
+
+            let mut readings = phylip_readings(&buffer, Some(strict));
+            match readings.len() {
+                // report the error of the most likely layout
+                0 => phylip::Reader::new()
+                    .strict(strict)
+                    .interleaved(phylip_interleaved(&buffer, strict))
+                    .read(&buffer[..]),
+                1 => Ok(readings.remove(0).2),
+                _ => Err(ambiguous_phylip(&readings)),
+            }
+        }
+        Format::Nexus => nexus::read(reader),
+        Format::Msf => msf::read(reader),
+        Format::A3m => a3m::read(reader),
+        Format::Pir => pir::read(reader),
+    }?;
+
+    Ok((alignment, format))
+}
+
+/// Tells FASTA, A3M and PIR apart from the records starting with `>`
+///
+/// The last record is only expected to be a prefix of a complete one if `complete` is `false`, as the buffer may end in the middle of it.
+fn detect_records(lines: &[&[u8]], complete: bool) -> Result<Format, MultiSeqAlignError> {
+    let mut records: Vec<Vec<u8>> = Vec::new();
+    let mut pir = true;
+    for line in lines {
+        if let Some(header) = line.strip_prefix(b">") {
+            pir &= header.get(2) == Some(&b';')
+                && SequenceType::from_code(&String::from_utf8_lossy(&header[..2])).is_some();
+            records.push(Vec::new());
+        } else if let Some(record) = records.last_mut() {
+            record.extend(line.iter().filter(|c| !c.is_ascii_whitespace()));
+        }
+    }
+
+    if pir {
+        return Ok(Format::Pir);
+    }
+    if !records.iter().flatten().any(u8::is_ascii_lowercase) {
+        return Ok(Format::Fasta);
+    }
+
+    let fasta = same_lengths(records.iter().map(Vec::len), complete);
+    let a3m = same_lengths(
+        records.iter().map(|record| {
+            record
+                .iter()
+                .filter(|&&c| c.is_ascii_uppercase() || c == b'-')
+                .count()
+        }),
+        complete,
+    );
+
+    match (fasta, a3m) {
+        (true, false) => Ok(Format::Fasta),
+        (false, true) => Ok(Format::A3m),
+        (true, true) => Err(MultiSeqAlignError::AmbiguousFormat {
+            candidates: vec![Format::Fasta.name(), Format::A3m.name()],
+            reason:
+                "the rows have the same length whether lowercase residues are insertions or not"
+                    .to_string(),
+        }),
+        (false, false) => Err(MultiSeqAlignError::UnknownFormat {
+            reason:
+                "the rows have different lengths whether lowercase residues are insertions or not"
+                    .to_string(),
+        }),
+    }
+}
+
+/// Returns `true` if all `lengths` are equal, the last one only needing to be shorter if it may be incomplete
+fn same_lengths<I>(mut lengths: I, complete: bool) -> bool
+where
+    I: DoubleEndedIterator<Item = usize>,
+{
+    let last = if complete { None } else { lengths.next_back() };
+    let mut lengths = lengths.peekable();
+    let expected = lengths.peek().copied();
+
+    lengths.all(|length| Some(length) == expected)
+        && last.is_none_or(|last| expected.is_none_or(|expected| last <= expected))
+}
+
+/// Ways of reading a PHYLIP file: strict names, interleaved sequences and their description
+const PHYLIP_READINGS: [(bool, bool, &str); 4] = [
+    (false, false, "relaxed sequential PHYLIP"),
+    (false, true, "relaxed interleaved PHYLIP"),
+    (true, false, "strict sequential PHYLIP"),
+    (true, true, "strict interleaved PHYLIP"),
+];
+
+/// Tells strict and relaxed PHYLIP apart by reading `buffer` in every way
+fn detect_phylip(buffer: &[u8]) -> Result<Format, MultiSeqAlignError> {
+    let readings = phylip_readings(buffer, None);
+    let strict = match readings.as_slice() {
+        [] => phylip_strict(buffer),
+        [(strict, _, _)] => *strict,
+        _ => return Err(ambiguous_phylip(&readings)),
+    };
+
+    Ok(if strict {
+        Format::StrictPhylip
+    } else {
+        Format::Phylip
+    })
+}
+
+/// Reads `buffer` as PHYLIP in every way, or only with strict or relaxed names if `strict` is set, keeping one reading per distinct alignment
+fn phylip_readings(
+    buffer: &[u8],
+    strict: Option<bool>,
+) -> Vec<(bool, &'static str, Alignment<u8>)> {
+    let mut readings: Vec<(bool, &'static str, Alignment<u8>)> = Vec::new();
+    for &(strict, interleaved, description) in PHYLIP_READINGS
+        .iter()
+        .filter(|(reading, _, _)| strict.is_none_or(|strict| strict == *reading))
+    {
+        if let Ok(alignment) = phylip::Reader::new()
+            .strict(strict)
+            .interleaved(interleaved)
+            .read(buffer)
+        {
+            if readings.iter().all(|(_, _, other)| *other != alignment) {
+                readings.push((strict, description, alignment));
+            }
+        }
+    }
+    readings
+}
+
+/// Returns the error of a PHYLIP file with several `readings`
+fn ambiguous_phylip(readings: &[(bool, &'static str, Alignment<u8>)]) -> MultiSeqAlignError {
+    MultiSeqAlignError::AmbiguousFormat {
+        candidates: readings
+            .iter()
+            .map(|&(_, description, _)| description)
+            .collect(),
+        reason: "the sequences depend on the length of the names or on the layout".to_string(),
+    }
+}
+
+/// Guesses whether a PHYLIP file is interleaved: the first block then holds one named line per sequence, all shorter than the alignment
+fn phylip_interleaved(buffer: &[u8], strict: bool) -> bool {
+    let mut lines = lines(buffer).filter(|line| !super::is_blank(line));
+    let Some((n_sequences, length)) = lines.next().and_then(phylip_header) else {
+        return false;
+    };
+
+    let counts: Vec<Option<usize>> = lines
+        .take(n_sequences)
+        .map(|line| {
+            let (name, residues) = phylip::split_name(line, strict);
+            let residues = residues.iter().filter(|c| !c.is_ascii_whitespace()).count();
+            (!name.is_empty() && residues > 0).then_some(residues)
+        })
+        .collect();
+
+    counts.len() == n_sequences
+        && counts
+            .iter()
+            .all(|&count| count.is_some_and(|count| count < length) && count == counts[0])
+}
+
+/// Guesses whether a PHYLIP file has strict names: the first sequence line then only makes sense with a name of 10 characters
+fn phylip_strict(buffer: &[u8]) -> bool {
+    let mut lines = lines(buffer).filter(|line| !super::is_blank(line));
+    let (Some((_, length)), Some(line)) = (lines.next().and_then(phylip_header), lines.next())
+    else {
+        return false;
+    };
+
+    // the name is followed by up to `length` residues
+    let fits = |strict: bool| {
+        let (name, residues) = phylip::split_name(line, strict);
+        let residues: Vec<u8> = residues
+            .iter()
+            .copied()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        !name.is_empty()
+            && !residues.is_empty()
+            && residues.len() <= length
+            && residues
+                .iter()
+                .all(|&c| c.is_ascii_alphabetic() || b"-.?*~".contains(&c))
+    };
+
+    !fits(false) && fits(true)
+}
+
+/// Parses the number of sequences and the length of the alignment of a PHYLIP header line, ignoring any option after them
+fn phylip_header(line: &[u8]) -> Option<(usize, usize)> {
+    let line = String::from_utf8_lossy(line);
+    let mut fields = line.split_whitespace().map(str::parse::<usize>);
+    match (fields.next(), fields.next()) {
+        (Some(Ok(n_sequences)), Some(Ok(length))) => Some((n_sequences, length)),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `line` is the `MSF:` line of an MSF header
+fn msf_header(line: &[u8]) -> bool {
+    let line = String::from_utf8_lossy(line);
+    let mut words = line.split_whitespace();
+    words.any(|word| word.eq_ignore_ascii_case("MSF:"))
+        && words.any(|word| word.eq_ignore_ascii_case("Check:"))
+}
+
+/// Splits `buffer` into lines, without the trailing `\r`
+fn lines(buffer: &[u8]) -> impl Iterator<Item = &[u8]> {
+    buffer
+        .split(|&c| c == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+fn starts_with_ignore_case(line: &[u8], prefix: &[u8]) -> bool {
+    line.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn contains(line: &[u8], pattern: &[u8]) -> bool {
+    line.windows(pattern.len()).any(|window| window == pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::BufReader;
+
+    fn detect_bytes(input: &[u8]) -> Result<Format, MultiSeqAlignError> {
+        detect(&mut &input[..])
+    }
+
+    #[test]
+    fn detect_headers() {
+        assert_eq!(
+            detect_bytes(b"\n# STOCKHOLM 1.0\n").unwrap(),
+            Format::Stockholm
+        );
+        assert_eq!(
+            detect_bytes(b"#nexus\nbegin data;\n").unwrap(),
+            Format::Nexus
+        );
+        assert_eq!(
+            detect_bytes(b"CLUSTAL O(1.2.4)\n").unwrap(),
+            Format::Clustal
+        );
+        assert_eq!(
+            detect_bytes(b"!!NA_MULTIPLE_ALIGNMENT 1.0\n").unwrap(),
+            Format::Msf
+        );
+        assert_eq!(
+            detect_bytes(b"PileUp\r\n\r\n MSF: 8  Type: P  Check: 5273  ..\r\n").unwrap(),
+            Format::Msf
+        );
+        assert_eq!(
+            detect_bytes(b" 2 8\nseq1 ALKHITAN\n").unwrap(),
+            Format::Phylip
+        );
+        assert_eq!(
+            detect_bytes(b" 2 8 I\nseq1 ALKH\nseq2 VLK-\n\nITAN\nITAN\n").unwrap(),
+            Format::Phylip
+        );
+        assert_eq!(
+            detect_bytes(b"#A3M#\n>seq1\nALKHITAN\n").unwrap(),
+            Format::A3m
+        );
+        assert_eq!(
+            detect_bytes(b">P1;seq1\n\nALKHITAN*\n").unwrap(),
+            Format::Pir
+        );
+    }
+
+    #[test]
+    fn detect_fasta_or_a3m() {
+        assert_eq!(
+            detect_bytes(b">seq1\nALKHITAN\n>seq2\nVLK-ITAN\n").unwrap(),
+            Format::Fasta
+        );
+        // soft-masked FASTA
+        assert_eq!(
+            detect_bytes(b">seq1\nacgtACGT\n>seq2\nACGTACGT\n").unwrap(),
+            Format::Fasta
+        );
+        assert_eq!(
+            detect_bytes(b">seq1\nALKhiHITAN\n>seq2\nVLK-ITAN\n>seq3\nVLK-ITAN\n").unwrap(),
+            Format::A3m
+        );
+        // the last record may be cut by the end of the buffer
+        assert_eq!(
+            detect_bytes(b">seq1\nALKhiHITAN\n>seq2\nVLK-ITAN\n>seq3\nVL").unwrap(),
+            Format::A3m
+        );
+    }
+
+    #[test]
+    fn detect_small_buffer() {
+        // the buffer ends with the first line of the second record
+        let line = |residue: &str| format!("{}\n", residue.repeat(60));
+        let record = |name: &str, first: &str| format!(">{}\n{}{}", name, line(first), line("A"));
+        let input = format!(
+            "{}{}{}",
+            record("s1", "a"),
+            record("s2", "A"),
+            record("s3", "A")
+        );
+        let capacity = 4 + 61 * 2 + 4 + 61;
+
+        let mut reader = BufReader::with_capacity(capacity, input.as_bytes());
+        assert_eq!(
+            detect(&mut reader).err().unwrap(),
+            MultiSeqAlignError::AmbiguousFormat {
+                candidates: vec!["FASTA", "A3M"],
+                reason:
+                    "the rows have the same length whether lowercase residues are insertions or not"
+                        .to_string()
+            }
+        );
+
+        let (align, format) = read(BufReader::with_capacity(capacity, input.as_bytes())).unwrap();
+        assert_eq!(format, Format::Fasta);
+        assert_eq!(*align.n_sequences(), 3);
+        assert_eq!(*align.length(), 120);
+    }
+
+    #[test]
+    fn detect_strict_phylip() {
+        let input = b"2 8\nHomo sapieALKHITAN\nBos taurusVLK-ITAN\n";
+        assert_eq!(detect_bytes(input).unwrap(), Format::StrictPhylip);
+        assert_eq!(
+            detect_bytes(b"2 8\nHomo_sapieALKHITAN\nBos_taurusVLK-ITAN\n").unwrap(),
+            Format::StrictPhylip
+        );
+        assert_eq!(
+            detect_bytes(b"2 8\nHomo_sapiens ALKHITAN\nBos_taurus VLK-ITAN\n").unwrap(),
+            Format::Phylip
+        );
+
+        let (align, format) = read(&input[..]).unwrap();
+        assert_eq!(format, Format::StrictPhylip);
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(
+                &["Homo sapie", "Bos taurus"],
+                &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn detect_ambiguous_phylip() {
+        // sequences `a` and `TT`, or `a` and `GG` interleaved
+        let input = b" 2 4\na CC\nGG\nTT\nAAAA\n";
+        let expected = MultiSeqAlignError::AmbiguousFormat {
+            candidates: vec!["relaxed sequential PHYLIP", "relaxed interleaved PHYLIP"],
+            reason: "the sequences depend on the length of the names or on the layout".to_string(),
+        };
+
+        assert_eq!(detect_bytes(input).err().unwrap(), expected);
+        assert_eq!(read(&input[..]).err().unwrap(), expected);
+    }
+
+    #[test]
+    fn detect_errors() {
+        assert_eq!(
+            detect_bytes(b"\n \n").err().unwrap(),
+            MultiSeqAlignError::UnknownFormat {
+                reason: "empty input".to_string()
+            }
+        );
+        assert_eq!(
+            detect_bytes(b"seq1 ALKHITAN\n").err().unwrap(),
+            MultiSeqAlignError::UnknownFormat {
+                reason: "unrecognised first line 'seq1 ALKHITAN'".to_string()
+            }
+        );
+        assert_eq!(
+            detect_bytes(b">seq1\nALKhITAN\n").err().unwrap(),
+            MultiSeqAlignError::AmbiguousFormat {
+                candidates: vec!["FASTA", "A3M"],
+                reason:
+                    "the rows have the same length whether lowercase residues are insertions or not"
+                        .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn phylip_layout() {
+        assert!(!phylip_interleaved(
+            b" 2 8\nseq1 ALKHITAN\nseq2 VLK-ITAN\n",
+            false
+        ));
+        assert!(!phylip_interleaved(
+            b" 2 8\nseq1 ALKH\nITAN\nseq2 VLK-\nITAN\n",
+            false
+        ));
+        assert!(phylip_interleaved(
+            b" 2 8\nseq1 ALKH\nseq2 VLK-\n\nITAN\nITAN\n",
+            false
+        ));
+        assert!(phylip_interleaved(
+            b" 2 8\nHomo sapieALKH\nBos taurusVLK-\n\nITAN\nITAN\n",
+            true
+        ));
+    }
+
+    #[test]
+    fn read_all_formats() {
+        let expected = Alignment::with_named_sequences(
+            &["seq1", "seq2"],
+            &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+        )
+        .unwrap();
+        let inputs: [(&[u8], Format); 5] = [
+            (b">seq1\nALKHITAN\n>seq2\nVLK-ITAN\n", Format::Fasta),
+            (b">seq1\nALKHITAN\n>seq2\nVLK-iITAN\n", Format::A3m),
+            (
+                b" 2 8\nseq1 ALKH\nseq2 VLK-\n\nITAN\nITAN\n",
+                Format::Phylip,
+            ),
+            (
+                b"CLUSTAL\n\nseq1  ALKHITAN\nseq2  VLK-ITAN\n",
+                Format::Clustal,
+            ),
+            (
+                b">P1;seq1\n\nALKHITAN*\n>P1;seq2\n\nVLK-ITAN*\n",
+                Format::Pir,
+            ),
+        ];
+
+        for (input, format) in &inputs {
+            assert_eq!(read(*input).unwrap(), (expected.clone(), *format));
+        }
+    }
+}
@@ -0,0 +1,587 @@
+/*! Aligned FASTA format
+
+Each record starts with a header line (`>identifier description`) followed by one or more lines of aligned sequence. Blank lines are ignored and both `\n` and `\r\n` line endings are accepted. The first word of the header is the identifier of the sequence, the rest its description.
+
+```text
+>seq1 first sequence
+ALKHITAN
+>seq2
+VLK-ITAN
+```
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{Alignment, AlignmentBuilder};
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "FASTA";
+
+/// Default number of residues per sequence line
+pub const DEFAULT_LINE_WIDTH: usize = 60;
+
+/// Reads an aligned FASTA file into an `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::fasta;
+/// let input = b">seq1\nALKHITAN\n>seq2 second sequence\nVLK-\nITAN\n";
+/// let align = fasta::read(&input[..]).unwrap();
+///
+/// assert_eq!(*align.n_sequences(), 2);
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'-']);
+/// assert_eq!(align.identifier(1), Some("seq2"));
+/// assert_eq!(align.description(1), Some("second sequence"));
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the input does not start with a header line, if the sequences have different lengths (based on the first sequence), or if an identifier is used more than once.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_headers(reader).map(|(_, alignment)| alignment)
+}
+
+/// Reads an aligned FASTA file, returning the header lines (without the leading `>`) along with the `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::fasta;
+/// let input = b">seq1 first sequence\nALKHITAN\n>seq2\nVLK-ITAN\n";
+/// let (headers, align) = fasta::read_with_headers(&input[..]).unwrap();
+///
+/// assert_eq!(headers, ["seq1 first sequence", "seq2"]);
+/// assert_eq!(*align.length(), 8);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the input does not start with a header line, if the sequences have different lengths (based on the first sequence), or if an identifier is used more than once.
+pub fn read_with_headers<R>(
+    mut reader: R,
+) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let (headers, sequences) = read_records(&mut reader, FORMAT)?;
+    let alignment = with_headers(&headers, &sequences)?;
+
+    Ok((headers, alignment))
+}
+
+/// Reads an aligned FASTA file one record at a time, returning the header lines (without the leading `>`) along with the `Alignment<u8>`
+///
+/// The residues are appended straight into the buffer of the alignment (see [`AlignmentBuilder`]), which is allocated for `n_sequences` records once the first one is read. This is meant for very large alignments: reading stops at the first sequence of a different length, which is the only one reported in the error.
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::fasta;
+/// let input = b">seq1 first sequence\nALKH\nITAN\n>seq2\nVLK-ITAN\n";
+/// let (headers, align) = fasta::read_streaming(&input[..], 2).unwrap();
+///
+/// assert_eq!(headers, ["seq1 first sequence", "seq2"]);
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'-']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the input does not start with a header line, if a sequence has a different length from the first one, or if an identifier is used more than once.
+///
+/// [`AlignmentBuilder`]: ../../struct.AlignmentBuilder.html
+pub fn read_streaming<R>(
+    mut reader: R,
+    n_sequences: usize,
+) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut headers: Vec<String> = Vec::with_capacity(n_sequences);
+    let mut builder = AlignmentBuilder::with_capacity(n_sequences);
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+
+        if let Some(header) = line.strip_prefix(b">") {
+            if !headers.is_empty() {
+                finish_record(&mut builder, &headers)?;
+            }
+            headers.push(String::from_utf8_lossy(header).trim().to_string());
+        } else if !headers.is_empty() {
+            builder.extend(line.iter().copied().filter(|c| !c.is_ascii_whitespace()));
+        } else if !super::is_blank(&line) {
+            return Err(MultiSeqAlignError::InvalidFormat {
+                format: FORMAT,
+                line: line_number,
+                message: "expected a header line starting with '>'".to_string(),
+            });
+        }
+    }
+    if !headers.is_empty() {
+        finish_record(&mut builder, &headers)?;
+    }
+
+    let mut alignment = builder.build()?;
+    let identifiers: Vec<&str> = headers
+        .iter()
+        .map(|header| split_header(header).0)
+        .collect();
+    alignment.set_identifiers(&identifiers)?;
+    describe(&mut alignment, &headers);
+
+    Ok((headers, alignment))
+}
+
+/// Finishes the record of the last of `headers`, naming it in the error if its length is different
+fn finish_record(
+    builder: &mut AlignmentBuilder<u8>,
+    headers: &[String],
+) -> Result<(), MultiSeqAlignError> {
+    match builder.finish_record() {
+        Ok(_) => Ok(()),
+        Err(MultiSeqAlignError::NewSequenceOfDifferentLength {
+            expected_length,
+            found_length,
+        }) => Err(MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length,
+            found_lengths: vec![found_length],
+            identifiers: headers
+                .last()
+                .map(|header| split_header(header).0.to_string())
+                .into_iter()
+                .collect(),
+        }),
+        Err(error) => Err(error),
+    }
+}
+
+/// Splits a header line (without the leading `>`) into the identifier, its first word, and the description, the rest if any
+pub(super) fn split_header(header: &str) -> (&str, Option<&str>) {
+    let header = header.trim();
+    match header.split_once(char::is_whitespace) {
+        Some((identifier, description)) => (identifier, Some(description.trim_start())),
+        None => (header, None),
+    }
+}
+
+/// Returns the alignment of `sequences`, named and described by their `headers`
+pub(super) fn with_headers(
+    headers: &[String],
+    sequences: &[Vec<u8>],
+) -> Result<Alignment<u8>, MultiSeqAlignError> {
+    let identifiers: Vec<&str> = headers
+        .iter()
+        .map(|header| split_header(header).0)
+        .collect();
+    let mut alignment = Alignment::with_named_sequences(&identifiers, sequences)?;
+    describe(&mut alignment, headers);
+
+    Ok(alignment)
+}
+
+/// Sets the description of each sequence of `alignment` from its header
+fn describe(alignment: &mut Alignment<u8>, headers: &[String]) {
+    for (row, header) in headers.iter().enumerate() {
+        alignment.set_description(row, split_header(header).1);
+    }
+}
+
+/// Reads the header lines and the sequences of FASTA-like `format`, without checking the sequences lengths
+pub(super) fn read_records<R>(
+    reader: &mut R,
+    format: &'static str,
+) -> Result<(Vec<String>, Vec<Vec<u8>>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut headers = Vec::new();
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+
+    while super::read_line(reader, &mut line)? {
+        line_number += 1;
+
+        if let Some(header) = line.strip_prefix(b">") {
+            headers.push(String::from_utf8_lossy(header).trim().to_string());
+            sequences.push(Vec::new());
+        } else if let Some(sequence) = sequences.last_mut() {
+            sequence.extend(line.iter().filter(|c| !c.is_ascii_whitespace()));
+        } else if !super::is_blank(&line) {
+            return Err(MultiSeqAlignError::InvalidFormat {
+                format,
+                line: line_number,
+                message: "expected a header line starting with '>'".to_string(),
+            });
+        }
+    }
+
+    Ok((headers, sequences))
+}
+
+/// Writes aligned FASTA files
+///
+/// Sequences are wrapped every [`DEFAULT_LINE_WIDTH`] residues unless configured otherwise with [`line_width()`].
+///
+/// [`DEFAULT_LINE_WIDTH`]: constant.DEFAULT_LINE_WIDTH.html
+/// [`line_width()`]: struct.Writer.html#method.line_width
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Number of residues per line, `None` to write each sequence on a single line
+    line_width: Option<usize>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` wrapping sequences every [`DEFAULT_LINE_WIDTH`] residues
+    ///
+    /// [`DEFAULT_LINE_WIDTH`]: constant.DEFAULT_LINE_WIDTH.html
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            line_width: Some(DEFAULT_LINE_WIDTH),
+        }
+    }
+
+    /// Sets the number of residues per line, `None` (or `Some(0)`) disables wrapping
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::fasta;
+    /// let mut align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+    /// )
+    /// .unwrap();
+    /// align.set_description(1, Some("second sequence"));
+    ///
+    /// let mut output = Vec::new();
+    /// fasta::Writer::new()
+    ///     .line_width(Some(5))
+    ///     .write(&mut output, &align)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, b">seq1\nALKHI\nTAN\n>seq2 second sequence\nVLK-I\nTAN\n");
+    ///
+    /// let mut output = Vec::new();
+    /// fasta::Writer::new()
+    ///     .line_width(None)
+    ///     .write_with_headers(&mut output, &align, &["a", "b"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, b">a\nALKHITAN\n>b\nVLK-ITAN\n");
+    /// ```
+    #[must_use]
+    pub const fn line_width(mut self, line_width: Option<usize>) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Writes `alignment` to `writer`, the header of each sequence being its identifier (or its row index) followed by its description
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer` fails.
+    pub fn write<W, T>(&self, writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_headers(writer, alignment, &super::headers(alignment))
+    }
+
+    /// Writes `alignment` to `writer`, using one of `headers` (without the leading `>`) per sequence
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `headers` is different from the number of sequences, or if `writer` fails.
+    pub fn write_with_headers<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        headers: &[S],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if headers.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: headers.len(),
+            });
+        }
+
+        let line_width = match self.line_width {
+            Some(width) if width > 0 => width,
+            _ => usize::MAX,
+        };
+
+        let mut residues = Vec::with_capacity(*alignment.length());
+        for (header, sequence) in headers.iter().zip(alignment.iter_sequences()) {
+            writeln!(writer, ">{}", header.as_ref())?;
+
+            residues.clear();
+            residues.extend(sequence.into_iter().map(|residue| residue.clone().into()));
+            for line in residues.chunks(line_width) {
+                writer.write_all(line)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `alignment` to `writer` as aligned FASTA with the default [`Writer`] settings
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::Alignment;
+/// # use multi_seq_align::io::fasta;
+/// let align = Alignment::with_named_sequences(
+///     &["seq1", "seq2"],
+///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+/// )
+/// .unwrap();
+///
+/// let mut output = Vec::new();
+/// fasta::write(&mut output, &align).unwrap();
+///
+/// assert_eq!(fasta::read(&output[..]).unwrap(), align);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment)
+}
+
+/// Writes `alignment` to `writer` as aligned FASTA with the default [`Writer`] settings, using one of `headers` (without the leading `>`) per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `headers` is different from the number of sequences, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write_with_headers<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    headers: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_headers(writer, alignment, headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_multi_lines() {
+        let input = b">seq1\nALKH\nITAN\n>seq2\nVLK-\nITAN\n>seq3\nALRYITAT\n";
+        let align = read(&input[..]).unwrap();
+
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(
+                &["seq1", "seq2", "seq3"],
+                &[
+                    b"ALKHITAN".to_vec(),
+                    b"VLK-ITAN".to_vec(),
+                    b"ALRYITAT".to_vec(),
+                ]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn read_blank_lines_crlf() {
+        let input = b"\r\n>seq1 desc\r\nALKH\r\n\r\nITAN\r\n\r\n>seq2\r\nVLK-ITAN";
+        let (headers, align) = read_with_headers(&input[..]).unwrap();
+
+        assert_eq!(headers, vec!["seq1 desc", "seq2"]);
+        assert_eq!(align.identifier(0), Some("seq1"));
+        assert_eq!(align.description(0), Some("desc"));
+        assert_eq!(align.description(1), None);
+        assert_eq!(align.nth_sequence(0).unwrap().len(), 8);
+        assert_eq!(align.nth_position(3).unwrap(), vec![&b'H', &b'-']);
+    }
+
+    #[test]
+    fn read_streaming_different_lengths() {
+        let input = b">seq1\nALKHITAN\n>seq2 desc\nVLK-ITAN--\n>seq3\nALRYITAT\n>seq4\nAL\n";
+        let error = read_streaming(&input[..], 4).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 8,
+            found_lengths: vec![10],
+            identifiers: vec!["seq2".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_streaming_like_read() {
+        let input =
+            b"\n>seq1\r\nALKH\r\nITAN\r\n>seq2\r\nVLK-\r\nITAN\r\n>seq3\r\n\r\nALRY ITAT\r\n";
+
+        assert_eq!(
+            read_streaming(&input[..], 0).unwrap(),
+            read_with_headers(&input[..]).unwrap()
+        );
+        assert!(read_streaming(&b""[..], 0).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn read_empty() {
+        let align = read(&b""[..]).unwrap();
+        assert!(align.is_empty());
+        assert_eq!(*align.length(), 0);
+    }
+
+    #[test]
+    fn read_different_lengths() {
+        let input = b">seq1\nALKHITAN\n>seq2 desc\nVLK-ITAN--\n>seq3\nALRYITAT\n>seq4\nAL\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 8,
+            found_lengths: vec![10, 2],
+            identifiers: vec!["seq2".to_string(), "seq4".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_duplicate_identifier() {
+        let input = b">seq1 first\nALKHITAN\n>seq1 second\nVLK-ITAN\n";
+
+        let expected = MultiSeqAlignError::DuplicateIdentifier {
+            identifier: "seq1".to_string(),
+        };
+        assert_eq!(read(&input[..]).err().unwrap(), expected);
+        assert_eq!(read_streaming(&input[..], 2).err().unwrap(), expected);
+    }
+
+    #[test]
+    fn read_missing_header() {
+        let input = b"\nALKHITAN\n>seq2\nVLK-ITAN\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "FASTA",
+            line: 2,
+            message: "expected a header line starting with '>'".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_default_width() {
+        let long = vec![b'A'; 130];
+        let align = Alignment::with_sequences(&[long.clone(), long]).unwrap();
+
+        let mut output = Vec::new();
+        write(&mut output, &align).unwrap();
+
+        let lines: Vec<usize> = output.split(|&c| c == b'\n').map(<[u8]>::len).collect();
+        assert_eq!(lines, vec![2, 60, 60, 10, 2, 60, 60, 10, 0]);
+        assert!(output.starts_with(b">0\n"));
+    }
+
+    #[test]
+    fn write_other_residue_type() {
+        #[derive(Clone)]
+        struct Residue(u8);
+        impl From<Residue> for u8 {
+            fn from(residue: Residue) -> Self {
+                residue.0
+            }
+        }
+
+        let align = Alignment::with_sequences(&[vec![Residue(b'A'), Residue(b'-')]]).unwrap();
+        let mut output = Vec::new();
+        write_with_headers(&mut output, &align, &["seq1"]).unwrap();
+
+        assert_eq!(output, b">seq1\nA-\n");
+    }
+
+    #[test]
+    fn write_wrong_number_of_headers() {
+        let align =
+            Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
+        let error = write_with_headers(Vec::new(), &align, &["seq1"])
+            .err()
+            .unwrap();
+
+        let expected = MultiSeqAlignError::WrongNumberOfNames {
+            n_sequences: 2,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = b">seq1 first sequence\nALKHI\nTAN\n>seq2\nVLK-I\nTAN\n>seq3\n-----\n---\n";
+        let align = read(&input[..]).unwrap();
+
+        let mut output = Vec::new();
+        Writer::new()
+            .line_width(Some(5))
+            .write(&mut output, &align)
+            .unwrap();
+
+        assert_eq!(output, input.to_vec());
+    }
+
+    #[test]
+    fn round_trip_no_wrap() {
+        let align = Alignment::with_named_sequences(
+            &["seq1", "seq2"],
+            &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+        )
+        .unwrap();
+        let writer = Writer::new().line_width(None);
+
+        let mut first = Vec::new();
+        writer.write(&mut first, &align).unwrap();
+        let read_back = read(&first[..]).unwrap();
+        let mut second = Vec::new();
+        writer.write(&mut second, &read_back).unwrap();
+
+        assert_eq!(read_back, align);
+        assert_eq!(first, second);
+    }
+}
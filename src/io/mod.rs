@@ -0,0 +1,94 @@
+/*! Reading and writing alignment files
+
+Each supported format lives in its own module. The format of an input can also be detected with [`detect`], or detected and read at once with [`read`].
+
+Readers name the sequences of the alignment with the identifiers found in the file (see [`Alignment::identifier()`]), along with their descriptions when the format has some. Writers use these identifiers, or the row index of unnamed sequences, unless other names are given to their `write_with_names` (or `write_with_headers`) variant.
+
+[`detect`]: fn.detect.html
+[`read`]: fn.read.html
+[`Alignment::identifier()`]: ../struct.Alignment.html#method.identifier
+*/
+
+pub mod a3m;
+pub mod clustal;
+mod detect;
+pub mod fasta;
+pub mod msf;
+pub mod nexus;
+pub mod phylip;
+pub mod pir;
+pub mod stockholm;
+
+pub use detect::{detect, read, Format};
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::io::BufRead;
+
+/// Reads the next line of `reader` into `buffer`, without the trailing `\n` or `\r\n`.
+///
+/// Returns `false` once the end of the input is reached.
+pub(crate) fn read_line<R>(reader: &mut R, buffer: &mut Vec<u8>) -> Result<bool, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    buffer.clear();
+    if reader.read_until(b'\n', buffer)? == 0 {
+        return Ok(false);
+    }
+    if buffer.last() == Some(&b'\n') {
+        buffer.pop();
+    }
+    if buffer.last() == Some(&b'\r') {
+        buffer.pop();
+    }
+    Ok(true)
+}
+
+/// Returns the name of each sequence of `alignment` to write: its identifier, or its row index
+pub(crate) fn names<T>(alignment: &Alignment<T>) -> Vec<String> {
+    alignment
+        .identifiers()
+        .enumerate()
+        .map(|(row, identifier)| identifier.map_or_else(|| row.to_string(), String::from))
+        .collect()
+}
+
+/// Returns the header of each sequence of `alignment` to write: its name (see [`names`]) followed by its description
+///
+/// [`names`]: fn.names.html
+pub(crate) fn headers<T>(alignment: &Alignment<T>) -> Vec<String> {
+    names(alignment)
+        .into_iter()
+        .enumerate()
+        .map(|(row, name)| match alignment.description(row) {
+            Some(description) => format!("{name} {description}"),
+            None => name,
+        })
+        .collect()
+}
+
+/// Returns `true` if `line` only contains whitespace
+pub(crate) fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_lines_crlf() {
+        let mut input = &b"ab\r\ncd\n\nef"[..];
+        let mut buffer = Vec::new();
+        let mut lines = Vec::new();
+        while read_line(&mut input, &mut buffer).unwrap() {
+            lines.push(buffer.clone());
+        }
+        assert_eq!(
+            lines,
+            vec![b"ab".to_vec(), b"cd".to_vec(), b"".to_vec(), b"ef".to_vec()]
+        );
+    }
+}
@@ -0,0 +1,525 @@
+/*! GCG MSF format
+
+The header declares the length and checksum of the alignment, then the name, length and checksum of each sequence. The sequences follow the `//` separator, in interleaved blocks of residues grouped by 10. Gaps are written as `.`.
+
+```text
+!!AA_MULTIPLE_ALIGNMENT 1.0
+
+ MSF: 8  Type: P  Check: 5273  ..
+
+ Name: seq1  Len: 8  Check: 2678  Weight: 1.00
+ Name: seq2  Len: 8  Check: 2595  Weight: 1.00
+
+//
+
+seq1  ALKHITAN
+seq2  VLK.ITAN
+```
+
+Checksums are validated on reading and computed on writing.
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "MSF";
+
+/// Number of residues per block when writing
+const BLOCK_WIDTH: usize = 50;
+
+/// Number of residues per group within a block when writing
+const GROUP_WIDTH: usize = 10;
+
+/// Returns the GCG checksum of a sequence
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::msf;
+/// assert_eq!(msf::checksum(b"ALKHITAN"), 2678);
+/// ```
+#[must_use]
+pub fn checksum(sequence: &[u8]) -> usize {
+    sequence
+        .iter()
+        .enumerate()
+        .map(|(index, residue)| (index % 57 + 1) * usize::from(residue.to_ascii_uppercase()))
+        .sum::<usize>()
+        % 10_000
+}
+
+/// Reads an MSF file into an `Alignment<u8>`
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the header is invalid, if a name is used more than once, if a sequence is missing from the header, if the sequences do not match the declared length, or if a checksum is invalid.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_names(reader).map(|(_, alignment)| alignment)
+}
+
+/// Reads an MSF file, returning the names of the sequences along with the `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::msf;
+/// let input = b"PileUp
+///
+///  MSF: 8  Type: P  Check: 5273  ..
+///
+///  Name: seq1  Len: 8  Check: 2678  Weight: 1.00
+///  Name: seq2  Len: 8  Check: 2595  Weight: 1.00
+///
+/// //
+///
+/// seq1  ALKH ITAN
+/// seq2  VLK. ITAN
+/// ";
+/// let (names, align) = msf::read_with_names(&input[..]).unwrap();
+///
+/// assert_eq!(names, ["seq1", "seq2"]);
+/// assert_eq!(align.identifier(1), Some("seq2"));
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'.']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the header is invalid, if a name is used more than once, if a sequence is missing from the header, if the sequences do not match the declared length, or if a checksum is invalid.
+pub fn read_with_names<R>(mut reader: R) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let invalid = |line: usize, message: &str| MultiSeqAlignError::InvalidFormat {
+        format: FORMAT,
+        line,
+        message: message.to_string(),
+    };
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+
+    // alignment length and checksum
+    let mut header: Option<(usize, usize)> = None;
+    // name, length and checksum of each sequence
+    let mut entries: Vec<(String, usize, usize)> = Vec::new();
+    let mut separator_found = false;
+
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+        let text = String::from_utf8_lossy(&line);
+
+        if text.trim() == "//" {
+            separator_found = true;
+            break;
+        }
+
+        if field(&text, "MSF:").is_some() {
+            let length = field(&text, "MSF:").and_then(|v| v.parse().ok());
+            let check = field(&text, "Check:").and_then(|v| v.parse().ok());
+            header = Some(length.zip(check).ok_or_else(|| {
+                invalid(
+                    line_number,
+                    "expected the length and checksum of the alignment",
+                )
+            })?);
+        } else if let Some(name) = field(&text, "Name:") {
+            let length = field(&text, "Len:").and_then(|v| v.parse().ok());
+            let check = field(&text, "Check:").and_then(|v| v.parse().ok());
+            let (length, check) = length.zip(check).ok_or_else(|| {
+                invalid(
+                    line_number,
+                    "expected the length and checksum of the sequence",
+                )
+            })?;
+            entries.push((name.to_string(), length, check));
+        }
+    }
+
+    let (length, check) = header.ok_or_else(|| invalid(line_number, "missing 'MSF:' line"))?;
+    if !separator_found {
+        return Err(invalid(line_number, "missing '//' separator"));
+    }
+
+    let names: Vec<String> = entries.iter().map(|(name, _, _)| name.clone()).collect();
+    let sequences = read_blocks(reader, &names, length, line_number)?;
+
+    utils::check_unequal_named_lengths(&sequences, &names, length)?;
+    if let Some((name, declared, _)) = entries.iter().find(|(_, declared, _)| *declared != length) {
+        return Err(MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: length,
+            found_lengths: vec![*declared],
+            identifiers: vec![name.clone()],
+        });
+    }
+
+    let mut total = 0_usize;
+    for ((name, _, expected), sequence) in entries.iter().zip(&sequences) {
+        let found = checksum(sequence);
+        if found != *expected {
+            return Err(MultiSeqAlignError::InvalidChecksum {
+                identifier: name.clone(),
+                expected: *expected,
+                found,
+            });
+        }
+        total += found;
+    }
+    if total % 10_000 != check {
+        return Err(MultiSeqAlignError::InvalidChecksum {
+            identifier: "alignment".to_string(),
+            expected: check,
+            found: total % 10_000,
+        });
+    }
+
+    let alignment = Alignment::with_named_sequences(&names, &sequences)?;
+
+    Ok((names, alignment))
+}
+
+/// Reads the blocks of sequences following the `//` separator at `line_number`, one per name of the header
+fn read_blocks<R>(
+    mut reader: R,
+    names: &[String],
+    length: usize,
+    mut line_number: usize,
+) -> Result<Vec<Vec<u8>>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut indices: HashMap<&str, usize> = HashMap::with_capacity(names.len());
+    for (index, name) in names.iter().enumerate() {
+        if indices.insert(name.as_str(), index).is_some() {
+            return Err(MultiSeqAlignError::DuplicateIdentifier {
+                identifier: name.clone(),
+            });
+        }
+    }
+    let mut sequences: Vec<Vec<u8>> = vec![Vec::with_capacity(length); names.len()];
+
+    let mut line = Vec::new();
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+        let text = String::from_utf8_lossy(&line);
+        let mut fields = text.split_whitespace();
+
+        let Some(name) = fields.next() else {
+            continue;
+        };
+        if let Some(&index) = indices.get(name) {
+            for residues in fields {
+                sequences[index].extend(residues.bytes());
+            }
+        } else if !text
+            .split_whitespace()
+            .all(|field| field.bytes().all(|c| c.is_ascii_digit()))
+        {
+            // only the lines of position numbers do not start with a name
+            return Err(MultiSeqAlignError::InvalidFormat {
+                format: FORMAT,
+                line: line_number,
+                message: format!("sequence {name:?} is missing from the header"),
+            });
+        }
+    }
+
+    Ok(sequences)
+}
+
+/// Returns the value following `key` (case insensitive) in `text`
+fn field<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let mut words = text.split_whitespace();
+    words.find(|word| word.eq_ignore_ascii_case(key))?;
+    words.next()
+}
+
+/// Writes `alignment` to `writer` in the MSF format, naming each sequence by its identifier (or its row index)
+///
+/// Gaps (`-` or `.`) are written as `.`. The type of the sequences (`N` or `P`) is guessed from the residues.
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::Alignment;
+/// # use multi_seq_align::io::msf;
+/// let align = Alignment::with_named_sequences(
+///     &["seq1", "seq2"],
+///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+/// )
+/// .unwrap();
+///
+/// let mut output = Vec::new();
+/// msf::write(&mut output, &align).unwrap();
+///
+/// let expected = [
+///     "!!AA_MULTIPLE_ALIGNMENT 1.0",
+///     "",
+///     " MSF: 8  Type: P  Check: 5273  ..",
+///     "",
+///     " Name: seq1  Len: 8  Check: 2678  Weight: 1.00",
+///     " Name: seq2  Len: 8  Check: 2595  Weight: 1.00",
+///     "",
+///     "//",
+///     "",
+///     "seq1  ALKHITAN",
+///     "seq2  VLK.ITAN",
+/// ];
+/// assert_eq!(String::from_utf8(output).unwrap().lines().collect::<Vec<_>>(), expected);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    write_with_names(writer, alignment, &super::names(alignment))
+}
+
+/// Writes `alignment` to `writer` in the MSF format, using one of `names` per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `names` is different from the number of sequences, or if `writer` fails.
+pub fn write_with_names<W, T, S>(
+    mut writer: W,
+    alignment: &Alignment<T>,
+    names: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    if names.len() != *alignment.n_sequences() {
+        return Err(MultiSeqAlignError::WrongNumberOfNames {
+            n_sequences: *alignment.n_sequences(),
+            found: names.len(),
+        });
+    }
+
+    let rows: Vec<Vec<u8>> = alignment
+        .iter_sequences()
+        .map(|sequence| {
+            sequence
+                .into_iter()
+                .map(|x| {
+                    let residue: u8 = x.clone().into();
+                    if utils::is_gap(residue) {
+                        b'.'
+                    } else {
+                        residue
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let checks: Vec<usize> = rows.iter().map(|row| checksum(row)).collect();
+
+    let nucleotides = rows
+        .iter()
+        .flatten()
+        .all(|&residue| residue == b'.' || b"ACGTUN".contains(&residue.to_ascii_uppercase()));
+    let (kind, code) = if nucleotides {
+        ("NA", 'N')
+    } else {
+        ("AA", 'P')
+    };
+
+    writeln!(writer, "!!{kind}_MULTIPLE_ALIGNMENT 1.0\n")?;
+    writeln!(
+        writer,
+        " MSF: {}  Type: {code}  Check: {}  ..\n",
+        alignment.length(),
+        checks.iter().sum::<usize>() % 10_000
+    )?;
+
+    let name_width = names
+        .iter()
+        .map(|name| name.as_ref().len())
+        .max()
+        .unwrap_or_default();
+    for (name, check) in names.iter().zip(&checks) {
+        writeln!(
+            writer,
+            " Name: {:<name_width$}  Len: {}  Check: {check}  Weight: 1.00",
+            name.as_ref(),
+            alignment.length()
+        )?;
+    }
+    writeln!(writer, "\n//")?;
+
+    for start in (0..*alignment.length()).step_by(BLOCK_WIDTH) {
+        let end = (start + BLOCK_WIDTH).min(*alignment.length());
+        writeln!(writer)?;
+        for (name, row) in names.iter().zip(&rows) {
+            write!(writer, "{:<name_width$} ", name.as_ref())?;
+            for group in row[start..end].chunks(GROUP_WIDTH) {
+                writer.write_all(b" ")?;
+                writer.write_all(group)?;
+            }
+            writeln!(writer)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(checksum(b""), 0);
+        assert_eq!(checksum(b"A"), 65);
+        assert_eq!(checksum(b"a"), 65);
+        // the weight of each position restarts every 57 residues
+        assert_eq!(checksum(&[b'A'; 58]), (65 * (57 * 58 / 2) + 65) % 10_000);
+    }
+
+    #[test]
+    fn read_blocks_with_positions() {
+        let input = b"!!AA_MULTIPLE_ALIGNMENT 1.0\r\n\r\n MSF: 14  Type: P  Check: 4031  ..\r\n\r\n Name: Bovine  Len: 14  Check: 8060  Weight: 1.00\r\n Name: Human  Len: 14  Check: 8085  Weight: 1.00\r\n Name: Sheep  Len: 14  Check: 7886  Weight: 1.00\r\n\r\n//\r\n\r\n       1                 \r\nBovine PAPISKWQSM PA\r\nHuman  HAQIPQRQYL PA\r\nSheep  PAQILQWQVL S.\r\n\r\nBovine QE\r\nHuman  QE\r\nSheep  QE\r\n";
+        let (names, align) = read_with_names(&input[..]).unwrap();
+
+        assert_eq!(names, vec!["Bovine", "Human", "Sheep"]);
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(
+                &names,
+                &[
+                    b"PAPISKWQSMPAQE".to_vec(),
+                    b"HAQIPQRQYLPAQE".to_vec(),
+                    b"PAQILQWQVLS.QE".to_vec(),
+                ]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn read_invalid_sequence_checksum() {
+        let input = b" MSF: 3  Check: 1  ..\n Name: a  Len: 3  Check: 1\n//\na ALK\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidChecksum {
+            identifier: "a".to_string(),
+            expected: 1,
+            found: checksum(b"ALK"),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_invalid_alignment_checksum() {
+        let input = format!(
+            " MSF: 3  Check: 1  ..\n Name: a  Len: 3  Check: {}\n//\na ALK\n",
+            checksum(b"ALK")
+        );
+        let error = read(input.as_bytes()).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidChecksum {
+            identifier: "alignment".to_string(),
+            expected: 1,
+            found: checksum(b"ALK"),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_wrong_length() {
+        let input = b" MSF: 4  Check: 1  ..\n Name: a  Len: 4  Check: 1\n//\na ALK\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 4,
+            found_lengths: vec![3],
+            identifiers: vec!["a".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_missing_separator() {
+        let input = b" MSF: 3  Check: 1  ..\n Name: a  Len: 3  Check: 1\na ALK\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "MSF",
+            line: 3,
+            message: "missing '//' separator".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_duplicate_name() {
+        let input = b" MSF: 3  Check: 1  ..\n Name: a  Len: 3  Check: 1\n Name: a  Len: 3  Check: 1\n//\na ALK\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DuplicateIdentifier {
+            identifier: "a".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_unknown_name() {
+        let input =
+            b" MSF: 3  Check: 1  ..\n Name: a  Len: 3  Check: 1\n//\n  1  3\na ALK\nb VLK\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "MSF",
+            line: 6,
+            message: "sequence \"b\" is missing from the header".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_nucleotides() {
+        let align = Alignment::with_sequences(&[b"ACGT".to_vec()]).unwrap();
+        let mut output = Vec::new();
+        write_with_names(&mut output, &align, &["dna"]).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("!!NA_MULTIPLE_ALIGNMENT 1.0\n"));
+        assert!(output.contains("Type: N"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let sequence: Vec<u8> = b"ACDEFGHIKLMNPQRSTVWY"
+            .iter()
+            .cycle()
+            .take(123)
+            .copied()
+            .collect();
+        let mut gapped = sequence.clone();
+        gapped[60] = b'-';
+        let align =
+            Alignment::with_named_sequences(&["first", "second"], &[sequence, gapped]).unwrap();
+
+        let mut output = Vec::new();
+        write(&mut output, &align).unwrap();
+        let (names, read_back) = read_with_names(&output[..]).unwrap();
+
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(read_back.nth_position(60).unwrap(), vec![&b'A', &b'.']);
+        assert_eq!(
+            read_back.nth_sequence(0).unwrap(),
+            align.nth_sequence(0).unwrap()
+        );
+    }
+}
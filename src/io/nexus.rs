@@ -0,0 +1,937 @@
+/*! NEXUS format
+
+Only the `DATA` and `CHARACTERS` blocks are read, other blocks are skipped. The `DIMENSIONS`, `FORMAT` and `MATRIX` commands are supported, with sequential or interleaved matrices. Custom gap (`GAP=`) and missing data (`MISSING=`) symbols are replaced by `-` and `?`, and match characters (`MATCHCHAR=`) by the residue of the first sequence at the same position.
+
+```text
+#NEXUS
+BEGIN DATA;
+    DIMENSIONS NTAX=2 NCHAR=8;
+    FORMAT DATATYPE=PROTEIN GAP=- MISSING=? MATCHCHAR=.;
+    MATRIX
+    seq1    ALKHITAN
+    seq2    V..-....
+    ;
+END;
+```
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+const FORMAT: &str = "NEXUS";
+
+/// Default number of residues per block when writing interleaved matrices
+pub const DEFAULT_BLOCK_WIDTH: usize = 60;
+
+/// Names of the taxa, alignment and declared type of data of a `MATRIX`
+type Matrix = (Vec<String>, Alignment<u8>, Option<DataType>);
+
+/// Type of data declared in the `FORMAT` command
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum DataType {
+    /// `DATATYPE=DNA`
+    Dna,
+    /// `DATATYPE=RNA`
+    Rna,
+    /// `DATATYPE=NUCLEOTIDE`
+    Nucleotide,
+    /// `DATATYPE=PROTEIN`
+    Protein,
+    /// `DATATYPE=STANDARD`
+    Standard,
+}
+
+impl DataType {
+    /// Returns the `DataType` of a `DATATYPE=` value, ignoring case
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "DNA" => Some(Self::Dna),
+            "RNA" => Some(Self::Rna),
+            "NUCLEOTIDE" => Some(Self::Nucleotide),
+            "PROTEIN" => Some(Self::Protein),
+            "STANDARD" => Some(Self::Standard),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dna => "DNA",
+            Self::Rna => "RNA",
+            Self::Nucleotide => "NUCLEOTIDE",
+            Self::Protein => "PROTEIN",
+            Self::Standard => "STANDARD",
+        })
+    }
+}
+
+/// Reads the first `DATA` or `CHARACTERS` block of a NEXUS file into an `Alignment<u8>`
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the file is not a valid NEXUS file without any `DATA` or `CHARACTERS` block, or if the matrix does not match the declared dimensions.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_names(reader).map(|(_, alignment)| alignment)
+}
+
+/// Reads the first `DATA` or `CHARACTERS` block of a NEXUS file, returning the names of the taxa along with the `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::nexus;
+/// let input = b"#NEXUS
+/// [an interleaved matrix]
+/// BEGIN CHARACTERS;
+///     DIMENSIONS NTAX=3 NCHAR=8;
+///     FORMAT DATATYPE=PROTEIN MATCHCHAR=. INTERLEAVE;
+///     MATRIX
+///     seq1            ALKH
+///     'seq 2'         V..-
+///     seq3            ...Y
+///
+///     seq1            ITAN
+///     'seq 2'         ....
+///     seq3            ...T
+///     ;
+/// END;
+/// ";
+/// let (names, align) = nexus::read_with_names(&input[..]).unwrap();
+///
+/// assert_eq!(names, ["seq1", "seq 2", "seq3"]);
+/// assert_eq!(align.identifier(1), Some("seq 2"));
+/// assert_eq!(align.nth_sequence(1).unwrap(), [&b'V', &b'L', &b'K', &b'-', &b'I', &b'T', &b'A', &b'N']);
+/// assert_eq!(align.nth_position(7).unwrap(), [&b'N', &b'N', &b'T']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the file is not a valid NEXUS file without any `DATA` or `CHARACTERS` block, or if the matrix does not match the declared dimensions.
+pub fn read_with_names<R>(reader: R) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_datatype(reader).map(|(names, alignment, _)| (names, alignment))
+}
+
+/// Reads the first `DATA` or `CHARACTERS` block of a NEXUS file, returning the names of the taxa and the declared type of data along with the `Alignment<u8>`
+///
+/// The type of data is `None` if the `FORMAT` command does not declare one of the [`DataType`]s, and can be passed to [`Writer::datatype()`] to write the alignment back.
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::nexus;
+/// let input = b"#NEXUS
+/// BEGIN DATA;
+///     DIMENSIONS NTAX=2 NCHAR=8;
+///     FORMAT DATATYPE=PROTEIN GAP=~ MISSING=0;
+///     MATRIX
+///     seq1    ALKHITAN
+///     seq2    VLK~0TAN
+///     ;
+/// END;
+/// ";
+/// let (_, align, datatype) = nexus::read_with_datatype(&input[..]).unwrap();
+///
+/// assert_eq!(datatype, Some(nexus::DataType::Protein));
+/// assert_eq!(align.nth_sequence(1).unwrap(), [&b'V', &b'L', &b'K', &b'-', &b'?', &b'T', &b'A', &b'N']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the file is not a valid NEXUS file without any `DATA` or `CHARACTERS` block, or if the matrix does not match the declared dimensions.
+///
+/// [`DataType`]: enum.DataType.html
+/// [`Writer::datatype()`]: struct.Writer.html#method.datatype
+pub fn read_with_datatype<R>(mut reader: R) -> Result<Matrix, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let text = strip_comments(&String::from_utf8_lossy(&bytes));
+
+    let invalid = |line: usize, message: &str| MultiSeqAlignError::InvalidFormat {
+        format: FORMAT,
+        line,
+        message: message.to_string(),
+    };
+
+    if !text
+        .trim_start()
+        .get(..6)
+        .is_some_and(|start| start.eq_ignore_ascii_case("#NEXUS"))
+    {
+        return Err(invalid(1, "expected the file to start with '#NEXUS'"));
+    }
+    let text = &text[text.find('#').unwrap_or_default() + 6..];
+
+    let mut block = String::new();
+    let mut n_taxa: Option<usize> = None;
+    let mut n_chars: Option<usize> = None;
+    let mut interleaved = false;
+    let mut datatype: Option<DataType> = None;
+    let mut gap: Option<u8> = None;
+    let mut missing: Option<u8> = None;
+    let mut match_char: Option<u8> = None;
+    let mut matrix: Option<(usize, &str)> = None;
+
+    for (line, command) in split_commands(text) {
+        let (keyword, arguments) = split_keyword(command);
+        let keyword = keyword.to_ascii_uppercase();
+
+        match (keyword.as_str(), block.as_str()) {
+            ("BEGIN", _) => block = arguments.trim().to_ascii_uppercase(),
+            ("END" | "ENDBLOCK", "DATA" | "CHARACTERS") => break,
+            ("END" | "ENDBLOCK", _) => block.clear(),
+            ("DIMENSIONS", "TAXA" | "DATA" | "CHARACTERS") => {
+                for (key, value) in parameters(arguments) {
+                    let value = value.and_then(|v| v.parse::<usize>().ok());
+                    match (key.to_ascii_uppercase().as_str(), value) {
+                        ("NTAX", Some(value)) => n_taxa = Some(value),
+                        ("NCHAR", Some(value)) if block != "TAXA" => n_chars = Some(value),
+                        ("NTAX" | "NCHAR", None) => {
+                            return Err(invalid(line, "expected a number of taxa or characters"))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ("FORMAT", "DATA" | "CHARACTERS") => {
+                for (key, value) in parameters(arguments) {
+                    match key.to_ascii_uppercase().as_str() {
+                        "INTERLEAVE" => {
+                            interleaved = value.is_none_or(|v| !v.eq_ignore_ascii_case("no"));
+                        }
+                        "DATATYPE" => datatype = value.as_deref().and_then(DataType::parse),
+                        "GAP" => gap = value.and_then(|v| v.bytes().next()),
+                        "MISSING" => missing = value.and_then(|v| v.bytes().next()),
+                        "MATCHCHAR" => match_char = value.and_then(|v| v.bytes().next()),
+                        _ => {}
+                    }
+                }
+            }
+            ("MATRIX", "DATA" | "CHARACTERS") => matrix = Some((line, arguments)),
+            _ => {}
+        }
+    }
+
+    let (line, matrix) =
+        matrix.ok_or_else(|| invalid(0, "missing DATA or CHARACTERS block with a MATRIX"))?;
+    let n_chars = n_chars.ok_or_else(|| invalid(line, "missing NCHAR in DIMENSIONS"))?;
+
+    let (names, mut sequences) = parse_matrix(matrix, n_chars, interleaved);
+
+    let n_taxa = n_taxa.unwrap_or(names.len());
+    if sequences.len() != n_taxa || sequences.iter().any(|s| s.len() != n_chars) {
+        return Err(MultiSeqAlignError::DimensionsMismatch {
+            expected_n_sequences: n_taxa,
+            expected_length: n_chars,
+            found_n_sequences: sequences.len(),
+            found_length: sequences
+                .iter()
+                .map(Vec::len)
+                .find(|&l| l != n_chars)
+                .unwrap_or(n_chars),
+        });
+    }
+
+    normalise_symbols(&mut sequences, gap, missing, match_char);
+
+    let alignment = Alignment::with_named_sequences(&names, &sequences)?;
+
+    Ok((names, alignment, datatype))
+}
+
+/// Replaces the `gap` and `missing` symbols by `-` and `?`, then the `match_char` by the residue of the first sequence
+fn normalise_symbols(
+    sequences: &mut [Vec<u8>],
+    gap: Option<u8>,
+    missing: Option<u8>,
+    match_char: Option<u8>,
+) {
+    if gap.is_some() || missing.is_some() {
+        for residue in sequences.iter_mut().flatten() {
+            if Some(*residue) == gap {
+                *residue = b'-';
+            } else if Some(*residue) == missing {
+                *residue = b'?';
+            }
+        }
+    }
+
+    if let Some(match_char) = match_char {
+        if let Some((first, others)) = sequences.split_first_mut() {
+            for sequence in others {
+                for (residue, reference) in sequence.iter_mut().zip(first.iter()) {
+                    if *residue == match_char {
+                        *residue = *reference;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses the rows of a `MATRIX` command into names and sequences
+fn parse_matrix(matrix: &str, n_chars: usize, interleaved: bool) -> (Vec<String>, Vec<Vec<u8>>) {
+    let mut names: Vec<String> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+
+    for row in matrix.lines().filter(|row| !row.trim().is_empty()) {
+        // in sequential matrices, a sequence can span several lines
+        let continues_sequence =
+            !interleaved && sequences.last().is_some_and(|s| s.len() < n_chars);
+
+        let (index, residues) = if continues_sequence {
+            (sequences.len() - 1, row)
+        } else {
+            let (name, residues) = split_name(row);
+            let index = *indices.entry(name.clone()).or_insert_with(|| {
+                names.push(name);
+                sequences.push(Vec::with_capacity(n_chars));
+                names.len() - 1
+            });
+            (index, residues)
+        };
+
+        sequences[index].extend(residues.bytes().filter(|c| !c.is_ascii_whitespace()));
+    }
+
+    (names, sequences)
+}
+
+/// Replaces comments (between square brackets) by spaces, keeping line breaks
+fn strip_comments(text: &str) -> String {
+    let mut depth = 0_usize;
+    let mut quoted = false;
+
+    text.chars()
+        .map(|c| match c {
+            '\'' if depth == 0 => {
+                quoted = !quoted;
+                c
+            }
+            '[' if !quoted => {
+                depth += 1;
+                ' '
+            }
+            ']' if depth > 0 => {
+                depth -= 1;
+                ' '
+            }
+            '\n' => c,
+            _ if depth > 0 => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Splits `text` into commands ending with `;`, along with the line number of their first word
+fn split_commands(text: &str) -> Vec<(usize, &str)> {
+    let mut commands = Vec::new();
+    let mut start = 0_usize;
+    let mut line = 1_usize;
+    let mut start_line = 1_usize;
+    let mut quoted = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ';' if !quoted => {
+                commands.push((start_line, &text[start..index]));
+                start = index + 1;
+                start_line = line;
+            }
+            '\n' => {
+                line += 1;
+                if text[start..index].trim().is_empty() {
+                    start_line = line;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    commands
+}
+
+/// Splits a command into its keyword and its arguments
+fn split_keyword(command: &str) -> (&str, &str) {
+    let command = command.trim_start();
+    let end = command
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(command.len());
+    (&command[..end], &command[end..])
+}
+
+/// Splits the arguments of a command into `KEY=value` or `KEY` parameters
+fn parameters(arguments: &str) -> Vec<(String, Option<String>)> {
+    let spaced = arguments.replace('=', " = ");
+    let mut words = spaced.split_whitespace().peekable();
+    let mut parameters = Vec::new();
+
+    while let Some(key) = words.next() {
+        if words.peek() == Some(&"=") {
+            words.next();
+            let value = words
+                .next()
+                .map(|v| v.trim_matches(|c| c == '\'' || c == '"'));
+            parameters.push((key.to_string(), value.map(str::to_string)));
+        } else {
+            parameters.push((key.to_string(), None));
+        }
+    }
+
+    parameters
+}
+
+/// Splits a row of the matrix into the name of the taxon (unquoted) and the residues
+fn split_name(row: &str) -> (String, &str) {
+    let row = row.trim_start();
+    if let Some(quoted) = row.strip_prefix('\'') {
+        let mut name = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c == '\'' {
+                if chars.peek().map(|&(_, next)| next) == Some('\'') {
+                    chars.next();
+                } else {
+                    return (name, &quoted[index + 1..]);
+                }
+            }
+            name.push(c);
+        }
+        (name, "")
+    } else {
+        let end = row.find(char::is_whitespace).unwrap_or(row.len());
+        (row[..end].to_string(), &row[end..])
+    }
+}
+
+/// Quotes `name` if it contains whitespace or punctuation
+fn quote_name(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+/// Writes NEXUS files with a `DATA` block, and optionally a `SETS` block of character sets
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Declared type of data
+    datatype: DataType,
+    /// Gap symbol
+    gap: u8,
+    /// Missing data symbol
+    missing: u8,
+    /// Interleaved matrix
+    interleaved: bool,
+    /// Number of residues per block when interleaved
+    block_width: usize,
+    /// Character sets: name, positions (starting at 0)
+    charsets: Vec<(String, Vec<Range<usize>>)>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` for sequential DNA matrices, with `-` as gap and `?` as missing data
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            datatype: DataType::Dna,
+            gap: b'-',
+            missing: b'?',
+            interleaved: false,
+            block_width: DEFAULT_BLOCK_WIDTH,
+            charsets: Vec::new(),
+        }
+    }
+
+    /// Sets the type of data declared in the `FORMAT` command
+    #[must_use]
+    pub const fn datatype(mut self, datatype: DataType) -> Self {
+        self.datatype = datatype;
+        self
+    }
+
+    /// Sets the gap symbol declared in the `FORMAT` command, which replaces the `-` and `.` gaps of the matrix
+    #[must_use]
+    pub const fn gap(mut self, gap: u8) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets the missing data symbol declared in the `FORMAT` command, which replaces the `?` of the matrix
+    #[must_use]
+    pub const fn missing(mut self, missing: u8) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Sets whether the matrix is interleaved or sequential
+    #[must_use]
+    pub const fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    /// Sets the number of residues per block when interleaved, `0` writes a single block
+    #[must_use]
+    pub const fn block_width(mut self, block_width: usize) -> Self {
+        self.block_width = block_width;
+        self
+    }
+
+    /// Adds a character set to the `SETS` block, `positions` start at 0
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::nexus;
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq 2"],
+    ///     &[b"ATGAAACCC".to_vec(), b"ATGAAGCCG".to_vec()],
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// nexus::Writer::new()
+    ///     .charset("gene1", &[0..6])
+    ///     .charset("gene2", &[6..9])
+    ///     .write(&mut output, &align)
+    ///     .unwrap();
+    ///
+    /// let expected = [
+    ///     "#NEXUS",
+    ///     "",
+    ///     "BEGIN DATA;",
+    ///     "\tDIMENSIONS NTAX=2 NCHAR=9;",
+    ///     "\tFORMAT DATATYPE=DNA GAP=- MISSING=?;",
+    ///     "\tMATRIX",
+    ///     "\tseq1     ATGAAACCC",
+    ///     "\t'seq 2'  ATGAAGCCG",
+    ///     "\t;",
+    ///     "END;",
+    ///     "",
+    ///     "BEGIN SETS;",
+    ///     "\tCHARSET gene1 = 1-6;",
+    ///     "\tCHARSET gene2 = 7-9;",
+    ///     "END;",
+    /// ];
+    /// assert_eq!(String::from_utf8(output).unwrap().lines().collect::<Vec<_>>(), expected);
+    /// ```
+    #[must_use]
+    pub fn charset(mut self, name: &str, positions: &[Range<usize>]) -> Self {
+        self.charsets.push((name.to_string(), positions.to_vec()));
+        self
+    }
+
+    /// Writes `alignment` to `writer`, naming each taxon by the identifier of its sequence (or its row index)
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a range of a character set is empty or out of the alignment, or if `writer` fails.
+    pub fn write<W, T>(&self, writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_names(writer, alignment, &super::names(alignment))
+    }
+
+    /// Writes `alignment` to `writer`, using one of `names` per taxon
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `names` is different from the number of sequences, if a range of a character set is empty or out of the alignment, or if `writer` fails.
+    pub fn write_with_names<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        names: &[S],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if names.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: names.len(),
+            });
+        }
+        if let Some(range) = self
+            .charsets
+            .iter()
+            .flat_map(|(_, positions)| positions)
+            .find(|range| range.is_empty() || range.end > *alignment.length())
+        {
+            return Err(MultiSeqAlignError::InvalidRange {
+                start: range.start,
+                end: range.end,
+                length: *alignment.length(),
+            });
+        }
+
+        let names: Vec<String> = names.iter().map(|name| quote_name(name.as_ref())).collect();
+        let name_width = names.iter().map(String::len).max().unwrap_or_default() + 2;
+        let block_width = if !self.interleaved || self.block_width == 0 {
+            usize::MAX
+        } else {
+            self.block_width
+        };
+
+        writeln!(writer, "#NEXUS\n\nBEGIN DATA;")?;
+        writeln!(
+            writer,
+            "\tDIMENSIONS NTAX={} NCHAR={};",
+            alignment.n_sequences(),
+            alignment.length()
+        )?;
+        writeln!(
+            writer,
+            "\tFORMAT DATATYPE={} GAP={} MISSING={}{};",
+            self.datatype,
+            char::from(self.gap),
+            char::from(self.missing),
+            if self.interleaved { " INTERLEAVE" } else { "" }
+        )?;
+        writeln!(writer, "\tMATRIX")?;
+
+        let rows: Vec<Vec<u8>> = alignment
+            .iter_sequences()
+            .map(|sequence| {
+                sequence
+                    .into_iter()
+                    .map(|x| match x.clone().into() {
+                        b'-' | b'.' => self.gap,
+                        b'?' => self.missing,
+                        residue => residue,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut start = 0_usize;
+        loop {
+            let end = start.saturating_add(block_width).min(*alignment.length());
+
+            if start > 0 {
+                writeln!(writer)?;
+            }
+            for (name, row) in names.iter().zip(&rows) {
+                write!(writer, "\t{name:<name_width$}")?;
+                writer.write_all(&row[start..end])?;
+                writeln!(writer)?;
+            }
+
+            start = end;
+            if start >= *alignment.length() {
+                break;
+            }
+        }
+
+        writeln!(writer, "\t;\nEND;")?;
+
+        if !self.charsets.is_empty() {
+            writeln!(writer, "\nBEGIN SETS;")?;
+            for (name, positions) in &self.charsets {
+                let positions: Vec<String> = positions
+                    .iter()
+                    .map(|range| {
+                        if range.end == range.start + 1 {
+                            format!("{}", range.end)
+                        } else {
+                            format!("{}-{}", range.start + 1, range.end)
+                        }
+                    })
+                    .collect();
+                writeln!(
+                    writer,
+                    "\tCHARSET {} = {};",
+                    quote_name(name),
+                    positions.join(" ")
+                )?;
+            }
+            writeln!(writer, "END;")?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `alignment` to `writer` as a NEXUS `DATA` block with the default [`Writer`] settings
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment)
+}
+
+/// Writes `alignment` to `writer` as a NEXUS `DATA` block with the default [`Writer`] settings, using one of `names` per taxon
+///
+/// # Errors
+///
+/// Will return an error if the number of `names` is different from the number of sequences, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write_with_names<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    names: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_names(writer, alignment, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn kappa_casein(names: &[&str]) -> Alignment<u8> {
+        Alignment::with_named_sequences(
+            names,
+            &[
+                b"PAPISKWQSMPAQE".to_vec(),
+                b"HAQIPQRQYLPAQE".to_vec(),
+                b"PAQILQWQVLS-QE".to_vec(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn read_sequential_with_other_blocks() {
+        let input = b"#nexus
+begin taxa;
+    dimensions ntax=3;
+    taxlabels Bovine Human Sheep;
+end;
+
+begin data; [ comment; with a semicolon ]
+    dimensions ntax=3 nchar=14;
+    format datatype=protein gap=- missing=?;
+    matrix
+    Bovine PAPISKW
+           QSMPAQE
+    Human  HAQIPQRQYLPAQE
+    Sheep
+    PAQILQWQVLS-QE
+    ;
+end;
+
+begin trees;
+    tree t1 = ((Bovine,Sheep),Human);
+end;
+";
+        let (names, align) = read_with_names(&input[..]).unwrap();
+
+        assert_eq!(names, vec!["Bovine", "Human", "Sheep"]);
+        assert_eq!(align, kappa_casein(&["Bovine", "Human", "Sheep"]));
+    }
+
+    #[test]
+    fn read_characters_ntax_from_taxa_block() {
+        let input = b"#NEXUS\r\nBEGIN TAXA;\r\nDIMENSIONS NTAX=2;\r\nEND;\r\nBEGIN CHARACTERS;\r\nDIMENSIONS NCHAR=3;\r\nMATRIX\r\na ALK\r\nb VLK\r\n;\r\nEND;\r\n";
+        let align = read(&input[..]).unwrap();
+
+        assert_eq!(*align.n_sequences(), 2);
+        assert_eq!(*align.length(), 3);
+    }
+
+    #[test]
+    fn read_match_char() {
+        let input = b"#NEXUS
+BEGIN DATA;
+DIMENSIONS NTAX=3 NCHAR=14;
+FORMAT DATATYPE=PROTEIN MATCHCHAR=. INTERLEAVE=YES;
+MATRIX
+Bovine PAPISKW
+Human  H.Q.PQR
+Sheep  ..Q.LQ.
+
+Bovine QSMPAQE
+Human  .YL....
+Sheep  .VLS-..
+;
+END;
+";
+        assert_eq!(
+            read(&input[..]).unwrap(),
+            kappa_casein(&["Bovine", "Human", "Sheep"])
+        );
+    }
+
+    #[test]
+    fn read_gap_missing_datatype() {
+        let input = b"#NEXUS
+BEGIN DATA;
+DIMENSIONS NTAX=3 NCHAR=14;
+FORMAT DATATYPE=protein GAP=~ MISSING=0 MATCHCHAR=.;
+MATRIX
+Bovine PAPISKWQSMPAQE
+Human  H.Q.PQR.YL....
+Sheep  ..Q.LQ..VLS~00
+;
+END;
+";
+        let (_, align, datatype) = read_with_datatype(&input[..]).unwrap();
+
+        assert_eq!(datatype, Some(DataType::Protein));
+        assert_eq!(
+            align.nth_sequence(2).unwrap(),
+            b"PAQILQWQVLS-??".iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn read_wrong_dimensions() {
+        let input =
+            b"#NEXUS\nBEGIN DATA;\nDIMENSIONS NTAX=3 NCHAR=3;\nMATRIX\na ALK\nb VLK\n;\nEND;\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DimensionsMismatch {
+            expected_n_sequences: 3,
+            expected_length: 3,
+            found_n_sequences: 2,
+            found_length: 3,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_not_nexus() {
+        let error = read(&b">seq1\nALK\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "NEXUS",
+            line: 1,
+            message: "expected the file to start with '#NEXUS'".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_missing_matrix() {
+        let input = b"#NEXUS\nBEGIN TREES;\nTREE t1 = (a,b);\nEND;\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "NEXUS",
+            line: 0,
+            message: "missing DATA or CHARACTERS block with a MATRIX".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn quote_names() {
+        assert_eq!(quote_name("Homo_sapiens"), "Homo_sapiens");
+        assert_eq!(quote_name("Homo sapiens"), "'Homo sapiens'");
+        assert_eq!(quote_name("BOVIN/1-14"), "'BOVIN/1-14'");
+        assert_eq!(quote_name("Homo-sapiens"), "'Homo-sapiens'");
+        assert_eq!(quote_name("O'Brien"), "'O''Brien'");
+        assert_eq!(
+            split_name("'O''Brien' ALK"),
+            ("O'Brien".to_string(), " ALK")
+        );
+    }
+
+    #[test]
+    fn write_empty_charset_range() {
+        let error = Writer::new()
+            .charset("empty", &[0..3, 5..5])
+            .write(Vec::new(), &kappa_casein(&["a", "b", "c"]))
+            .err()
+            .unwrap();
+
+        let expected = MultiSeqAlignError::InvalidRange {
+            start: 5,
+            end: 5,
+            length: 14,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_gap_missing() {
+        let align =
+            Alignment::with_named_sequences(&["a", "b"], &[b"AC-?".to_vec(), b"A?-C".to_vec()])
+                .unwrap();
+        let mut output = Vec::new();
+        Writer::new()
+            .gap(b'~')
+            .missing(b'N')
+            .write(&mut output, &align)
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("GAP=~ MISSING=N;"));
+        assert!(output.contains("\ta  AC~N\n"));
+        assert_eq!(read(output.as_bytes()).unwrap(), align);
+    }
+
+    #[test]
+    fn round_trip() {
+        let names = vec!["Bos taurus", "Homo-sapiens", "O'Brien"];
+        let kappa_casein = kappa_casein(&names);
+        for interleaved in [false, true] {
+            let mut output = Vec::new();
+            Writer::new()
+                .datatype(DataType::Protein)
+                .interleaved(interleaved)
+                .block_width(5)
+                .charset("first", &[0..3, 3..7])
+                .charset("second", &[7..10, 11..12])
+                .write(&mut output, &kappa_casein)
+                .unwrap();
+
+            assert_eq!(
+                read_with_datatype(&output[..]).unwrap(),
+                (
+                    names.iter().map(ToString::to_string).collect(),
+                    kappa_casein.clone(),
+                    Some(DataType::Protein)
+                )
+            );
+        }
+    }
+}
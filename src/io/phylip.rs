@@ -0,0 +1,638 @@
+/*! PHYLIP format
+
+The file starts with a header giving the number of sequences and the length of the alignment, followed by the named sequences.
+
+Two flavours of names are supported:
+
+- strict PHYLIP: names occupy the first 10 characters of the line, padded with spaces
+- relaxed PHYLIP: names end at the first whitespace and can be of any length
+
+The sequences are either sequential (each sequence can span several lines) or interleaved (the first block contains the names, the following blocks only residues, in the same order).
+
+```text
+2 8
+seq1      ALKH ITAN
+seq2      VLK- ITAN
+```
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "PHYLIP";
+
+/// Length of the names in strict PHYLIP
+pub const STRICT_NAME_LENGTH: usize = 10;
+
+/// Default number of residues per block when writing interleaved files
+pub const DEFAULT_BLOCK_WIDTH: usize = 60;
+
+/// Reads PHYLIP files
+///
+/// By default, names are relaxed and sequences sequential. Reading stops once all the sequences declared in the header have been read, so files containing several data sets can be read by passing the same `&mut` reader repeatedly.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Reader {
+    /// Names are exactly 10 characters long
+    strict: bool,
+    /// Sequences are interleaved
+    interleaved: bool,
+}
+
+impl Reader {
+    /// Returns a `Reader` for relaxed sequential PHYLIP files
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strict: false,
+            interleaved: false,
+        }
+    }
+
+    /// Sets whether names are strict (10 characters) or relaxed (ending at the first whitespace)
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether sequences are interleaved or sequential
+    #[must_use]
+    pub const fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    /// Reads a PHYLIP file into an `Alignment<u8>`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader` fails, if the header is invalid, if the sequences do not match the number of sequences and length declared in the header, or if a name is used more than once.
+    pub fn read<R>(&self, reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+    where
+        R: BufRead,
+    {
+        self.read_with_names(reader).map(|(_, alignment)| alignment)
+    }
+
+    /// Reads a PHYLIP file, returning the names of the sequences along with the `Alignment<u8>`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::io::phylip;
+    /// let input = b"3 12
+    /// Bovine    PAPISKWQSM
+    /// Human     HAQIPQRQYL
+    /// Sheep     PAQILQWQVL
+    ///
+    /// PA
+    /// PA
+    /// S-
+    /// ";
+    /// let (names, align) = phylip::Reader::new()
+    ///     .strict(true)
+    ///     .interleaved(true)
+    ///     .read_with_names(&input[..])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(names, ["Bovine", "Human", "Sheep"]);
+    /// assert_eq!(align.identifier(2), Some("Sheep"));
+    /// assert_eq!(align.nth_sequence(2).unwrap().len(), 12);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader` fails, if the header is invalid, if the sequences do not match the number of sequences and length declared in the header, or if a name is used more than once.
+    pub fn read_with_names<R>(
+        &self,
+        mut reader: R,
+    ) -> Result<(Vec<String>, Alignment<u8>), MultiSeqAlignError>
+    where
+        R: BufRead,
+    {
+        let mut line = Vec::new();
+        let mut line_number = 0_usize;
+
+        let (n_sequences, length) = loop {
+            if !super::read_line(&mut reader, &mut line)? {
+                return Err(MultiSeqAlignError::InvalidFormat {
+                    format: FORMAT,
+                    line: line_number,
+                    message: "missing header".to_string(),
+                });
+            }
+            line_number += 1;
+            if !super::is_blank(&line) {
+                break parse_header(&line).ok_or_else(|| MultiSeqAlignError::InvalidFormat {
+                    format: FORMAT,
+                    line: line_number,
+                    message: "expected the number of sequences and the length of the alignment"
+                        .to_string(),
+                })?;
+            }
+        };
+
+        let mut names: Vec<String> = Vec::with_capacity(n_sequences);
+        let mut sequences: Vec<Vec<u8>> = Vec::with_capacity(n_sequences);
+        let mut n_complete = 0_usize;
+        let mut next_block_row = 0_usize;
+
+        while n_complete < n_sequences && super::read_line(&mut reader, &mut line)? {
+            if super::is_blank(&line) {
+                continue;
+            }
+
+            let starts_new_sequence = names.len() < n_sequences
+                && (self.interleaved || sequences.last().is_none_or(|s| s.len() >= length));
+
+            let (row, residues, was_complete) = if starts_new_sequence {
+                let (name, residues) = split_name(&line, self.strict);
+                names.push(String::from_utf8_lossy(name).to_string());
+                sequences.push(Vec::with_capacity(length));
+                // a new sequence is incomplete even if empty: alignments of length 0 have one name per line
+                (sequences.len() - 1, residues, false)
+            } else if self.interleaved {
+                let row = next_block_row;
+                next_block_row = (next_block_row + 1) % n_sequences;
+                (row, &line[..], sequences[row].len() >= length)
+            } else {
+                let row = sequences.len() - 1;
+                (row, &line[..], sequences[row].len() >= length)
+            };
+
+            let sequence = &mut sequences[row];
+            sequence.extend(residues.iter().filter(|c| !c.is_ascii_whitespace()));
+            if !was_complete && sequence.len() >= length {
+                n_complete += 1;
+            }
+        }
+
+        if sequences.len() != n_sequences || sequences.iter().any(|s| s.len() != length) {
+            return Err(MultiSeqAlignError::DimensionsMismatch {
+                expected_n_sequences: n_sequences,
+                expected_length: length,
+                found_n_sequences: sequences.len(),
+                found_length: sequences
+                    .iter()
+                    .map(Vec::len)
+                    .find(|&l| l != length)
+                    .unwrap_or(length),
+            });
+        }
+
+        let alignment = Alignment::with_named_sequences(&names, &sequences)?;
+
+        Ok((names, alignment))
+    }
+}
+
+/// Reads a relaxed sequential PHYLIP file into an `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::phylip;
+/// let input = b" 2 8\nseq1 ALKH\nITAN\nseq2 VLK-ITAN\n";
+/// let align = phylip::read(&input[..]).unwrap();
+///
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'-']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the header is invalid, if the sequences do not match the number of sequences and length declared in the header, or if a name is used more than once.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    Reader::new().read(reader)
+}
+
+/// Parses the number of sequences and the length of the alignment, ignoring any option after them
+fn parse_header(line: &[u8]) -> Option<(usize, usize)> {
+    let text = String::from_utf8_lossy(line);
+    let mut fields = text.split_whitespace();
+    let n_sequences = fields.next()?.parse().ok()?;
+    let length = fields.next()?.parse().ok()?;
+    Some((n_sequences, length))
+}
+
+/// Splits a line starting with a name into the name and the residues
+///
+/// Strict names are the first [`STRICT_NAME_LENGTH`] bytes of the line, leading spaces included.
+pub(crate) fn split_name(line: &[u8], strict: bool) -> (&[u8], &[u8]) {
+    let (name, residues) = if strict {
+        line.split_at(line.len().min(STRICT_NAME_LENGTH))
+    } else {
+        let line = trim_start(line);
+        line.split_at(
+            line.iter()
+                .position(u8::is_ascii_whitespace)
+                .unwrap_or(line.len()),
+        )
+    };
+    (trim_end(trim_start(name)), residues)
+}
+
+fn trim_start(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn trim_end(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(0, |index| index + 1);
+    &bytes[..end]
+}
+
+/// Writes PHYLIP files
+///
+/// By default, names are relaxed and sequences sequential, each sequence on a single line.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Names are padded to 10 characters
+    strict: bool,
+    /// Sequences are interleaved
+    interleaved: bool,
+    /// Number of residues per block when interleaved
+    block_width: usize,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` for relaxed sequential PHYLIP files
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strict: false,
+            interleaved: false,
+            block_width: DEFAULT_BLOCK_WIDTH,
+        }
+    }
+
+    /// Sets whether names are strict (padded to 10 characters) or relaxed
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether sequences are interleaved or sequential
+    #[must_use]
+    pub const fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    /// Sets the number of residues per block when interleaved, `0` writes a single block
+    #[must_use]
+    pub const fn block_width(mut self, block_width: usize) -> Self {
+        self.block_width = block_width;
+        self
+    }
+
+    /// Writes `alignment` to `writer`, naming each sequence by its identifier (or its row index)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::phylip;
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// phylip::Writer::new()
+    ///     .strict(true)
+    ///     .interleaved(true)
+    ///     .block_width(5)
+    ///     .write(&mut output, &align)
+    ///     .unwrap();
+    ///
+    /// let expected = [
+    ///     "2 8",
+    ///     "seq1      ALKHI",
+    ///     "seq2      VLK-I",
+    ///     "",
+    ///     "          TAN",
+    ///     "          TAN",
+    /// ];
+    /// assert_eq!(String::from_utf8(output).unwrap().lines().collect::<Vec<_>>(), expected);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a name is longer than 10 characters in strict mode, or if `writer` fails.
+    pub fn write<W, T>(&self, writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_names(writer, alignment, &super::names(alignment))
+    }
+
+    /// Writes `alignment` to `writer`, using one of `names` per sequence
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `names` is different from the number of sequences, if a name is longer than 10 characters in strict mode, or if `writer` fails.
+    pub fn write_with_names<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        names: &[S],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if names.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: names.len(),
+            });
+        }
+
+        let name_width = if self.strict {
+            if let Some(name) = names
+                .iter()
+                .find(|name| name.as_ref().len() > STRICT_NAME_LENGTH)
+            {
+                return Err(MultiSeqAlignError::NameTooLong {
+                    name: name.as_ref().to_string(),
+                    max_length: STRICT_NAME_LENGTH,
+                });
+            }
+            STRICT_NAME_LENGTH
+        } else {
+            names
+                .iter()
+                .map(|name| name.as_ref().len())
+                .max()
+                .unwrap_or_default()
+                + 1
+        };
+        let block_width = if !self.interleaved || self.block_width == 0 {
+            usize::MAX
+        } else {
+            self.block_width
+        };
+
+        writeln!(writer, "{} {}", alignment.n_sequences(), alignment.length())?;
+
+        let rows: Vec<Vec<u8>> = alignment
+            .iter_sequences()
+            .map(|sequence| sequence.into_iter().map(|x| x.clone().into()).collect())
+            .collect();
+
+        let mut start = 0_usize;
+        loop {
+            let end = start.saturating_add(block_width).min(*alignment.length());
+
+            if start > 0 {
+                writeln!(writer)?;
+            }
+            for (name, row) in names.iter().zip(&rows) {
+                let label = if start == 0 { name.as_ref() } else { "" };
+                write!(writer, "{label:<name_width$}")?;
+                writer.write_all(&row[start..end])?;
+                writeln!(writer)?;
+            }
+
+            start = end;
+            if start >= *alignment.length() {
+                break;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `alignment` to `writer` as relaxed sequential PHYLIP
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment)
+}
+
+/// Writes `alignment` to `writer` as relaxed sequential PHYLIP, using one of `names` per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `names` is different from the number of sequences, or if `writer` fails.
+pub fn write_with_names<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    names: &[S],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_names(writer, alignment, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn kappa_casein(names: &[&str]) -> Alignment<u8> {
+        Alignment::with_named_sequences(
+            names,
+            &[
+                b"PAPISKWQSMPAQE".to_vec(),
+                b"HAQIPQRQYLPAQE".to_vec(),
+                b"PAQILQWQVLS-QE".to_vec(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn read_strict_sequential() {
+        let input = b"3 14\nBos taurusPAPISKWQSM\nPAQE\n  Homo    HAQIPQRQYLPAQE\nOvis aries PAQILQWQ\n VLS-QE\n";
+        let (names, align) = Reader::new()
+            .strict(true)
+            .read_with_names(&input[..])
+            .unwrap();
+
+        assert_eq!(names, vec!["Bos taurus", "Homo", "Ovis aries"]);
+        assert_eq!(align, kappa_casein(&["Bos taurus", "Homo", "Ovis aries"]));
+    }
+
+    #[test]
+    fn read_relaxed_interleaved_crlf() {
+        let input = b" 3  14\r\nBos_taurus PAPISKW\r\nHomo_sapiens HAQIPQR\r\nOvis_aries PAQILQW\r\n\r\nQSMPAQE\r\nQYLPAQE\r\nQVLS-QE\r\n";
+        let (names, align) = Reader::new()
+            .interleaved(true)
+            .read_with_names(&input[..])
+            .unwrap();
+
+        assert_eq!(names, vec!["Bos_taurus", "Homo_sapiens", "Ovis_aries"]);
+        assert_eq!(
+            align,
+            kappa_casein(&["Bos_taurus", "Homo_sapiens", "Ovis_aries"])
+        );
+    }
+
+    #[test]
+    fn read_several_data_sets() {
+        let input = b"1 3\nseq1 ALK\n1 2\nseq1 HI\n";
+        let mut reader = &input[..];
+
+        assert_eq!(*read(&mut reader).unwrap().length(), 3);
+        assert_eq!(*read(&mut reader).unwrap().length(), 2);
+    }
+
+    #[test]
+    fn read_missing_sequence() {
+        let input = b"3 8\nseq1 ALKHITAN\nseq2 VLK-ITAN\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DimensionsMismatch {
+            expected_n_sequences: 3,
+            expected_length: 8,
+            found_n_sequences: 2,
+            found_length: 8,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_wrong_length() {
+        let input = b"2 8\nseq1 ALKHITAN\nseq2 VLK-ITANS\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DimensionsMismatch {
+            expected_n_sequences: 2,
+            expected_length: 8,
+            found_n_sequences: 2,
+            found_length: 9,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_zero_length() {
+        let input = b"2 0\nseq1\nseq2\n2 3\nseq1 ALK\nseq2 VLK\n";
+        let mut reader = &input[..];
+
+        let (names, align) = Reader::new().read_with_names(&mut reader).unwrap();
+        assert_eq!(names, vec!["seq1", "seq2"]);
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(&["seq1", "seq2"], &[vec![], vec![]]).unwrap()
+        );
+        assert_eq!(*read(&mut reader).unwrap().length(), 3);
+
+        let error = read(&b"2 0\nseq1 ALK\nseq2\n"[..]).err().unwrap();
+        let expected = MultiSeqAlignError::DimensionsMismatch {
+            expected_n_sequences: 2,
+            expected_length: 0,
+            found_n_sequences: 2,
+            found_length: 3,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_duplicate_name() {
+        let error = read(&b"2 3\nseq1 ALK\nseq1 VLK\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::DuplicateIdentifier {
+            identifier: "seq1".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_invalid_header() {
+        let error = read(&b"two 8\nseq1 ALKHITAN\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "PHYLIP",
+            line: 1,
+            message: "expected the number of sequences and the length of the alignment".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_relaxed_sequential() {
+        let mut output = Vec::new();
+        write(&mut output, &kappa_casein(&["Bovine", "Human", "Sheep"])).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "3 14\nBovine PAPISKWQSMPAQE\nHuman  HAQIPQRQYLPAQE\nSheep  PAQILQWQVLS-QE\n"
+        );
+    }
+
+    #[test]
+    fn write_strict_name_too_long() {
+        let error = Writer::new()
+            .strict(true)
+            .write(
+                Vec::new(),
+                &kappa_casein(&["Bos_taurus", "Homo_sapiens", "Ovis"]),
+            )
+            .err()
+            .unwrap();
+
+        let expected = MultiSeqAlignError::NameTooLong {
+            name: "Homo_sapiens".to_string(),
+            max_length: 10,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let names = vec!["Bovine", "Human", "Sheep"];
+        let kappa_casein = kappa_casein(&names);
+        for strict in [false, true] {
+            for interleaved in [false, true] {
+                let mut output = Vec::new();
+                Writer::new()
+                    .strict(strict)
+                    .interleaved(interleaved)
+                    .block_width(4)
+                    .write(&mut output, &kappa_casein)
+                    .unwrap();
+
+                let (read_names, align) = Reader::new()
+                    .strict(strict)
+                    .interleaved(interleaved)
+                    .read_with_names(&output[..])
+                    .unwrap();
+                assert_eq!(read_names, names);
+                assert_eq!(align, kappa_casein);
+            }
+        }
+    }
+}
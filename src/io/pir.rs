@@ -0,0 +1,555 @@
+/*! PIR / NBRF format
+
+Each entry starts with a `>` line holding a two-letter sequence type code and the name of the sequence, followed by a description line, then the residues terminated by `*`. The name is the identifier of the sequence in the alignment, and the description line its description unless empty.
+
+```text
+>P1;seq1
+first sequence
+ALKHITAN*
+>P1;seq2
+second sequence
+VLK-ITAN*
+```
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::fmt;
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "PIR";
+
+/// Default number of residues per line when writing
+pub const DEFAULT_LINE_WIDTH: usize = 60;
+
+/// Type of a sequence, as declared by the two-letter code of a PIR header
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum SequenceType {
+    /// Protein, complete (`P1`)
+    #[default]
+    Protein,
+    /// Protein, fragment (`F1`)
+    ProteinFragment,
+    /// DNA, linear (`DL`)
+    DnaLinear,
+    /// DNA, circular (`DC`)
+    DnaCircular,
+    /// RNA, linear (`RL`)
+    RnaLinear,
+    /// RNA, circular (`RC`)
+    RnaCircular,
+    /// Transfer RNA (`N3`)
+    TransferRna,
+    /// Other functional RNA (`N1`)
+    OtherRna,
+}
+
+impl SequenceType {
+    /// Returns the two-letter code of the sequence type
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Protein => "P1",
+            Self::ProteinFragment => "F1",
+            Self::DnaLinear => "DL",
+            Self::DnaCircular => "DC",
+            Self::RnaLinear => "RL",
+            Self::RnaCircular => "RC",
+            Self::TransferRna => "N3",
+            Self::OtherRna => "N1",
+        }
+    }
+
+    /// Returns the sequence type of a two-letter code, `None` if the code is unknown
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::io::pir::SequenceType;
+    /// assert_eq!(SequenceType::from_code("DC"), Some(SequenceType::DnaCircular));
+    /// assert_eq!(SequenceType::from_code("XX"), None);
+    /// ```
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "P1" => Some(Self::Protein),
+            "F1" => Some(Self::ProteinFragment),
+            "DL" => Some(Self::DnaLinear),
+            "DC" => Some(Self::DnaCircular),
+            "RL" => Some(Self::RnaLinear),
+            "RC" => Some(Self::RnaCircular),
+            "N3" => Some(Self::TransferRna),
+            "N1" => Some(Self::OtherRna),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SequenceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Header of a PIR entry
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Header {
+    /// Type of the sequence
+    pub sequence_type: SequenceType,
+    /// Name of the sequence
+    pub name: String,
+    /// Description line
+    pub description: String,
+}
+
+impl Header {
+    /// Returns a `Header` with the given type and name, and an empty description
+    #[must_use]
+    pub fn new<S: Into<String>>(sequence_type: SequenceType, name: S) -> Self {
+        Self {
+            sequence_type,
+            name: name.into(),
+            description: String::new(),
+        }
+    }
+
+    /// Sets the description line
+    #[must_use]
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+/// Reads a PIR file into an `Alignment<u8>`
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if an entry is malformed, if the sequences have different lengths, or if a name is used more than once.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_headers(reader).map(|(_, alignment)| alignment)
+}
+
+/// Reads a PIR file, returning the header of each entry along with the `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::pir::{self, SequenceType};
+/// let input = b">P1;seq1
+/// first sequence
+/// ALKH
+/// ITAN*
+/// >F1;seq2
+/// second sequence
+/// VLK-ITAN*
+/// ";
+/// let (headers, align) = pir::read_with_headers(&input[..]).unwrap();
+///
+/// assert_eq!(headers[1].sequence_type, SequenceType::ProteinFragment);
+/// assert_eq!(headers[1].name, "seq2");
+/// assert_eq!(headers[1].description, "second sequence");
+/// assert_eq!(align.identifier(1), Some("seq2"));
+/// assert_eq!(align.description(1), Some("second sequence"));
+/// assert_eq!(align.nth_position(3).unwrap(), [&b'H', &b'-']);
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if an entry is malformed, if the sequences have different lengths, or if a name is used more than once.
+pub fn read_with_headers<R>(
+    mut reader: R,
+) -> Result<(Vec<Header>, Alignment<u8>), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let invalid = |line: usize, message: String| MultiSeqAlignError::InvalidFormat {
+        format: FORMAT,
+        line,
+        message,
+    };
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+    let mut headers: Vec<Header> = Vec::new();
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+    // whether the residues of the last entry were terminated by '*'
+    let mut terminated = true;
+
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+
+        if let Some(header) = line.strip_prefix(b">") {
+            if !terminated {
+                return Err(invalid(
+                    line_number,
+                    "expected '*' before the next entry".to_string(),
+                ));
+            }
+            let header = String::from_utf8_lossy(header).into_owned();
+            let (code, name) = header.split_once(';').ok_or_else(|| {
+                invalid(line_number, "expected a header like '>P1;name'".to_string())
+            })?;
+            let sequence_type = SequenceType::from_code(code)
+                .ok_or_else(|| invalid(line_number, format!("unknown sequence type '{code}'")))?;
+            let name = name.trim().to_string();
+
+            if !super::read_line(&mut reader, &mut line)? {
+                return Err(invalid(line_number, "missing description line".to_string()));
+            }
+            line_number += 1;
+
+            headers.push(Header {
+                sequence_type,
+                name,
+                description: String::from_utf8_lossy(&line).trim().to_string(),
+            });
+            sequences.push(Vec::new());
+            terminated = false;
+        } else if let (Some(sequence), false) = (sequences.last_mut(), terminated) {
+            let residues = line.iter().filter(|c| !c.is_ascii_whitespace());
+            if let Some(end) = line.iter().position(|&c| c == b'*') {
+                sequence.extend(residues.take_while(|&&c| c != b'*'));
+                terminated = true;
+                if !super::is_blank(&line[end + 1..]) {
+                    return Err(invalid(
+                        line_number,
+                        "unexpected residues after '*'".to_string(),
+                    ));
+                }
+            } else {
+                sequence.extend(residues);
+            }
+        } else if !super::is_blank(&line) {
+            return Err(invalid(
+                line_number,
+                "expected a header line starting with '>'".to_string(),
+            ));
+        }
+    }
+
+    if !terminated {
+        return Err(invalid(
+            line_number,
+            "missing '*' at the end of the sequence".to_string(),
+        ));
+    }
+
+    let names: Vec<&str> = headers.iter().map(|header| header.name.as_str()).collect();
+    utils::check_unequal_named_lengths(
+        &sequences,
+        &names,
+        utils::first_sequence_length(&sequences),
+    )?;
+    let mut alignment = Alignment::with_named_sequences(&names, &sequences)?;
+    for (row, header) in headers.iter().enumerate() {
+        if !header.description.is_empty() {
+            alignment.set_description(row, Some(header.description.as_str()));
+        }
+    }
+
+    Ok((headers, alignment))
+}
+
+/// Writes PIR files
+///
+/// Sequences are wrapped every [`DEFAULT_LINE_WIDTH`] residues unless configured otherwise with [`line_width()`].
+///
+/// [`DEFAULT_LINE_WIDTH`]: constant.DEFAULT_LINE_WIDTH.html
+/// [`line_width()`]: struct.Writer.html#method.line_width
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Writer {
+    /// Number of residues per line, `None` to write each sequence on a single line
+    line_width: Option<usize>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Returns a `Writer` wrapping sequences every [`DEFAULT_LINE_WIDTH`] residues
+    ///
+    /// [`DEFAULT_LINE_WIDTH`]: constant.DEFAULT_LINE_WIDTH.html
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            line_width: Some(DEFAULT_LINE_WIDTH),
+        }
+    }
+
+    /// Sets the number of residues per line, `None` (or `Some(0)`) disables wrapping
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::pir::{self, Header, SequenceType};
+    /// let align = Alignment::with_sequences(&[b"ALKHITAN".to_vec()]).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// pir::Writer::new()
+    ///     .line_width(Some(5))
+    ///     .write_with_headers(&mut output, &align, &[Header::new(SequenceType::Protein, "seq1")])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, b">P1;seq1\n\nALKHI\nTAN*\n");
+    /// ```
+    #[must_use]
+    pub const fn line_width(mut self, line_width: Option<usize>) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Writes `alignment` to `writer` as proteins, naming each sequence by its identifier (or its row index) and describing it by its description
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer` fails.
+    pub fn write<W, T>(&self, writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_headers(writer, alignment, &headers(alignment))
+    }
+
+    /// Writes `alignment` to `writer`, using one of `headers` per sequence
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `headers` is different from the number of sequences, or if `writer` fails.
+    pub fn write_with_headers<W, T>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        headers: &[Header],
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        if headers.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: headers.len(),
+            });
+        }
+
+        let line_width = match self.line_width {
+            Some(width) if width > 0 => width,
+            _ => usize::MAX,
+        };
+
+        let mut residues = Vec::with_capacity(*alignment.length() + 1);
+        for (header, sequence) in headers.iter().zip(alignment.iter_sequences()) {
+            writeln!(writer, ">{};{}", header.sequence_type, header.name)?;
+            writeln!(writer, "{}", header.description)?;
+
+            residues.clear();
+            residues.extend(sequence.into_iter().map(|residue| residue.clone().into()));
+            residues.push(b'*');
+            for line in residues.chunks(line_width) {
+                writer.write_all(line)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Returns the protein headers naming and describing the sequences of `alignment`
+fn headers<T>(alignment: &Alignment<T>) -> Vec<Header> {
+    super::names(alignment)
+        .into_iter()
+        .enumerate()
+        .map(|(row, name)| {
+            Header::new(SequenceType::Protein, name)
+                .description(alignment.description(row).unwrap_or_default())
+        })
+        .collect()
+}
+
+/// Writes `alignment` to `writer` in the PIR format with the default [`Writer`] settings, as proteins named by their identifiers (or their row indices)
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::Alignment;
+/// # use multi_seq_align::io::pir;
+/// let mut align = Alignment::with_sequences(&[b"ALKH".to_vec(), b"VLK-".to_vec()]).unwrap();
+/// align.set_identifiers(&["seq1", "seq2"]).unwrap();
+/// align.set_description(0, Some("first sequence"));
+///
+/// let mut output = Vec::new();
+/// pir::write(&mut output, &align).unwrap();
+///
+/// assert_eq!(output, b">P1;seq1\nfirst sequence\nALKH*\n>P1;seq2\n\nVLK-*\n".to_vec());
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write<W, T>(writer: W, alignment: &Alignment<T>) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment)
+}
+
+/// Writes `alignment` to `writer` in the PIR format with the default [`Writer`] settings, using one of `headers` per sequence
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::Alignment;
+/// # use multi_seq_align::io::pir::{self, Header, SequenceType};
+/// let align = Alignment::with_sequences(&[b"ACGT".to_vec(), b"AC-T".to_vec()]).unwrap();
+/// let headers = [
+///     Header::new(SequenceType::DnaLinear, "seq1").description("first sequence"),
+///     Header::new(SequenceType::DnaCircular, "seq2"),
+/// ];
+///
+/// let mut output = Vec::new();
+/// pir::write_with_headers(&mut output, &align, &headers).unwrap();
+///
+/// assert_eq!(output, b">DL;seq1\nfirst sequence\nACGT*\n>DC;seq2\n\nAC-T*\n".to_vec());
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if the number of `headers` is different from the number of sequences, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write_with_headers<W, T>(
+    writer: W,
+    alignment: &Alignment<T>,
+    headers: &[Header],
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write_with_headers(writer, alignment, headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_nbrf() {
+        let input = b">P1;CRAB_ANAPL\r\nALPHA CRYSTALLIN B CHAIN.\r\nMDITIHNPLI\r\nRRPLFSW*\r\n\r\n>P1;CRAB_BOVIN\r\nALPHA CRYSTALLIN B CHAIN.\r\nMDIAIHHPWI\r\nRRPFFPF*\r\n";
+        let (headers, align) = read_with_headers(&input[..]).unwrap();
+
+        assert_eq!(
+            headers,
+            vec![
+                Header::new(SequenceType::Protein, "CRAB_ANAPL")
+                    .description("ALPHA CRYSTALLIN B CHAIN."),
+                Header::new(SequenceType::Protein, "CRAB_BOVIN")
+                    .description("ALPHA CRYSTALLIN B CHAIN."),
+            ]
+        );
+        let mut expected = Alignment::with_named_sequences(
+            &["CRAB_ANAPL", "CRAB_BOVIN"],
+            &[b"MDITIHNPLIRRPLFSW".to_vec(), b"MDIAIHHPWIRRPFFPF".to_vec()],
+        )
+        .unwrap();
+        expected
+            .set_description(0, Some("ALPHA CRYSTALLIN B CHAIN."))
+            .set_description(1, Some("ALPHA CRYSTALLIN B CHAIN."));
+        assert_eq!(align, expected);
+    }
+
+    #[test]
+    fn read_unknown_type() {
+        let error = read(&b">XX;seq\n\nACGT*\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "PIR",
+            line: 1,
+            message: "unknown sequence type 'XX'".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_missing_terminator() {
+        let error = read(&b">P1;a\n\nACGT\n>P1;b\n\nACGT*\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "PIR",
+            line: 4,
+            message: "expected '*' before the next entry".to_string(),
+        };
+        assert_eq!(error, expected);
+
+        let error = read(&b">P1;a\n\nACGT\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "PIR",
+            line: 3,
+            message: "missing '*' at the end of the sequence".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_unequal_lengths() {
+        let error = read(&b">P1;a\n\nACGT*\n>P1;b\n\nAC*\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 4,
+            found_lengths: vec![2],
+            identifiers: vec!["b".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_wrong_number_of_headers() {
+        let align = Alignment::with_sequences(&[b"ACGT".to_vec()]).unwrap();
+        let error = write_with_headers(Vec::new(), &align, &[]).err().unwrap();
+
+        let expected = MultiSeqAlignError::WrongNumberOfNames {
+            n_sequences: 1,
+            found: 0,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn write_unnamed() {
+        let align = Alignment::with_sequences(&[b"ACGT".to_vec(), b"AC-T".to_vec()]).unwrap();
+        let mut output = Vec::new();
+        write(&mut output, &align).unwrap();
+
+        assert_eq!(output, b">P1;0\n\nACGT*\n>P1;1\n\nAC-T*\n".to_vec());
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = b">RL;first\nan RNA\nACGU-\nACGU*\n>N3;second\na tRNA\nACGUA\nACG-*\n";
+        let (headers, align) = read_with_headers(&input[..]).unwrap();
+
+        let mut output = Vec::new();
+        Writer::new()
+            .line_width(Some(5))
+            .write_with_headers(&mut output, &align, &headers)
+            .unwrap();
+
+        assert_eq!(output, input.to_vec());
+    }
+}
@@ -0,0 +1,829 @@
+/*! Stockholm format
+
+Used by Pfam and Rfam, the Stockholm format adds markup lines to the aligned sequences:
+
+- `#=GF <feature> <text>`: annotation of the whole file
+- `#=GS <name> <feature> <text>`: annotation of a sequence
+- `#=GR <name> <feature> <markup>`: annotation of each residue of a sequence
+- `#=GC <feature> <markup>`: annotation of each column of the alignment
+
+```text
+# STOCKHOLM 1.0
+#=GF ID    example
+#=GS seq1  AC P02668
+
+seq1       ALKHITAN
+#=GR seq1  SS HHHHEE-E
+seq2       VLK-ITAN
+#=GC SS_cons HHHH-EEE
+//
+```
+
+The alignment can be split in several blocks, the markup of each block is appended to the previous one. The `//` line terminates the alignment, the reader stops right after it so files containing several alignments can be read by passing the same `&mut` reader repeatedly.
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+const FORMAT: &str = "Stockholm";
+
+/// Annotations of a Stockholm alignment
+///
+/// Annotations are stored in the order they were added, which is the order they are written back.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Annotations {
+    /// `#=GF` annotations: feature, text
+    file: Vec<(String, String)>,
+    /// `#=GS` annotations: sequence name, feature, text
+    sequence: Vec<(String, String, String)>,
+    /// `#=GR` annotations: sequence name, feature, markup
+    residue: Vec<(String, String, Vec<u8>)>,
+    /// `#=GC` annotations: feature, markup
+    column: Vec<(String, Vec<u8>)>,
+}
+
+impl Annotations {
+    /// Returns an empty set of annotations
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if there is no annotation
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.file.is_empty()
+            && self.sequence.is_empty()
+            && self.residue.is_empty()
+            && self.column.is_empty()
+    }
+
+    /// Returns the texts of the file annotations (`#=GF`) of a `feature`, one per line
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::io::stockholm::Annotations;
+    /// let mut annotations = Annotations::new();
+    /// annotations.add_file_annotation("CC", "first line");
+    /// annotations.add_file_annotation("CC", "second line");
+    ///
+    /// assert_eq!(annotations.file_annotations("CC"), ["first line", "second line"]);
+    /// ```
+    #[must_use]
+    pub fn file_annotations(&self, feature: &str) -> Vec<&str> {
+        self.file
+            .iter()
+            .filter(|(f, _)| f == feature)
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    /// Returns the texts of the annotations (`#=GS`) of a `feature` for the sequence `name`
+    #[must_use]
+    pub fn sequence_annotations(&self, name: &str, feature: &str) -> Vec<&str> {
+        self.sequence
+            .iter()
+            .filter(|(n, f, _)| n == name && f == feature)
+            .map(|(_, _, text)| text.as_str())
+            .collect()
+    }
+
+    /// Returns the per-residue markup (`#=GR`) of a `feature` for the sequence `name`
+    #[must_use]
+    pub fn residue_annotation(&self, name: &str, feature: &str) -> Option<&[u8]> {
+        self.residue
+            .iter()
+            .find(|(n, f, _)| n == name && f == feature)
+            .map(|(_, _, markup)| markup.as_slice())
+    }
+
+    /// Returns the per-column markup (`#=GC`) of a `feature`, like `SS_cons` or `RF`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::io::stockholm;
+    /// let input = b"# STOCKHOLM 1.0\nseq1 ALKH\nseq2 VLK-\n#=GC SS_cons HHH.\n//\n";
+    /// let (_, _, annotations) = stockholm::read_with_annotations(&input[..]).unwrap();
+    ///
+    /// assert_eq!(annotations.column_annotation("SS_cons"), Some(&b"HHH."[..]));
+    /// ```
+    #[must_use]
+    pub fn column_annotation(&self, feature: &str) -> Option<&[u8]> {
+        self.column
+            .iter()
+            .find(|(f, _)| f == feature)
+            .map(|(_, markup)| markup.as_slice())
+    }
+
+    /// Adds a file annotation (`#=GF`)
+    pub fn add_file_annotation(&mut self, feature: &str, text: &str) -> &mut Self {
+        self.file.push((feature.to_string(), text.to_string()));
+        self
+    }
+
+    /// Adds an annotation (`#=GS`) to the sequence `name`
+    pub fn add_sequence_annotation(&mut self, name: &str, feature: &str, text: &str) -> &mut Self {
+        self.sequence
+            .push((name.to_string(), feature.to_string(), text.to_string()));
+        self
+    }
+
+    /// Sets the per-residue markup (`#=GR`) of a `feature` for the sequence `name`, replacing any previous one
+    pub fn set_residue_annotation(
+        &mut self,
+        name: &str,
+        feature: &str,
+        markup: Vec<u8>,
+    ) -> &mut Self {
+        match self
+            .residue
+            .iter_mut()
+            .find(|(n, f, _)| n == name && f == feature)
+        {
+            Some(annotation) => annotation.2 = markup,
+            None => self
+                .residue
+                .push((name.to_string(), feature.to_string(), markup)),
+        }
+        self
+    }
+
+    /// Sets the per-column markup (`#=GC`) of a `feature`, replacing any previous one
+    pub fn set_column_annotation(&mut self, feature: &str, markup: Vec<u8>) -> &mut Self {
+        match self.column.iter_mut().find(|(f, _)| f == feature) {
+            Some(annotation) => annotation.1 = markup,
+            None => self.column.push((feature.to_string(), markup)),
+        }
+        self
+    }
+
+    /// Appends `markup` to the per-residue markup of a `feature` for the sequence `name`
+    fn extend_residue_annotation(&mut self, name: &str, feature: &str, markup: &[u8]) {
+        match self
+            .residue
+            .iter_mut()
+            .find(|(n, f, _)| n == name && f == feature)
+        {
+            Some(annotation) => annotation.2.extend_from_slice(markup),
+            None => self
+                .residue
+                .push((name.to_string(), feature.to_string(), markup.to_vec())),
+        }
+    }
+
+    /// Appends `markup` to the per-column markup of a `feature`
+    fn extend_column_annotation(&mut self, feature: &str, markup: &[u8]) {
+        match self.column.iter_mut().find(|(f, _)| f == feature) {
+            Some(annotation) => annotation.1.extend_from_slice(markup),
+            None => self.column.push((feature.to_string(), markup.to_vec())),
+        }
+    }
+}
+
+/// Reads a Stockholm alignment into an `Alignment<u8>`, discarding the annotations
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the `# STOCKHOLM` header or the `//` terminator are missing, if the sequences have different lengths, if a `#=GS` or `#=GR` line refers to an unknown sequence, or if some markup is not as long as the alignment.
+pub fn read<R>(reader: R) -> Result<Alignment<u8>, MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    read_with_annotations(reader).map(|(_, alignment, _)| alignment)
+}
+
+/// Reads a Stockholm alignment, returning the names of the sequences and the annotations along with the `Alignment<u8>`
+///
+/// # Examples
+///
+/// ```rust
+/// # use multi_seq_align::io::stockholm;
+/// let input = b"# STOCKHOLM 1.0
+/// #=GF ID example
+/// #=GS seq1 AC P02668
+///
+/// seq1         ALKH
+/// #=GR seq1 SS HHHH
+/// seq2         VLK-
+/// #=GC RF      xxxx
+///
+/// seq1         ITAN
+/// #=GR seq1 SS EE-E
+/// seq2         ITAN
+/// #=GC RF      xx..
+/// //
+/// ";
+/// let (names, align, annotations) = stockholm::read_with_annotations(&input[..]).unwrap();
+///
+/// assert_eq!(names, ["seq1", "seq2"]);
+/// assert_eq!(align.identifier(0), Some("seq1"));
+/// assert_eq!(*align.length(), 8);
+/// assert_eq!(annotations.file_annotations("ID"), ["example"]);
+/// assert_eq!(annotations.sequence_annotations("seq1", "AC"), ["P02668"]);
+/// assert_eq!(annotations.residue_annotation("seq1", "SS"), Some(&b"HHHHEE-E"[..]));
+/// assert_eq!(annotations.column_annotation("RF"), Some(&b"xxxxxx.."[..]));
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `reader` fails, if the `# STOCKHOLM` header or the `//` terminator are missing, if the sequences have different lengths, if a `#=GS` or `#=GR` line refers to an unknown sequence, or if some markup is not as long as the alignment.
+pub fn read_with_annotations<R>(
+    mut reader: R,
+) -> Result<(Vec<String>, Alignment<u8>, Annotations), MultiSeqAlignError>
+where
+    R: BufRead,
+{
+    let mut names: Vec<String> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+    let mut annotations = Annotations::new();
+
+    let mut line = Vec::new();
+    let mut line_number = 0_usize;
+    let mut header_found = false;
+    let mut terminated = false;
+
+    let invalid = |line: usize, message: &str| MultiSeqAlignError::InvalidFormat {
+        format: FORMAT,
+        line,
+        message: message.to_string(),
+    };
+
+    while super::read_line(&mut reader, &mut line)? {
+        line_number += 1;
+
+        if super::is_blank(&line) {
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim();
+
+        if !header_found {
+            if !text.starts_with("# STOCKHOLM") {
+                return Err(invalid(
+                    line_number,
+                    "expected a header line starting with '# STOCKHOLM'",
+                ));
+            }
+            header_found = true;
+            continue;
+        }
+
+        if text == "//" {
+            terminated = true;
+            break;
+        }
+
+        if let Some(markup) = text.strip_prefix("#=") {
+            parse_markup(&mut annotations, markup).ok_or_else(|| {
+                invalid(
+                    line_number,
+                    "expected a '#=GF', '#=GS', '#=GR' or '#=GC' markup line",
+                )
+            })?;
+            continue;
+        }
+
+        // other lines starting with '#' are comments
+        if text.starts_with('#') {
+            continue;
+        }
+
+        let (name, residues) = split_field(text);
+        if residues.is_empty() || residues.contains(char::is_whitespace) {
+            return Err(invalid(
+                line_number,
+                "expected a sequence name followed by residues",
+            ));
+        }
+
+        let index = *indices.entry(name.to_string()).or_insert_with(|| {
+            names.push(name.to_string());
+            sequences.push(Vec::new());
+            names.len() - 1
+        });
+        sequences[index].extend(residues.bytes());
+    }
+
+    if !header_found {
+        return Err(invalid(line_number, "missing '# STOCKHOLM' header"));
+    }
+    if !terminated {
+        return Err(invalid(line_number, "missing '//' terminator"));
+    }
+
+    let length = utils::first_sequence_length(&sequences);
+    utils::check_unequal_named_lengths(&sequences, &names, length)?;
+
+    if let Some(message) = check_markup_names(&annotations, &names)
+        .or_else(|| check_markup_lengths(&annotations, length))
+    {
+        return Err(invalid(line_number, &message));
+    }
+
+    let alignment = Alignment::with_named_sequences(&names, &sequences)?;
+
+    Ok((names, alignment, annotations))
+}
+
+/// Adds the annotation of a markup line (without the leading `#=`) to `annotations`
+///
+/// Returns `None` if the markup line is not a `GF`, `GS`, `GR` or `GC` line.
+fn parse_markup(annotations: &mut Annotations, markup: &str) -> Option<()> {
+    let (kind, rest) = split_field(markup);
+    match kind {
+        "GF" => {
+            let (feature, text) = split_field(rest);
+            annotations.add_file_annotation(feature, text);
+        }
+        "GS" => {
+            let (name, rest) = split_field(rest);
+            let (feature, text) = split_field(rest);
+            annotations.add_sequence_annotation(name, feature, text);
+        }
+        "GR" => {
+            let (name, rest) = split_field(rest);
+            let (feature, markup) = split_field(rest);
+            annotations.extend_residue_annotation(name, feature, markup.as_bytes());
+        }
+        "GC" => {
+            let (feature, markup) = split_field(rest);
+            annotations.extend_column_annotation(feature, markup.as_bytes());
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Returns an error message if some sequence or per-residue annotation refers to a sequence missing from `names`
+fn check_markup_names<S>(annotations: &Annotations, names: &[S]) -> Option<String>
+where
+    S: AsRef<str>,
+{
+    let is_unknown = |name: &str| !names.iter().any(|n| n.as_ref() == name);
+    annotations
+        .sequence
+        .iter()
+        .map(|(name, feature, _)| ("GS", name, feature))
+        .chain(
+            annotations
+                .residue
+                .iter()
+                .map(|(name, feature, _)| ("GR", name, feature)),
+        )
+        .find(|(_, name, _)| is_unknown(name))
+        .map(|(kind, name, feature)| {
+            format!("'#={kind} {name} {feature}' markup refers to an unknown sequence")
+        })
+}
+
+/// Returns an error message if some per-residue or per-column markup is not `length` long
+fn check_markup_lengths(annotations: &Annotations, length: usize) -> Option<String> {
+    annotations
+        .residue
+        .iter()
+        .map(|(name, feature, markup)| (format!("#=GR {name} {feature}"), markup.len()))
+        .chain(
+            annotations
+                .column
+                .iter()
+                .map(|(feature, markup)| (format!("#=GC {feature}"), markup.len())),
+        )
+        .find(|(_, markup_length)| *markup_length != length)
+        .map(|(markup, markup_length)| {
+            format!("'{markup}' markup has {markup_length} columns, expected {length} columns")
+        })
+}
+
+/// Splits `text` at the first whitespace, trimming the whitespace around the second part
+fn split_field(text: &str) -> (&str, &str) {
+    text.find(char::is_whitespace)
+        .map_or((text, ""), |index| (&text[..index], text[index..].trim()))
+}
+
+/// Writes Stockholm files
+///
+/// Sequences are written in a single block unless configured otherwise with [`block_width()`].
+///
+/// [`block_width()`]: struct.Writer.html#method.block_width
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Writer {
+    /// Number of residues per block, 0 for a single block
+    block_width: usize,
+}
+
+impl Writer {
+    /// Returns a `Writer` writing the alignment in a single block
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { block_width: 0 }
+    }
+
+    /// Sets the number of residues per block, `0` writes a single block
+    #[must_use]
+    pub const fn block_width(mut self, block_width: usize) -> Self {
+        self.block_width = block_width;
+        self
+    }
+
+    /// Writes `alignment` and its `annotations` to `writer`, naming each sequence by its identifier (or its row index)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::io::stockholm;
+    /// let align = Alignment::with_named_sequences(
+    ///     &["seq1", "seq2"],
+    ///     &[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()],
+    /// )
+    /// .unwrap();
+    /// let mut annotations = stockholm::Annotations::new();
+    /// annotations
+    ///     .add_file_annotation("ID", "example")
+    ///     .set_column_annotation("SS_cons", b"HHHH-EEE".to_vec());
+    ///
+    /// let mut output = Vec::new();
+    /// stockholm::Writer::new()
+    ///     .write(&mut output, &align, &annotations)
+    ///     .unwrap();
+    ///
+    /// let expected = [
+    ///     "# STOCKHOLM 1.0",
+    ///     "#=GF ID example",
+    ///     "",
+    ///     "seq1          ALKHITAN",
+    ///     "seq2          VLK-ITAN",
+    ///     "#=GC SS_cons  HHHH-EEE",
+    ///     "//",
+    /// ];
+    /// assert_eq!(String::from_utf8(output).unwrap().lines().collect::<Vec<_>>(), expected);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if some annotation refers to a name missing from the alignment, if some markup is not as long as the alignment, or if `writer` fails.
+    pub fn write<W, T>(
+        &self,
+        writer: W,
+        alignment: &Alignment<T>,
+        annotations: &Annotations,
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+    {
+        self.write_with_names(writer, alignment, &super::names(alignment), annotations)
+    }
+
+    /// Writes `alignment` and its `annotations` to `writer`, using one of `names` per sequence
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of `names` is different from the number of sequences, if some annotation refers to a name missing from `names`, if some markup is not as long as the alignment, or if `writer` fails.
+    pub fn write_with_names<W, T, S>(
+        &self,
+        mut writer: W,
+        alignment: &Alignment<T>,
+        names: &[S],
+        annotations: &Annotations,
+    ) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+        T: Clone + Into<u8>,
+        S: AsRef<str>,
+    {
+        if names.len() != *alignment.n_sequences() {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: *alignment.n_sequences(),
+                found: names.len(),
+            });
+        }
+        if let Some(message) = check_markup_names(annotations, names)
+            .or_else(|| check_markup_lengths(annotations, *alignment.length()))
+        {
+            return Err(MultiSeqAlignError::InvalidAnnotations {
+                format: FORMAT,
+                message,
+            });
+        }
+
+        writeln!(writer, "# STOCKHOLM 1.0")?;
+        for (feature, text) in &annotations.file {
+            writeln!(writer, "#=GF {feature} {text}")?;
+        }
+        for (name, feature, text) in &annotations.sequence {
+            writeln!(writer, "#=GS {name} {feature} {text}")?;
+        }
+
+        let residue_labels: Vec<(usize, String, &[u8])> = annotations
+            .residue
+            .iter()
+            .filter_map(|(name, feature, markup)| {
+                names
+                    .iter()
+                    .position(|n| n.as_ref() == name)
+                    .map(|index| (index, format!("#=GR {name} {feature}"), markup.as_slice()))
+            })
+            .collect();
+        let column_labels: Vec<(String, &[u8])> = annotations
+            .column
+            .iter()
+            .map(|(feature, markup)| (format!("#=GC {feature}"), markup.as_slice()))
+            .collect();
+
+        let label_width = names
+            .iter()
+            .map(|name| name.as_ref().len())
+            .chain(residue_labels.iter().map(|(_, label, _)| label.len()))
+            .chain(column_labels.iter().map(|(label, _)| label.len()))
+            .max()
+            .unwrap_or_default()
+            + 2;
+        let block_width = if self.block_width == 0 {
+            usize::MAX
+        } else {
+            self.block_width
+        };
+
+        let rows: Vec<Vec<u8>> = alignment
+            .iter_sequences()
+            .map(|sequence| sequence.into_iter().map(|x| x.clone().into()).collect())
+            .collect();
+
+        let mut start = 0_usize;
+        loop {
+            let end = start.saturating_add(block_width).min(*alignment.length());
+
+            writeln!(writer)?;
+            for (index, (name, row)) in names.iter().zip(&rows).enumerate() {
+                write_markup(&mut writer, name.as_ref(), &row[start..end], label_width)?;
+                for (_, label, markup) in residue_labels.iter().filter(|(i, _, _)| *i == index) {
+                    write_markup(&mut writer, label, &markup[start..end], label_width)?;
+                }
+            }
+            for (label, markup) in &column_labels {
+                write_markup(&mut writer, label, &markup[start..end], label_width)?;
+            }
+
+            start = end;
+            if start >= *alignment.length() {
+                break;
+            }
+        }
+
+        writeln!(writer, "//")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes one line of the alignment: a label padded to `label_width` followed by residues or markup
+fn write_markup<W>(
+    writer: &mut W,
+    label: &str,
+    markup: &[u8],
+    label_width: usize,
+) -> std::io::Result<()>
+where
+    W: Write,
+{
+    write!(writer, "{label:<label_width$}")?;
+    writer.write_all(markup)?;
+    writeln!(writer)
+}
+
+/// Writes `alignment` and its `annotations` to `writer` in the Stockholm format with the default [`Writer`] settings
+///
+/// # Errors
+///
+/// Will return an error if some annotation refers to a name missing from the alignment, if some markup is not as long as the alignment, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write<W, T>(
+    writer: W,
+    alignment: &Alignment<T>,
+    annotations: &Annotations,
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+{
+    Writer::new().write(writer, alignment, annotations)
+}
+
+/// Writes `alignment` and its `annotations` to `writer` in the Stockholm format with the default [`Writer`] settings, using one of `names` per sequence
+///
+/// # Errors
+///
+/// Will return an error if the number of `names` is different from the number of sequences, if some annotation refers to a name missing from `names`, if some markup is not as long as the alignment, or if `writer` fails.
+///
+/// [`Writer`]: struct.Writer.html
+pub fn write_with_names<W, T, S>(
+    writer: W,
+    alignment: &Alignment<T>,
+    names: &[S],
+    annotations: &Annotations,
+) -> Result<(), MultiSeqAlignError>
+where
+    W: Write,
+    T: Clone + Into<u8>,
+    S: AsRef<str>,
+{
+    Writer::new().write_with_names(writer, alignment, names, annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const PFAM: &[u8] = b"# STOCKHOLM 1.0
+#=GF ID   CASK
+#=GF CC   Kappa casein fragments
+#=GF CC   from three species
+#=GS BOVIN/1-14 AC P02668
+#=GS HUMAN/1-14 AC P07498
+
+BOVIN/1-14          PAPISKW
+#=GR BOVIN/1-14 SS  CCCHHHH
+HUMAN/1-14          HAQIPQR
+SHEEP/1-14          PAQILQW
+#=GC SS_cons        CCCHHHH
+#=GC RF             xxxxxxx
+
+BOVIN/1-14          QSMPAQE
+#=GR BOVIN/1-14 SS  HHH-EEE
+HUMAN/1-14          QYLPAQE
+SHEEP/1-14          QVLS-QE
+#=GC SS_cons        HHH.EEE
+#=GC RF             xxx.xxx
+//
+";
+
+    #[test]
+    fn read_multi_blocks() {
+        let (names, align, annotations) = read_with_annotations(PFAM).unwrap();
+
+        assert_eq!(names, vec!["BOVIN/1-14", "HUMAN/1-14", "SHEEP/1-14"]);
+        assert_eq!(
+            align,
+            Alignment::with_named_sequences(
+                &names,
+                &[
+                    b"PAPISKWQSMPAQE".to_vec(),
+                    b"HAQIPQRQYLPAQE".to_vec(),
+                    b"PAQILQWQVLS-QE".to_vec(),
+                ]
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            annotations.file_annotations("CC"),
+            vec!["Kappa casein fragments", "from three species"]
+        );
+        assert_eq!(
+            annotations.sequence_annotations("HUMAN/1-14", "AC"),
+            vec!["P07498"]
+        );
+        assert_eq!(
+            annotations.residue_annotation("BOVIN/1-14", "SS"),
+            Some(&b"CCCHHHHHHH-EEE"[..])
+        );
+        assert_eq!(annotations.residue_annotation("HUMAN/1-14", "SS"), None);
+        assert_eq!(
+            annotations.column_annotation("SS_cons"),
+            Some(&b"CCCHHHHHHH.EEE"[..])
+        );
+        assert_eq!(
+            annotations.column_annotation("RF"),
+            Some(&b"xxxxxxxxxx.xxx"[..])
+        );
+    }
+
+    #[test]
+    fn read_several_alignments() {
+        let input = b"# STOCKHOLM 1.0\r\nseq1 ALK\r\n//\r\n# STOCKHOLM 1.0\r\nseq1 HITAN\r\n//\r\n";
+        let mut reader = &input[..];
+
+        assert_eq!(*read(&mut reader).unwrap().length(), 3);
+        assert_eq!(*read(&mut reader).unwrap().length(), 5);
+    }
+
+    #[test]
+    fn read_missing_terminator() {
+        let error = read(&b"# STOCKHOLM 1.0\nseq1 ALK\n"[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "Stockholm",
+            line: 2,
+            message: "missing '//' terminator".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_markup_wrong_length() {
+        let input = b"# STOCKHOLM 1.0\nseq1 ALK\nseq2 VLK\n#=GC RF xx\n//\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "Stockholm",
+            line: 5,
+            message: "'#=GC RF' markup has 2 columns, expected 3 columns".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_unknown_sequence_markup() {
+        let input = b"# STOCKHOLM 1.0\n#=GS seq3 AC P02668\nseq1 ALK\nseq2 VLK\n//\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::InvalidFormat {
+            format: "Stockholm",
+            line: 5,
+            message: "'#=GS seq3 AC' markup refers to an unknown sequence".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn read_different_lengths() {
+        let input = b"# STOCKHOLM 1.0\nseq1 ALK\nseq2 VLKH\n//\n";
+        let error = read(&input[..]).err().unwrap();
+
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 3,
+            found_lengths: vec![4],
+            identifiers: vec!["seq2".to_string()],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn set_annotations() {
+        let mut annotations = Annotations::new();
+        assert!(annotations.is_empty());
+
+        annotations
+            .set_residue_annotation("seq1", "SS", b"HH".to_vec())
+            .set_residue_annotation("seq1", "SS", b"EE".to_vec())
+            .set_column_annotation("RF", b"x.".to_vec());
+
+        assert!(!annotations.is_empty());
+        assert_eq!(
+            annotations.residue_annotation("seq1", "SS"),
+            Some(&b"EE"[..])
+        );
+        assert_eq!(annotations.column_annotation("RF"), Some(&b"x."[..]));
+    }
+
+    #[test]
+    fn write_invalid_markup() {
+        let align =
+            Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
+        let mut unknown = Annotations::new();
+        unknown.set_residue_annotation("seq3", "SS", b"HHHHEE-E".to_vec());
+        let mut short = Annotations::new();
+        short.set_column_annotation("RF", b"xxxx".to_vec());
+
+        let error = write_with_names(Vec::new(), &align, &["seq1", "seq2"], &unknown)
+            .err()
+            .unwrap();
+        let expected = MultiSeqAlignError::InvalidAnnotations {
+            format: "Stockholm",
+            message: "'#=GR seq3 SS' markup refers to an unknown sequence".to_string(),
+        };
+        assert_eq!(error, expected);
+
+        let error = write_with_names(Vec::new(), &align, &["seq1", "seq2"], &short)
+            .err()
+            .unwrap();
+        let expected = MultiSeqAlignError::InvalidAnnotations {
+            format: "Stockholm",
+            message: "'#=GC RF' markup has 4 columns, expected 8 columns".to_string(),
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let (names, align, annotations) = read_with_annotations(PFAM).unwrap();
+
+        for writer in [Writer::new(), Writer::new().block_width(5)] {
+            let mut output = Vec::new();
+            writer.write(&mut output, &align, &annotations).unwrap();
+
+            assert_eq!(
+                read_with_annotations(&output[..]).unwrap(),
+                (names.clone(), align.clone(), annotations.clone())
+            );
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+
+/// Storage of the residues of an [`Alignment`]
+///
+/// The rows are always stored back to back, so that they can be borrowed as slices. The other layouts add a column-major copy of the residues, which makes the columns contiguous for the column accessors and the conservation scores at the cost of twice the memory.
+///
+/// [`Alignment`]: struct.Alignment.html
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Layout {
+    /// Rows only, the residues of a column are `length` residues apart
+    #[default]
+    RowMajor,
+    /// Rows and a column-major copy, built as soon as the layout is set, then rebuilt on the first column access after the alignment changes
+    ColumnMajor,
+    /// Rows and a column-major copy, built on the first column access after the layout is set or the alignment changes
+    Lazy,
+}
+
+/// Builds the column-major copy of `n_sequences` rows of `length` residues
+type Transpose<T> = fn(&[T], usize, usize) -> Vec<T>;
+
+/// Column-major copy of the residues, ignored when comparing or hashing alignments
+pub struct Transposed<T> {
+    /// Layout of the alignment
+    layout: Layout,
+    /// Builds the copy, `None` for the row-major layout
+    transpose: Option<Transpose<T>>,
+    /// Residues, column after column, once built
+    columns: OnceLock<Vec<T>>,
+}
+
+impl<T> Transposed<T> {
+    /// Returns the copy of the row-major layout, which is never built
+    pub const fn row_major() -> Self {
+        Self {
+            layout: Layout::RowMajor,
+            transpose: None,
+            columns: OnceLock::new(),
+        }
+    }
+
+    /// Returns an empty copy for `layout`
+    pub fn new(layout: Layout) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            layout,
+            transpose: match layout {
+                Layout::RowMajor => None,
+                Layout::ColumnMajor | Layout::Lazy => Some(transpose),
+            },
+            columns: OnceLock::new(),
+        }
+    }
+
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Drops the copy of the previous residues, to be rebuilt on the next column access
+    pub fn invalidate(&mut self) {
+        self.columns = OnceLock::new();
+    }
+
+    /// Returns the residues of `sequences` column after column, building them if needed, `None` with the row-major layout
+    pub fn columns(&self, sequences: &[T], n_sequences: usize, length: usize) -> Option<&[T]> {
+        let transpose = self.transpose?;
+        Some(
+            self.columns
+                .get_or_init(|| transpose(sequences, n_sequences, length)),
+        )
+    }
+}
+
+/// Returns the residues of `sequences`, `n_sequences` rows of `length` residues, column after column
+fn transpose<T>(sequences: &[T], n_sequences: usize, length: usize) -> Vec<T>
+where
+    T: Clone,
+{
+    let mut columns = Vec::with_capacity(sequences.len());
+    for column in 0..length {
+        columns.extend((0..n_sequences).map(|row| sequences[row * length + column].clone()));
+    }
+    columns
+}
+
+impl<T> Default for Transposed<T> {
+    fn default() -> Self {
+        Self::row_major()
+    }
+}
+
+impl<T> Clone for Transposed<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            layout: self.layout,
+            transpose: self.transpose,
+            columns: self.columns.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Transposed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transposed")
+            .field("layout", &self.layout)
+            .field("built", &self.columns.get().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> PartialEq for Transposed<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for Transposed<T> {}
+
+impl<T> PartialOrd for Transposed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Transposed<T> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl<T> Hash for Transposed<T> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Alignment;
+    use pretty_assertions::assert_eq;
+
+    const LAYOUTS: [Layout; 3] = [Layout::RowMajor, Layout::ColumnMajor, Layout::Lazy];
+
+    fn alignment(layout: Layout) -> Alignment<u8> {
+        let mut align =
+            Alignment::with_sequences(&[b"ACGTA".to_vec(), b"AC-TT".to_vec(), b"TTGTC".to_vec()])
+                .unwrap();
+        align.set_layout(layout);
+        align
+    }
+
+    fn positions(align: &Alignment<u8>) -> Vec<Vec<u8>> {
+        align
+            .iter_positions()
+            .map(|position| position.into_iter().copied().collect())
+            .collect()
+    }
+
+    fn columns(align: &Alignment<u8>) -> Vec<Vec<u8>> {
+        align
+            .iter_columns()
+            .map(|column| column.into_iter().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn transpose_rows() {
+        assert_eq!(transpose(b"ABCDEF", 2, 3), b"ADBECF".to_vec());
+        assert_eq!(transpose::<u8>(&[], 0, 3), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn same_columns() {
+        let expected = positions(&alignment(Layout::RowMajor));
+
+        for &layout in &LAYOUTS {
+            let align = alignment(layout);
+            assert_eq!(align.layout(), layout);
+            assert_eq!(positions(&align), expected);
+            assert_eq!(columns(&align), expected);
+            assert_eq!(align.column(2).unwrap()[1], b'-');
+            assert_eq!(align, alignment(Layout::RowMajor));
+        }
+    }
+
+    #[test]
+    fn lazy_copy() {
+        let align = alignment(Layout::Lazy);
+        assert!(align.transposed.columns.get().is_none());
+
+        assert!(align.nth_position(0).is_some());
+        assert_eq!(
+            align.transposed.columns.get(),
+            Some(&b"AATCCTG-GTTTATC".to_vec())
+        );
+        assert!(alignment(Layout::ColumnMajor)
+            .transposed
+            .columns
+            .get()
+            .is_some());
+        assert!(alignment(Layout::RowMajor).transposed().is_none());
+    }
+
+    #[test]
+    fn modified_alignment() {
+        for &layout in &LAYOUTS {
+            let mut align = alignment(layout);
+            assert!(align.nth_position(0).is_some());
+            align.add(b"GGGGG".to_vec()).unwrap();
+
+            assert_eq!(
+                align.nth_position(4).unwrap(),
+                vec![&b'A', &b'T', &b'C', &b'G']
+            );
+            assert_eq!(columns(&align), positions(&align));
+
+            let selected = align.select_columns(&[4, 0]).unwrap();
+            assert_eq!(selected.layout(), layout);
+            assert_eq!(
+                selected.nth_position(1).unwrap(),
+                vec![&b'A', &b'A', &b'T', &b'G']
+            );
+        }
+    }
+
+    #[test]
+    fn rebuilt_on_access() {
+        let mut align = alignment(Layout::ColumnMajor);
+        align.add(b"GGGGG".to_vec()).unwrap();
+        assert!(align.transposed.columns.get().is_none());
+
+        assert_eq!(align.column(4).unwrap()[3], b'G');
+        assert!(align.transposed.columns.get().is_some());
+    }
+
+    #[test]
+    fn residues_without_clone() {
+        #[derive(PartialEq, Debug)]
+        struct Residue(u8);
+
+        let mut align = Alignment::new(2);
+        align
+            .add(vec![Residue(b'A'), Residue(b'C')])
+            .unwrap()
+            .add_named("seq2", vec![Residue(b'G'), Residue(b'T')])
+            .unwrap();
+
+        assert_eq!(
+            align.nth_position(1).unwrap(),
+            vec![&Residue(b'C'), &Residue(b'T')]
+        );
+        assert_eq!(align.column(0).unwrap()[1], Residue(b'G'));
+        assert_eq!(align.iter_columns().len(), 2);
+    }
+
+    #[test]
+    fn send_sync() {
+        const fn send_sync<T: Send + Sync>() {}
+        send_sync::<Alignment<u8>>();
+    }
+
+    #[test]
+    fn back_to_row_major() {
+        let mut align = alignment(Layout::ColumnMajor);
+        align.set_layout(Layout::RowMajor);
+
+        assert_eq!(align.layout(), Layout::RowMajor);
+        assert!(align.transposed().is_none());
+        assert_eq!(positions(&align), positions(&alignment(Layout::Lazy)));
+    }
+}
@@ -51,34 +51,100 @@ Here I instancied an alignment using `u8`, but `Alignment` works on generics lik
 # Features
 
 - Create [`Alignment`] from one or multiple aligned sequences at once (see [`add()`] and [`with_sequences()`]).
+- Build large alignments one record at a time (see [`AlignmentBuilder`]).
+- Check the residues against DNA, RNA, protein or IUPAC alphabets (see [`alphabet`]).
+- Compute consensus sequences, with thresholds, weights and IUPAC ambiguity codes (see [`consensus`]).
+- Score the conservation of each column with Shannon entropy, Jensen–Shannon divergence, Capra & Singh's window smoothing or Valdar's score (see [`conservation`]).
+- Build residue frequency profiles with pseudocounts (constant, background-scaled or Dirichlet mixture) and log-odds PSSMs, written in the PSI-BLAST ASCII layout (see [`profile`]).
+- Weight sequences with Henikoff position-based or identity cluster weights, and compute the effective number of sequences (see [`weights`]).
+- Compute all-vs-all identity matrices with a choice of denominator, stored as a compact symmetric matrix and optionally in parallel with the `rayon` feature (see [`identity`] and [`matrix`]).
+- Compute evolutionary distance matrices (p-distance, Jukes–Cantor, Kimura 2P, Tamura–Nei, `LogDet`, Poisson, Kimura protein) with pairwise or complete deletion (see [`distance`]).
+- Build neighbor joining and UPGMA trees with branch lengths, and write them in the Newick format (see [`tree`]).
+- Draw seeded bootstrap and delete-half jackknife replicates, as alignments or column weights (see [`resampling`]).
+- Trim gappy or poorly conserved columns with gap thresholds, trimAl's gappyout and strict heuristics or conservation cutoffs, keeping the map to the original columns (see [`trim`]).
+- Remove all-gap or invariant columns and gappy sequences, reporting the removed indices (see [`remove_gap_columns()`], [`remove_invariant_columns()`] and [`remove_gappy_sequences()`]).
+- Name sequences with unique identifiers and descriptions, and look them up by identifier (see [`with_named_sequences()`] and [`index_of()`]).
 - Extract columns of the alignment (see [`iter_positions()`] and [`iter_sequences(`]).
+- Borrow rows as slices, columns as strided views and blocks as 2D views without allocating (see [`view`]).
+- Keep a column-major copy of the residues, eagerly or lazily, for column-heavy workloads (see [`Layout`] and the `layout` benchmark).
+- Extract sub-alignments by column ranges, index lists or boolean masks, and by row selections (see [`select`]).
+- Read and write alignment files (see [`io`]):
+    - aligned FASTA
+    - Clustal
+    - Stockholm
+    - PHYLIP
+    - NEXUS
+    - A2M / A3M
+    - MSF
+    - PIR / NBRF
+- Detect the format of alignment files (see [`io::detect`])
+
 This crate is currently in early stage development. I wouldn't recommend using it in production but I am interested in possible ideas to further the developemt of this project. Quite some work needs toi be done to improve the API and make it easy to use in other project.
 # Ideas
-- Computation of conservation scores
-- Identification of conserved sites
-- Computation of consensus sequence
-- Collapse / trim alignment
+- Collapse alignment
 - Serialisation / Deserialisation of alignment files
-- Extract sub-alignments
-    - positions
-    - motifs
+- Extract sub-alignments matching motifs
 
 # Optimisation
 
 My goal is to reduce the footprint of this crate, there is ome work to do to achieve it. The code will eventually be optimised to be faster and to better use memory.
 
 [`Alignment`]: struct.Alignment.html
+[`AlignmentBuilder`]: struct.AlignmentBuilder.html
 [`iter_positions()`]: struct.Alignment.html#method.iter_positions
 [`iter_sequences(`]: struct.Alignment.html#method.iter_sequences
 [`add()`]: struct.Alignment.html#method.add
 [`with_sequences()`]: struct.Alignment.html#method.with_sequences
+[`with_named_sequences()`]: struct.Alignment.html#method.with_named_sequences
+[`index_of()`]: struct.Alignment.html#method.index_of
+[`io`]: io/index.html
+[`alphabet`]: alphabet/index.html
+[`consensus`]: consensus/index.html
+[`conservation`]: conservation/index.html
+[`profile`]: profile/index.html
+[`weights`]: weights/index.html
+[`identity`]: identity/index.html
+[`matrix`]: matrix/index.html
+[`distance`]: distance/index.html
+[`tree`]: tree/index.html
+[`resampling`]: resampling/index.html
+[`trim`]: trim/index.html
+[`remove_gap_columns()`]: struct.Alignment.html#method.remove_gap_columns
+[`remove_invariant_columns()`]: struct.Alignment.html#method.remove_invariant_columns
+[`remove_gappy_sequences()`]: struct.Alignment.html#method.remove_gappy_sequences
+[`select`]: select/index.html
+[`view`]: view/index.html
+[`Layout`]: enum.Layout.html
+[`io::detect`]: io/fn.detect.html
 */
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 
+pub mod alphabet;
+mod builder;
+pub mod consensus;
+pub mod conservation;
+pub mod distance;
 mod errors;
+mod identifiers;
+pub mod identity;
+pub mod io;
+mod layout;
+pub mod matrix;
+pub mod profile;
+pub mod resampling;
+pub mod select;
+mod substitution;
+pub mod tree;
+pub mod trim;
 mod utils;
-
-use errors::MultiSeqAlignError;
+pub mod view;
+pub mod weights;
+
+pub use builder::AlignmentBuilder;
+pub use errors::MultiSeqAlignError;
+pub use layout::Layout;
+use layout::Transposed;
+use std::collections::BTreeMap;
 use std::iter::FromIterator;
 
 #[cfg(feature = "serde")]
@@ -97,6 +163,15 @@ pub struct Alignment<T> {
     n_sequences: usize,
     /// The length of the alignment
     length: usize,
+    /// Identifiers of the sequences, `None` for unnamed sequences
+    identifiers: Vec<Option<String>>,
+    /// Descriptions of the sequences
+    descriptions: Vec<Option<String>>,
+    /// Index of the sequence of each identifier
+    index: BTreeMap<String, usize>,
+    /// Column-major copy of the sequences, depending on the layout
+    #[cfg_attr(feature = "serde", serde(skip))]
+    transposed: Transposed<T>,
 }
 
 impl<T> Default for Alignment<T>
@@ -108,6 +183,10 @@ where
             sequences: Vec::<T>::default(),
             n_sequences: 0_usize,
             length: 0_usize,
+            identifiers: Vec::new(),
+            descriptions: Vec::new(),
+            index: BTreeMap::new(),
+            transposed: Transposed::row_major(),
         }
     }
 }
@@ -137,15 +216,15 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        if self.size_hint < usize::max_value() {
+        if self.size_hint < usize::MAX {
             (self.size_hint, Some(self.size_hint))
         } else {
-            (usize::max_value(), None)
+            (usize::MAX, None)
         }
     }
 }
 
-impl<'a, T> ExactSizeIterator for AlignmentPositionIterator<'a, T>
+impl<T> ExactSizeIterator for AlignmentPositionIterator<'_, T>
 where
     T: Clone,
 {
@@ -191,16 +270,16 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        if self.size_hint < usize::max_value() {
+        if self.size_hint < usize::MAX {
             // ?
             (self.size_hint, Some(self.size_hint))
         } else {
-            (usize::max_value(), None)
+            (usize::MAX, None)
         }
     }
 }
 
-impl<'a, T> ExactSizeIterator for AlignmentSequenceIterator<'a, T>
+impl<T> ExactSizeIterator for AlignmentSequenceIterator<'_, T>
 where
     T: Clone,
 {
@@ -228,8 +307,56 @@ impl<T> Alignment<T> {
         &self.n_sequences
     }
 
+    /// Returns the storage layout of `self`
+    #[must_use]
+    pub const fn layout(&self) -> Layout {
+        self.transposed.layout()
+    }
+
+    /// Sets the storage layout of `self`, [`Layout::RowMajor`] by default
+    ///
+    /// The column-major layouts speed up the column accessors ([`nth_position()`], [`iter_positions()`], [`column()`] and [`iter_columns()`]) and [`Conservation`], but double the memory used by the residues. The other column scans, such as the consensus, the trimming and the weights, still read the rows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::{Alignment, Layout};
+    /// let mut align = Alignment::with_sequences(&[b"ELK".to_vec(), b"ILK".to_vec()]).unwrap();
+    /// align.set_layout(Layout::Lazy);
+    ///
+    /// assert_eq!(align.layout(), Layout::Lazy);
+    /// assert_eq!(align.nth_position(0).unwrap(), &[&b'E', &b'I']);
+    /// ```
+    ///
+    /// [`Layout::RowMajor`]: enum.Layout.html#variant.RowMajor
+    /// [`nth_position()`]: #method.nth_position
+    /// [`iter_positions()`]: #method.iter_positions
+    /// [`column()`]: #method.column
+    /// [`iter_columns()`]: #method.iter_columns
+    /// [`Conservation`]: conservation/struct.Conservation.html
+    pub fn set_layout(&mut self, layout: Layout) -> &mut Self
+    where
+        T: Clone,
+    {
+        if layout != self.layout() {
+            self.transposed = Transposed::new(layout);
+            if layout == Layout::ColumnMajor {
+                self.transposed();
+            }
+        }
+        self
+    }
+
+    /// Returns the residues of `self` column after column, `None` with the row-major layout
+    pub(crate) fn transposed(&self) -> Option<&[T]> {
+        self.transposed
+            .columns(&self.sequences, self.n_sequences, self.length)
+    }
+
     /// Returns an Iterator over the positions of the alignment
     ///
+    /// Each position is collected in a new `Vec`, see [`iter_columns()`] to borrow them as views instead.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -247,6 +374,9 @@ impl<T> Alignment<T> {
     ///     assert_eq!(position.len(), 3)
     /// }
     /// ```
+    ///
+    /// [`iter_columns()`]: #method.iter_columns
+    #[allow(clippy::implied_bounds_in_impls)]
     pub fn iter_positions(
         &self,
     ) -> impl Iterator<Item = Vec<&T>> + ExactSizeIterator<Item = Vec<&T>>
@@ -262,6 +392,8 @@ impl<T> Alignment<T> {
 
     /// Returns an Iterator over the sequences of the alignment
     ///
+    /// Each sequence is collected in a new `Vec`, see [`iter_rows()`] to borrow them as slices instead.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -279,6 +411,9 @@ impl<T> Alignment<T> {
     ///     assert_eq!(sequence.len(), 8)
     /// }
     /// ```
+    ///
+    /// [`iter_rows()`]: #method.iter_rows
+    #[allow(clippy::implied_bounds_in_impls)]
     pub fn iter_sequences(
         &self,
     ) -> impl Iterator<Item = Vec<&T>> + ExactSizeIterator<Item = Vec<&T>>
@@ -310,6 +445,10 @@ impl<T> Alignment<T> {
             sequences: Vec::new(),
             n_sequences: 0_usize,
             length,
+            identifiers: Vec::new(),
+            descriptions: Vec::new(),
+            index: BTreeMap::new(),
+            transposed: Transposed::row_major(),
         }
     }
 
@@ -360,13 +499,81 @@ impl<T> Alignment<T> {
 
         let n_sequences = sequences.len();
 
-        let sequences_vec = sequences.iter().flat_map(|x| x.to_vec()).collect();
+        let sequences_vec = sequences.iter().flat_map(Clone::clone).collect();
 
-        Ok(Self {
-            sequences: sequences_vec,
+        Ok(Self::from_parts(sequences_vec, n_sequences, length))
+    }
+
+    /// Returns an `Alignment` of unnamed sequences from its flat buffer
+    pub(crate) fn from_parts(sequences: Vec<T>, n_sequences: usize, length: usize) -> Self {
+        Self {
+            sequences,
             n_sequences,
             length,
-        })
+            identifiers: vec![None; n_sequences],
+            descriptions: vec![None; n_sequences],
+            index: BTreeMap::new(),
+            transposed: Transposed::row_major(),
+        }
+    }
+
+    /// Returns an `Alignment` made of the `columns` of `self`, in that order, keeping the identifiers, descriptions and layout
+    pub(crate) fn with_columns(&self, columns: &[usize]) -> Self
+    where
+        T: Clone,
+    {
+        let mut sequences = Vec::with_capacity(self.n_sequences * columns.len());
+        for row in self.sequences.chunks_exact(self.length.max(1)) {
+            sequences.extend(columns.iter().map(|&column| row[column].clone()));
+        }
+
+        let mut alignment = Self {
+            sequences,
+            n_sequences: self.n_sequences,
+            length: columns.len(),
+            identifiers: self.identifiers.clone(),
+            descriptions: self.descriptions.clone(),
+            index: self.index.clone(),
+            transposed: Transposed::row_major(),
+        };
+        alignment.set_layout(self.layout());
+        alignment
+    }
+
+    /// Returns an `Alignment` made of the distinct `rows` of `self`, in that order, keeping their identifiers and descriptions, and the layout
+    pub(crate) fn with_rows(&self, rows: &[usize]) -> Self
+    where
+        T: Clone,
+    {
+        let mut sequences = Vec::with_capacity(rows.len() * self.length);
+        for &row in rows {
+            sequences
+                .extend_from_slice(&self.sequences[row * self.length..(row + 1) * self.length]);
+        }
+        let identifiers: Vec<Option<String>> = rows
+            .iter()
+            .map(|&row| self.identifiers[row].clone())
+            .collect();
+        let index = identifiers
+            .iter()
+            .enumerate()
+            .filter_map(|(row, identifier)| Some((identifier.clone()?, row)))
+            .collect();
+
+        let mut alignment = Self {
+            sequences,
+            n_sequences: rows.len(),
+            length: self.length,
+            identifiers,
+            descriptions: rows
+                .iter()
+                .map(|&row| self.descriptions[row].clone())
+                .collect(),
+            index,
+            transposed: Transposed::row_major(),
+        };
+        alignment.set_layout(self.layout());
+        alignment
     }
 
     /// Add a sequence to `self`
@@ -397,7 +604,7 @@ impl<T> Alignment<T> {
     /// # Errors
     ///
     /// Will return an error if the length of `sequence` is different from the one of the alignment.
-    pub fn add<'a>(&'a mut self, sequence: Vec<T>) -> Result<&'a mut Self, MultiSeqAlignError> {
+    pub fn add(&mut self, sequence: Vec<T>) -> Result<&mut Self, MultiSeqAlignError> {
         if sequence.len() != self.length {
             return Err(MultiSeqAlignError::NewSequenceOfDifferentLength {
                 expected_length: self.length,
@@ -406,8 +613,11 @@ impl<T> Alignment<T> {
         }
 
         self.sequences.extend(sequence);
+        self.identifiers.push(None);
+        self.descriptions.push(None);
 
         self.n_sequences += 1;
+        self.transposed.invalidate();
 
         Ok(self)
     }
@@ -431,6 +641,13 @@ impl<T> Alignment<T> {
     #[must_use]
     pub fn nth_position(&self, n: usize) -> Option<Vec<&T>> {
         assert!(n < self.length);
+        if let Some(columns) = self.transposed() {
+            return Some(
+                columns[n * self.n_sequences..(n + 1) * self.n_sequences]
+                    .iter()
+                    .collect(),
+            );
+        }
         (0..self.n_sequences)
             .map(|i| self.sequences.get(i * self.length + n))
             .collect::<Vec<Option<&T>>>()
@@ -487,15 +704,11 @@ where
                 }
 
                 n_sequences += 1;
-                x.to_vec()
+                x
             })
             .collect::<Vec<_>>();
 
-        Self {
-            sequences,
-            n_sequences,
-            length: length.unwrap(),
-        }
+        Self::from_parts(sequences, n_sequences, length.unwrap())
     }
 }
 
@@ -529,7 +742,7 @@ mod tests {
         align.add(b"ALRYITAT".to_vec()).unwrap();
 
         assert_eq!(align.n_sequences, 3_usize);
-        assert_eq!(align.nth_position(3).unwrap(), vec![&b'H', &b'-', &b'Y'])
+        assert_eq!(align.nth_position(3).unwrap(), vec![&b'H', &b'-', &b'Y']);
     }
 
     #[test]
@@ -571,7 +784,7 @@ mod tests {
     fn nth_residues_3() {
         let align =
             Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
-        assert_eq!(align.nth_position(3).unwrap(), vec![&b'H', &b'-'])
+        assert_eq!(align.nth_position(3).unwrap(), vec![&b'H', &b'-']);
     }
 
     #[test]
@@ -586,7 +799,7 @@ mod tests {
         assert_eq!(
             align.nth_position(3).unwrap(),
             vec![&b'H', &b'-', &b'W', &b'M']
-        )
+        );
     }
 
     #[test]
@@ -606,6 +819,7 @@ mod tests {
         let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
             expected_length: 8,
             found_lengths: vec![11],
+            identifiers: vec![],
         };
         assert_eq!(error, expected);
     }
@@ -622,12 +836,13 @@ mod tests {
         }
 
         assert_eq!(x.len(), 8);
-        assert_eq!(x.get(0).unwrap(), &[&b'A', &b'V']);
+        assert_eq!(x.first().unwrap(), &[&b'A', &b'V']);
         assert_eq!(x.get(3).unwrap(), &[&b'H', &b'-']);
     }
 
     #[test]
     #[should_panic]
+    #[allow(clippy::should_panic_without_expect)]
     fn for_positions_out_of_bonds() {
         let align =
             Alignment::with_sequences(&[b"ALKHITAN".to_vec(), b"VLK-ITAN".to_vec()]).unwrap();
@@ -661,7 +876,7 @@ mod tests {
             x.push(row);
         }
 
-        assert_eq!(x.len(), 2)
+        assert_eq!(x.len(), 2);
     }
 
     #[test]
@@ -0,0 +1,173 @@
+/*! Symmetric matrices
+
+A [`SymmetricMatrix`] stores a square symmetric matrix, such as pairwise identities or distances between the sequences of an alignment, as its lower triangle (diagonal included) packed row after row: `n (n + 1) / 2` values instead of `n²`.
+
+```rust
+# use multi_seq_align::matrix::SymmetricMatrix;
+let mut matrix = SymmetricMatrix::from_fn(3, |i, j| i * 10 + j);
+
+assert_eq!(matrix[(2, 1)], 21);
+assert_eq!(matrix[(1, 2)], 21);
+matrix[(0, 2)] = 7;
+assert_eq!(matrix.get(2, 0), Some(&7));
+assert_eq!(matrix.get(3, 0), None);
+assert_eq!(matrix.values(), &[0, 10, 11, 7, 21, 22]);
+```
+
+[`SymmetricMatrix`]: struct.SymmetricMatrix.html
+*/
+
+use std::ops::{Index, IndexMut};
+
+/// Square symmetric matrix storing its lower triangle
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SymmetricMatrix<T> {
+    /// Number of rows and columns
+    size: usize,
+    /// Values of the lower triangle, row after row
+    values: Vec<T>,
+}
+
+impl<T> SymmetricMatrix<T> {
+    /// Returns a `size` × `size` matrix filled with `value`
+    #[must_use]
+    pub fn new(size: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            size,
+            values: vec![value; size * (size + 1) / 2],
+        }
+    }
+
+    /// Returns a `size` × `size` matrix whose value at row `i` and column `j` is `f(i, j)`, `f` being only called for `j <= i`
+    pub fn from_fn<F>(size: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let mut values = Vec::with_capacity(size * (size + 1) / 2);
+        for i in 0..size {
+            for j in 0..=i {
+                values.push(f(i, j));
+            }
+        }
+        Self { size, values }
+    }
+
+    /// Returns the number of rows (and columns)
+    #[must_use]
+    pub const fn size(&self) -> &usize {
+        &self.size
+    }
+
+    /// Returns the index of the value at row `i` and column `j` in the packed lower triangle
+    const fn offset(i: usize, j: usize) -> usize {
+        let (i, j) = if i >= j { (i, j) } else { (j, i) };
+        i * (i + 1) / 2 + j
+    }
+
+    /// Returns the value at row `i` and column `j`, `None` if out of the matrix
+    #[must_use]
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        if i < self.size && j < self.size {
+            self.values.get(Self::offset(i, j))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value at row `i` and column `j` (and at row `j` and column `i`), `None` if out of the matrix
+    #[must_use]
+    pub fn get_mut(&mut self, i: usize, j: usize) -> Option<&mut T> {
+        if i < self.size && j < self.size {
+            self.values.get_mut(Self::offset(i, j))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the values of row `i`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is greater or equal to the size of the matrix.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = &T> {
+        assert!(i < self.size);
+        (0..self.size).map(move |j| &self.values[Self::offset(i, j)])
+    }
+
+    /// Returns the values of the lower triangle (diagonal included), row after row
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the lower triangle rows, row `i` having `i + 1` values, to be filled independently
+    pub(crate) fn rows_mut(&mut self) -> Vec<&mut [T]> {
+        let mut rows = Vec::with_capacity(self.size);
+        let mut rest = self.values.as_mut_slice();
+        for i in 0..self.size {
+            let (row, tail) = rest.split_at_mut(i + 1);
+            rows.push(row);
+            rest = tail;
+        }
+        rows
+    }
+}
+
+impl<T> Index<(usize, usize)> for SymmetricMatrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.size && j < self.size, "index out of the matrix");
+        &self.values[Self::offset(i, j)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for SymmetricMatrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(i < self.size && j < self.size, "index out of the matrix");
+        &mut self.values[Self::offset(i, j)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn packed_lower_triangle() {
+        let matrix = SymmetricMatrix::from_fn(4, |i, j| (i, j));
+
+        assert_eq!(matrix.values().len(), 10);
+        assert_eq!(matrix[(1, 3)], (3, 1));
+        assert_eq!(
+            matrix.row(2).copied().collect::<Vec<_>>(),
+            vec![(2, 0), (2, 1), (2, 2), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn rows_mut() {
+        let mut matrix = SymmetricMatrix::new(3, 0);
+        for (i, row) in matrix.rows_mut().into_iter().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = i + j;
+            }
+        }
+
+        assert_eq!(matrix.values(), &[0, 1, 2, 2, 3, 4]);
+        assert_eq!(*matrix.size(), 3);
+        assert!(SymmetricMatrix::new(0, 0.0).values().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of the matrix")]
+    fn out_of_the_matrix() {
+        let matrix = SymmetricMatrix::new(2, 0);
+        let _ = matrix[(2, 0)];
+    }
+}
@@ -0,0 +1,741 @@
+/*! Residue frequency profiles and position-specific scoring matrices
+
+A [`Profile`] holds the (weighted) count of each symbol in each column of an alignment. Frequencies can be smoothed with [`Pseudocounts`] and turned into a log-odds [`Pssm`] against a background distribution, which can be written in the ASCII layout of PSI-BLAST.
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::profile::Pseudocounts;
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"VLK-ITAN".to_vec(),
+    b"ALRYITAT".to_vec(),
+])
+.unwrap();
+
+let profile = align.profile();
+// the symbols are the 20 amino acids, in the order of PSI-BLAST
+assert_eq!(profile.symbols()[0], b'A');
+assert_eq!(profile.counts(0)[0], 2.0);
+assert_eq!(profile.gaps(3), 1.0);
+
+let pssm = profile.pssm(&Pseudocounts::Background(1.0)).unwrap();
+// leucine is conserved in the second column
+assert!(pssm.score(1, b'L').unwrap() > 2.0);
+
+let mut output = Vec::new();
+pssm.write_psiblast(&mut output).unwrap();
+```
+
+[`Profile`]: struct.Profile.html
+[`Pseudocounts`]: enum.Pseudocounts.html
+[`Pssm`]: struct.Pssm.html
+*/
+
+use crate::errors::MultiSeqAlignError;
+pub use crate::substitution::AMINO_ACIDS;
+use crate::substitution::{self, BLOSUM62_BACKGROUND};
+use crate::{utils, Alignment};
+use std::io::Write;
+
+/// Lowest and highest scores written in the PSI-BLAST layout
+const PSIBLAST_SCORE_RANGE: (f64, f64) = (-99.0, 99.0);
+
+/// Pseudocounts added to the observed counts when computing frequencies
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum Pseudocounts {
+    /// Observed frequencies only
+    #[default]
+    None,
+    /// The same count added to every symbol: `(n_a + c) / (N + K c)`
+    Constant(f64),
+    /// A total count spread over the symbols following the background frequencies: `(n_a + w b_a) / (N + w)`
+    Background(f64),
+    /// Posterior mean frequencies under a Dirichlet mixture prior
+    Dirichlet(DirichletMixture),
+}
+
+/// Mixture of Dirichlet distributions, used as a prior on the frequencies of a column
+///
+/// Each component has a mixture coefficient and one positive parameter per symbol of the profile, in the same order. Published mixtures (for example the ones of Sjölander et al. 1996) list the amino acids in various orders: they must be reordered like [`AMINO_ACIDS`].
+///
+/// [`AMINO_ACIDS`]: constant.AMINO_ACIDS.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct DirichletMixture {
+    /// Mixture coefficient and parameters of each component
+    components: Vec<(f64, Vec<f64>)>,
+}
+
+impl DirichletMixture {
+    /// Returns a mixture of `components`, each one being a mixture coefficient and its parameters
+    #[must_use]
+    pub const fn new(components: Vec<(f64, Vec<f64>)>) -> Self {
+        Self { components }
+    }
+
+    /// Returns the mixture coefficient and parameters of each component
+    #[must_use]
+    pub fn components(&self) -> &[(f64, Vec<f64>)] {
+        &self.components
+    }
+
+    /// Returns the posterior mean frequencies of a column, and the total pseudocount
+    fn frequencies(&self, counts: &[f64]) -> Result<(Vec<f64>, f64), MultiSeqAlignError> {
+        for (_, parameters) in &self.components {
+            check_symbols(parameters, counts.len())?;
+        }
+        let total: f64 = counts.iter().sum();
+
+        // log of the probability of the counts under each component, up to a constant
+        let log_probabilities: Vec<f64> = self
+            .components
+            .iter()
+            .map(|(coefficient, parameters)| {
+                let sum: f64 = parameters.iter().sum();
+                let terms: f64 = counts
+                    .iter()
+                    .zip(parameters)
+                    .map(|(&count, &parameter)| ln_gamma(count + parameter) - ln_gamma(parameter))
+                    .sum();
+                coefficient.ln() + ln_gamma(sum) - ln_gamma(total + sum) + terms
+            })
+            .collect();
+        let max = log_probabilities
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let posteriors: Vec<f64> = log_probabilities
+            .iter()
+            .map(|probability| (probability - max).exp())
+            .collect();
+        let normalization: f64 = posteriors.iter().sum();
+
+        let mut frequencies = vec![0.0; counts.len()];
+        let mut pseudocount = 0.0;
+        for (posterior, (_, parameters)) in posteriors.iter().zip(&self.components) {
+            let posterior = posterior / normalization;
+            let sum: f64 = parameters.iter().sum();
+            for ((frequency, &count), &parameter) in
+                frequencies.iter_mut().zip(counts).zip(parameters)
+            {
+                *frequency += posterior * (count + parameter) / (total + sum);
+            }
+            pseudocount += posterior * sum;
+        }
+        Ok((frequencies, pseudocount))
+    }
+}
+
+/// Weighted count of each symbol in each column of an alignment
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    /// Symbols counted, in uppercase
+    symbols: Vec<u8>,
+    /// Background frequency of each symbol
+    background: Vec<f64>,
+    /// Number of columns
+    length: usize,
+    /// Count of each symbol, column after column
+    counts: Vec<f64>,
+    /// Count of gaps in each column
+    gaps: Vec<f64>,
+    /// First sequence of the alignment
+    query: Vec<u8>,
+}
+
+impl Profile {
+    /// Returns the profile of `alignment` over `symbols`, each sequence counting for its weight (1 by default)
+    ///
+    /// Residues are compared to the symbols regardless of their case. Residues that are neither a symbol nor a gap (`-` or `.`) are not counted.
+    ///
+    /// The background frequencies are the BLOSUM62 ones when the symbols are the 20 amino acids, uniform otherwise (see [`set_background()`]).
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number of weights is different from the number of sequences, or if a weight is negative or not a finite number.
+    ///
+    /// [`set_background()`]: #method.set_background
+    pub fn new<T>(
+        alignment: &Alignment<T>,
+        symbols: &[u8],
+        weights: Option<&[f64]>,
+    ) -> Result<Self, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+    {
+        if let Some(weights) = weights {
+            utils::check_weights(weights, alignment.n_sequences)?;
+        }
+
+        Ok(Self::count(alignment, symbols, weights))
+    }
+
+    /// Returns the profile of `alignment`, the weights having been checked
+    fn count<T>(alignment: &Alignment<T>, symbols: &[u8], weights: Option<&[f64]>) -> Self
+    where
+        T: Clone + Into<u8>,
+    {
+        let symbols: Vec<u8> = symbols.iter().map(u8::to_ascii_uppercase).collect();
+        let mut indices = [None; 256];
+        for (index, &symbol) in symbols.iter().enumerate() {
+            indices[usize::from(symbol)] = Some(index);
+        }
+
+        let mut counts = vec![0.0; alignment.length * symbols.len()];
+        let mut gaps = vec![0.0; alignment.length];
+        for (index, residue) in alignment.sequences.iter().enumerate() {
+            let (row, column) = (index / alignment.length, index % alignment.length);
+            let weight = weights.map_or(1.0, |weights| weights[row]);
+            let residue: u8 = residue.clone().into();
+            if utils::is_gap(residue) {
+                gaps[column] += weight;
+            } else if let Some(symbol) = indices[usize::from(residue.to_ascii_uppercase())] {
+                counts[column * symbols.len() + symbol] += weight;
+            }
+        }
+
+        let query = alignment
+            .sequences
+            .iter()
+            .take(alignment.length)
+            .map(|residue| residue.clone().into().to_ascii_uppercase())
+            .collect();
+
+        Self {
+            background: default_background(&symbols),
+            symbols,
+            length: alignment.length,
+            counts,
+            gaps,
+            query,
+        }
+    }
+
+    /// Returns the symbols counted, in uppercase
+    #[must_use]
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    /// Returns the number of columns
+    #[must_use]
+    pub const fn length(&self) -> &usize {
+        &self.length
+    }
+
+    /// Returns the background frequency of each symbol
+    #[must_use]
+    pub fn background(&self) -> &[f64] {
+        &self.background
+    }
+
+    /// Sets the background frequency of each symbol, in the order of [`symbols()`]
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is not one frequency per symbol.
+    ///
+    /// [`symbols()`]: #method.symbols
+    pub fn set_background(&mut self, background: Vec<f64>) -> Result<(), MultiSeqAlignError> {
+        check_symbols(&background, self.symbols.len())?;
+        self.background = background;
+        Ok(())
+    }
+
+    /// Returns the weighted count of each symbol in `column`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the profile.
+    #[must_use]
+    pub fn counts(&self, column: usize) -> &[f64] {
+        assert!(column < self.length);
+        let n_symbols = self.symbols.len();
+        &self.counts[column * n_symbols..(column + 1) * n_symbols]
+    }
+
+    /// Returns the weighted count of gaps in `column`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the profile.
+    #[must_use]
+    pub fn gaps(&self, column: usize) -> f64 {
+        self.gaps[column]
+    }
+
+    /// Returns the observed frequency of each symbol in `column`, gaps excluded
+    ///
+    /// The frequencies of a column without any symbol are all 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the profile.
+    #[must_use]
+    pub fn frequencies(&self, column: usize) -> Vec<f64> {
+        let counts = self.counts(column);
+        let total: f64 = counts.iter().sum();
+        counts
+            .iter()
+            .map(|&count| if total > 0.0 { count / total } else { 0.0 })
+            .collect()
+    }
+
+    /// Returns the frequency of each symbol in `column`, with `pseudocounts`
+    ///
+    /// Without pseudocounts, the frequencies of a column without any symbol are the background ones.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a component of a Dirichlet mixture does not have one parameter per symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the profile.
+    pub fn frequencies_with(
+        &self,
+        column: usize,
+        pseudocounts: &Pseudocounts,
+    ) -> Result<Vec<f64>, MultiSeqAlignError> {
+        self.pseudo_frequencies(column, pseudocounts)
+            .map(|(frequencies, _)| frequencies)
+    }
+
+    /// Returns the frequencies of `column` with `pseudocounts`, and the total pseudocount
+    #[allow(clippy::cast_precision_loss)]
+    fn pseudo_frequencies(
+        &self,
+        column: usize,
+        pseudocounts: &Pseudocounts,
+    ) -> Result<(Vec<f64>, f64), MultiSeqAlignError> {
+        let counts = self.counts(column);
+        let total: f64 = counts.iter().sum();
+
+        let (pseudocount, spread): (f64, Vec<f64>) = match pseudocounts {
+            Pseudocounts::Dirichlet(mixture) => return mixture.frequencies(counts),
+            Pseudocounts::None => (0.0, vec![0.0; counts.len()]),
+            Pseudocounts::Constant(constant) => (
+                constant * counts.len() as f64,
+                vec![*constant; counts.len()],
+            ),
+            Pseudocounts::Background(weight) => (
+                *weight,
+                self.background.iter().map(|b| weight * b).collect(),
+            ),
+        };
+
+        if total + pseudocount <= 0.0 {
+            return Ok((self.background.clone(), 0.0));
+        }
+        let frequencies = counts
+            .iter()
+            .zip(&spread)
+            .map(|(count, extra)| (count + extra) / (total + pseudocount))
+            .collect();
+        Ok((frequencies, pseudocount))
+    }
+
+    /// Returns the log-odds scoring matrix of the profile against its background, with `pseudocounts`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a component of a Dirichlet mixture does not have one parameter per symbol.
+    pub fn pssm(&self, pseudocounts: &Pseudocounts) -> Result<Pssm, MultiSeqAlignError> {
+        let n_symbols = self.symbols.len();
+        let mut pssm = Pssm {
+            symbols: self.symbols.clone(),
+            query: self.query.clone(),
+            length: self.length,
+            scores: Vec::with_capacity(self.length * n_symbols),
+            percentages: Vec::with_capacity(self.length * n_symbols),
+            information: Vec::with_capacity(self.length),
+            relative_weights: Vec::with_capacity(self.length),
+        };
+
+        for column in 0..self.length {
+            let (frequencies, pseudocount) = self.pseudo_frequencies(column, pseudocounts)?;
+            let mut information = 0.0;
+            for (&frequency, &background) in frequencies.iter().zip(&self.background) {
+                pssm.scores.push(log_odds(frequency, background));
+                if frequency > 0.0 && background > 0.0 {
+                    information += frequency * (frequency / background).log2();
+                }
+            }
+            pssm.information.push(information);
+
+            let total: f64 = self.counts(column).iter().sum();
+            pssm.relative_weights.push(if total > 0.0 {
+                total / (total + pseudocount)
+            } else {
+                0.0
+            });
+            pssm.percentages.extend(
+                self.frequencies(column)
+                    .iter()
+                    .map(|frequency| (frequency * 100.0).floor()),
+            );
+        }
+
+        Ok(pssm)
+    }
+}
+
+impl<T> Alignment<T> {
+    /// Returns the unweighted amino acid profile of `self`, see [`Profile`] for the other options
+    ///
+    /// [`Profile`]: profile/struct.Profile.html
+    #[must_use]
+    pub fn profile(&self) -> Profile
+    where
+        T: Clone + Into<u8>,
+    {
+        Profile::count(self, AMINO_ACIDS, None)
+    }
+}
+
+/// Position-specific scoring matrix, in bits
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pssm {
+    /// Symbols scored, in uppercase
+    symbols: Vec<u8>,
+    /// First sequence of the alignment
+    query: Vec<u8>,
+    /// Number of columns
+    length: usize,
+    /// Score of each symbol, column after column
+    scores: Vec<f64>,
+    /// Observed percentage of each symbol rounded down, column after column
+    percentages: Vec<f64>,
+    /// Information content of each column
+    information: Vec<f64>,
+    /// Observed counts over observed counts and pseudocounts, for each column
+    relative_weights: Vec<f64>,
+}
+
+impl Pssm {
+    /// Returns the symbols scored, in uppercase
+    #[must_use]
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    /// Returns the number of columns
+    #[must_use]
+    pub const fn length(&self) -> &usize {
+        &self.length
+    }
+
+    /// Returns the log-odds score (in bits) of each symbol in `column`
+    ///
+    /// A symbol absent from a column without pseudocounts scores minus infinity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the matrix.
+    #[must_use]
+    pub fn scores(&self, column: usize) -> &[f64] {
+        assert!(column < self.length);
+        let n_symbols = self.symbols.len();
+        &self.scores[column * n_symbols..(column + 1) * n_symbols]
+    }
+
+    /// Returns the log-odds score (in bits) of `residue` (in any case) in `column`, `None` if it is not a symbol of the matrix
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is greater or equal to the `length` of the matrix.
+    #[must_use]
+    pub fn score(&self, column: usize, residue: u8) -> Option<f64> {
+        let residue = residue.to_ascii_uppercase();
+        let index = self.symbols.iter().position(|&symbol| symbol == residue)?;
+        Some(self.scores(column)[index])
+    }
+
+    /// Returns the information content (relative entropy to the background, in bits) of each column
+    #[must_use]
+    pub fn information(&self) -> &[f64] {
+        &self.information
+    }
+
+    /// Writes the matrix in the ASCII layout of PSI-BLAST (`-out_ascii_pssm`)
+    ///
+    /// Each row is a column of the alignment, labelled with the residue of the first sequence. Scores are written in half bits (the unit of BLOSUM62) rounded to the nearest integer and clamped between -99 and 99, followed by the observed percentages rounded down, the information content and the relative weight of the observed counts to the pseudocounts. The Karlin–Altschul statistics written by PSI-BLAST after the matrix are not computed, and are left out.
+    ///
+    /// PSI-BLAST expects the 20 amino acids in the order of [`AMINO_ACIDS`], the symbols of a profile built on other symbols are written in their own order.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if writing fails.
+    ///
+    /// [`AMINO_ACIDS`]: constant.AMINO_ACIDS.html
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn write_psiblast<W>(&self, mut writer: W) -> Result<(), MultiSeqAlignError>
+    where
+        W: Write,
+    {
+        writeln!(writer)?;
+        writeln!(writer, "Last position-specific scoring matrix computed, weighted observed percentages rounded down, information per position, and relative weight of gapless real matches to pseudocounts")?;
+
+        write!(writer, "{:9}", "")?;
+        for &symbol in &self.symbols {
+            write!(writer, "{:>3}", char::from(symbol))?;
+        }
+        write!(writer, " ")?;
+        for &symbol in &self.symbols {
+            write!(writer, "{:>4}", char::from(symbol))?;
+        }
+        writeln!(writer)?;
+
+        let n_symbols = self.symbols.len();
+        for column in 0..self.length {
+            write!(
+                writer,
+                "{:>5} {}  ",
+                column + 1,
+                char::from(self.query[column])
+            )?;
+            for score in self.scores(column) {
+                let (min, max) = PSIBLAST_SCORE_RANGE;
+                write!(writer, "{:>3}", (score * 2.0).round().clamp(min, max) as i8)?;
+            }
+            write!(writer, " ")?;
+            for percentage in &self.percentages[column * n_symbols..(column + 1) * n_symbols] {
+                write!(writer, "{percentage:>4}")?;
+            }
+            writeln!(
+                writer,
+                "  {:4.2} {:4.2}",
+                self.information[column], self.relative_weights[column]
+            )?;
+        }
+        writeln!(writer)?;
+
+        Ok(())
+    }
+}
+
+/// Returns an error unless there is one value per symbol
+const fn check_symbols(values: &[f64], n_symbols: usize) -> Result<(), MultiSeqAlignError> {
+    if values.len() == n_symbols {
+        Ok(())
+    } else {
+        Err(MultiSeqAlignError::WrongNumberOfSymbols {
+            n_symbols,
+            found: values.len(),
+        })
+    }
+}
+
+/// Returns the BLOSUM62 background frequencies (normalised) for the 20 amino acids, uniform frequencies otherwise
+#[allow(clippy::cast_precision_loss)]
+fn default_background(symbols: &[u8]) -> Vec<f64> {
+    let amino_acids: Option<Vec<f64>> = symbols
+        .iter()
+        .map(|&symbol| substitution::amino_acid_index(symbol).map(|i| BLOSUM62_BACKGROUND[i]))
+        .collect();
+
+    match amino_acids {
+        Some(frequencies) if frequencies.len() == AMINO_ACIDS.len() => {
+            let total: f64 = frequencies.iter().sum();
+            frequencies
+                .iter()
+                .map(|frequency| frequency / total)
+                .collect()
+        }
+        _ => vec![1.0 / symbols.len() as f64; symbols.len()],
+    }
+}
+
+/// Returns the log-odds score of a frequency against its background, in bits
+fn log_odds(frequency: f64, background: f64) -> f64 {
+    if frequency <= 0.0 {
+        f64::NEG_INFINITY
+    } else if background <= 0.0 {
+        f64::INFINITY
+    } else {
+        (frequency / background).log2()
+    }
+}
+
+/// Returns the natural logarithm of the gamma function of a positive `x` (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // reflection formula
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (coefficient, i) in COEFFICIENTS[1..].iter().zip(1_u8..) {
+        sum += coefficient / (x + f64::from(i));
+    }
+    let t = x + G + 0.5;
+    0.5_f64.mul_add((2.0 * std::f64::consts::PI).ln(), (x + 0.5) * t.ln()) - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::assert_close;
+    use pretty_assertions::assert_eq;
+
+    fn profile() -> Profile {
+        let align = Alignment::with_sequences(&[
+            b"AC-".to_vec(),
+            b"Ag-".to_vec(),
+            b"TG-".to_vec(),
+            b"TN.".to_vec(),
+        ])
+        .unwrap();
+        Profile::new(&align, b"acgt", None).unwrap()
+    }
+
+    #[test]
+    fn counts() {
+        let profile = profile();
+
+        assert_eq!(profile.symbols(), b"ACGT");
+        assert_close(profile.background(), &[0.25; 4]);
+        assert_close(profile.counts(0), &[2.0, 0.0, 0.0, 2.0]);
+        // N is not a symbol
+        assert_close(profile.counts(1), &[0.0, 1.0, 2.0, 0.0]);
+        assert_close(&profile.frequencies(1), &[0.0, 1.0 / 3.0, 2.0 / 3.0, 0.0]);
+        assert_close(&[profile.gaps(2)], &[4.0]);
+    }
+
+    #[test]
+    fn weights() {
+        let align = Alignment::with_sequences(&[b"A".to_vec(), b"C".to_vec()]).unwrap();
+
+        let profile = Profile::new(&align, b"AC", Some(&[3.0, 1.0])).unwrap();
+        assert_close(&profile.frequencies(0), &[0.75, 0.25]);
+
+        let error = Profile::new(&align, b"AC", Some(&[1.0])).err().unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfWeights {
+            n_sequences: 2,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+
+        let error = Profile::new(&align, b"AC", Some(&[1.0, -1.0]))
+            .err()
+            .unwrap();
+        assert_eq!(error, MultiSeqAlignError::InvalidWeight { row: 1 });
+    }
+
+    #[test]
+    fn pseudocounts() {
+        let mut profile = profile();
+
+        assert_close(
+            &profile
+                .frequencies_with(1, &Pseudocounts::Constant(1.0))
+                .unwrap(),
+            &[1.0 / 7.0, 2.0 / 7.0, 3.0 / 7.0, 1.0 / 7.0],
+        );
+        profile.set_background(vec![0.5, 0.5, 0.0, 0.0]).unwrap();
+        assert_close(
+            &profile
+                .frequencies_with(1, &Pseudocounts::Background(2.0))
+                .unwrap(),
+            &[0.2, 0.4, 0.4, 0.0],
+        );
+        // a column without symbols has the background frequencies
+        assert_close(
+            &profile.frequencies_with(2, &Pseudocounts::None).unwrap(),
+            &[0.5, 0.5, 0.0, 0.0],
+        );
+
+        let error = profile.set_background(vec![1.0]).err().unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfSymbols {
+            n_symbols: 4,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn dirichlet_mixture() {
+        let profile = profile();
+
+        // a single component with equal parameters is a constant pseudocount
+        let single = DirichletMixture::new(vec![(1.0, vec![1.0; 4])]);
+        assert_close(
+            &profile
+                .frequencies_with(1, &Pseudocounts::Dirichlet(single))
+                .unwrap(),
+            &[1.0 / 7.0, 2.0 / 7.0, 3.0 / 7.0, 1.0 / 7.0],
+        );
+
+        // the component favouring G explains the second column best
+        let mixture = DirichletMixture::new(vec![
+            (0.5, vec![5.0, 0.1, 0.1, 5.0]),
+            (0.5, vec![0.1, 0.1, 5.0, 0.1]),
+        ]);
+        let frequencies = profile
+            .frequencies_with(1, &Pseudocounts::Dirichlet(mixture))
+            .unwrap();
+        assert!(frequencies[2] > 0.5);
+        assert_close(&[frequencies.iter().sum()], &[1.0]);
+
+        let wrong = DirichletMixture::new(vec![(1.0, vec![1.0; 3])]);
+        let error = profile
+            .frequencies_with(0, &Pseudocounts::Dirichlet(wrong))
+            .err()
+            .unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfSymbols {
+            n_symbols: 4,
+            found: 3,
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn log_odds_scores() {
+        let pssm = profile().pssm(&Pseudocounts::None).unwrap();
+
+        assert_close(pssm.scores(0)[..1].as_ref(), &[1.0]);
+        assert_eq!(pssm.score(0, b'c'), Some(f64::NEG_INFINITY));
+        assert_eq!(pssm.score(0, b'N'), None);
+        assert_close(&pssm.information()[..1], &[1.0]);
+    }
+
+    #[test]
+    fn psiblast_layout() {
+        let align = Alignment::with_sequences(&[b"AC".to_vec(), b"AG".to_vec()]).unwrap();
+        let pssm = Profile::new(&align, b"ACG", None)
+            .unwrap()
+            .pssm(&Pseudocounts::Constant(1.0))
+            .unwrap();
+
+        let mut output = Vec::new();
+        pssm.write_psiblast(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "           A  C  G    A   C   G");
+        // A: log2((3/5) / (1/3)) = 0.85 bits, C and G: log2((1/5) / (1/3)) = -0.74 bits
+        assert_eq!(lines[3], "    1 A    2 -1 -1  100   0   0  0.21 0.40");
+        assert_eq!(lines[4], "    2 C   -1  1  1    0  50  50  0.06 0.40");
+    }
+
+    #[test]
+    fn gamma_function() {
+        assert_close(&[ln_gamma(1.0), ln_gamma(2.0)], &[0.0, 0.0]);
+        assert!((ln_gamma(5.0) - 24_f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,250 @@
+/*! Column resampling
+
+Replicates of an alignment for bootstrap (Felsenstein 1985, columns sampled with replacement) or delete-half jackknife (half of the columns sampled without replacement) support values. A [`Resampler`] is seeded, so that the same seed always gives the same replicates, and draws them one at a time: as new alignments, as the indices of the sampled columns, or as the number of times each column is sampled.
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::resampling::Resampler;
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"VLK-ITAN".to_vec(),
+    b"ALRYITAT".to_vec(),
+])
+.unwrap();
+
+for replicate in align.bootstrap(42).take(100) {
+    assert_eq!(*replicate.length(), 8);
+}
+
+// the weights of a replicate can be given to any statistic instead of a new alignment
+let weights = Resampler::jackknife(8, 42).weights().next().unwrap();
+assert_eq!(weights.iter().sum::<usize>(), 4);
+
+let first: Vec<_> = align.jackknife(7).take(10).collect();
+let again: Vec<_> = align.jackknife(7).take(10).collect();
+assert_eq!(first, again);
+```
+
+[`Resampler`]: struct.Resampler.html
+*/
+
+use crate::Alignment;
+
+/// Sampling of the columns
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Method {
+    /// As many columns as the alignment, sampled with replacement
+    Bootstrap,
+    /// Half of the columns (rounded down), sampled without replacement
+    Jackknife,
+}
+
+/// Seeded generator of resampled columns
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Resampler {
+    /// Sampling of the columns
+    method: Method,
+    /// Number of columns of the alignment
+    length: usize,
+    /// Random number generator
+    rng: SplitMix64,
+}
+
+impl Resampler {
+    /// Returns a `Resampler` of the columns of an alignment of `length` columns, drawn by `method` from a generator seeded with `seed`
+    #[must_use]
+    pub const fn new(method: Method, length: usize, seed: u64) -> Self {
+        Self {
+            method,
+            length,
+            rng: SplitMix64(seed),
+        }
+    }
+
+    /// Returns a bootstrap `Resampler`
+    #[must_use]
+    pub const fn bootstrap(length: usize, seed: u64) -> Self {
+        Self::new(Method::Bootstrap, length, seed)
+    }
+
+    /// Returns a delete-half jackknife `Resampler`
+    #[must_use]
+    pub const fn jackknife(length: usize, seed: u64) -> Self {
+        Self::new(Method::Jackknife, length, seed)
+    }
+
+    /// Returns the indices of the columns of the next replicate, in increasing order (repeated when sampled several times)
+    pub fn next_indices(&mut self) -> Vec<usize> {
+        let mut indices = match self.method {
+            Method::Bootstrap => (0..self.length)
+                .map(|_| self.rng.below(self.length))
+                .collect(),
+            Method::Jackknife => {
+                // partial Fisher-Yates shuffle
+                let mut columns: Vec<usize> = (0..self.length).collect();
+                let half = self.length / 2;
+                for i in 0..half {
+                    let j = i + self.rng.below(self.length - i);
+                    columns.swap(i, j);
+                }
+                columns.truncate(half);
+                columns
+            }
+        };
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Returns the number of times each column is sampled in the next replicate
+    pub fn next_weights(&mut self) -> Vec<usize> {
+        let mut weights = vec![0; self.length];
+        for index in self.next_indices() {
+            weights[index] += 1;
+        }
+        weights
+    }
+
+    /// Returns an endless iterator over the column indices of the replicates, see [`next_indices()`]
+    ///
+    /// [`next_indices()`]: #method.next_indices
+    pub fn indices(mut self) -> impl Iterator<Item = Vec<usize>> {
+        std::iter::repeat_with(move || self.next_indices())
+    }
+
+    /// Returns an endless iterator over the column weights of the replicates, see [`next_weights()`]
+    ///
+    /// [`next_weights()`]: #method.next_weights
+    pub fn weights(mut self) -> impl Iterator<Item = Vec<usize>> {
+        std::iter::repeat_with(move || self.next_weights())
+    }
+
+    /// Returns an endless iterator over the replicates of `alignment`, which keep the identifiers and descriptions of the sequences
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` does not have the length of the `Resampler`.
+    pub fn replicates<T>(self, alignment: &Alignment<T>) -> impl Iterator<Item = Alignment<T>> + '_
+    where
+        T: Clone,
+    {
+        assert_eq!(alignment.length, self.length);
+        self.indices()
+            .map(move |indices| alignment.with_columns(&indices))
+    }
+}
+
+/// `SplitMix64` random number generator (Steele et al. 2014)
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number drawn uniformly below `n`, rejecting the draws that would bias the modulo
+    #[allow(clippy::cast_possible_truncation)]
+    const fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let draw = self.next_u64();
+            if draw < zone {
+                return (draw % n) as usize;
+            }
+        }
+    }
+}
+
+/// Resampled replicates
+impl<T> Alignment<T>
+where
+    T: Clone,
+{
+    /// Returns an endless iterator over bootstrap replicates of `self`, drawn from a generator seeded with `seed`
+    pub fn bootstrap(&self, seed: u64) -> impl Iterator<Item = Self> + '_ {
+        Resampler::bootstrap(self.length, seed).replicates(self)
+    }
+
+    /// Returns an endless iterator over delete-half jackknife replicates of `self`, drawn from a generator seeded with `seed`
+    pub fn jackknife(&self, seed: u64) -> impl Iterator<Item = Self> + '_ {
+        Resampler::jackknife(self.length, seed).replicates(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bootstrap_columns() {
+        let mut resampler = Resampler::bootstrap(10, 1);
+
+        for _ in 0..20 {
+            let indices = resampler.next_indices();
+            assert_eq!(indices.len(), 10);
+            assert!(indices.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(indices.iter().all(|&index| index < 10));
+        }
+        assert_eq!(resampler.next_weights().iter().sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn jackknife_columns() {
+        for indices in Resampler::jackknife(9, 3).indices().take(20) {
+            assert_eq!(indices.len(), 4);
+            assert!(indices.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        for weights in Resampler::jackknife(9, 3).weights().take(20) {
+            assert!(weights.iter().all(|&weight| weight <= 1));
+        }
+    }
+
+    #[test]
+    fn seeds() {
+        let replicates = |seed| {
+            Resampler::bootstrap(50, seed)
+                .indices()
+                .take(5)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(replicates(12), replicates(12));
+        assert!(replicates(12) != replicates(13));
+    }
+
+    #[test]
+    fn replicates() {
+        let mut align = Alignment::with_sequences(&[b"ABCD".to_vec(), b"abcd".to_vec()]).unwrap();
+        align.set_identifier(1, Some("lower")).unwrap();
+
+        let indices = Resampler::bootstrap(4, 5).next_indices();
+        let replicate = align.bootstrap(5).next().unwrap();
+
+        let expected: Vec<Vec<u8>> = [b"ABCD", b"abcd"]
+            .iter()
+            .map(|row| indices.iter().map(|&index| row[index]).collect())
+            .collect();
+        assert_eq!(
+            replicate
+                .iter_sequences()
+                .map(|sequence| sequence.into_iter().copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(replicate.index_of("lower"), Some(1));
+    }
+
+    #[test]
+    fn empty_alignment() {
+        let align = Alignment::<u8>::with_sequences(&[]).unwrap();
+
+        assert_eq!(*align.bootstrap(0).next().unwrap().length(), 0);
+        assert_eq!(*align.jackknife(0).next().unwrap().n_sequences(), 0);
+    }
+}
@@ -0,0 +1,326 @@
+/*! Sub-alignments
+
+Rows and columns are selected by anything implementing [`Selection`]: ranges (`2..5`, `2..=4`, `2..`, `..5`, `..`), lists of indices (which may repeat or reorder the columns) and boolean masks. Out-of-bounds selections return a [`MultiSeqAlignError`] instead of panicking.
+
+```rust
+# use multi_seq_align::Alignment;
+let align = Alignment::with_named_sequences(
+    &["human", "mouse", "fly"],
+    &[
+        b"ALKHITAN".to_vec(),
+        b"VLK-ITAN".to_vec(),
+        b"ALRYITAT".to_vec(),
+    ],
+)
+.unwrap();
+
+let domain = align.select_columns(2..=5).unwrap();
+assert_eq!(domain.sequence_named("mouse").unwrap(), b"K-IT".iter().collect::<Vec<_>>());
+
+let mammals = align.select(&[true, true, false], 4..).unwrap();
+assert_eq!(*mammals.n_sequences(), 2);
+assert_eq!(mammals.nth_position(0).unwrap(), vec![&b'I', &b'I']);
+
+assert!(align.select_columns(6..9).is_err());
+```
+
+[`Selection`]: trait.Selection.html
+[`MultiSeqAlignError`]: ../enum.MultiSeqAlignError.html
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::collections::BTreeSet;
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+/// Selection of rows or columns
+pub trait Selection {
+    /// Returns the selected indices, out of `length` rows or columns
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if an index is out of bounds, or if a mask does not have `length` values.
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError>;
+}
+
+impl Selection for Range<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        if self.start > self.end || self.end > length {
+            return Err(MultiSeqAlignError::InvalidRange {
+                start: self.start,
+                end: self.end,
+                length,
+            });
+        }
+        Ok(self.collect())
+    }
+}
+
+impl Selection for RangeInclusive<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        let (start, end) = self.into_inner();
+        if end == usize::MAX {
+            return Err(MultiSeqAlignError::IndexOutOfBounds { index: end, length });
+        }
+        (start..end + 1).indices(length)
+    }
+}
+
+impl Selection for RangeFrom<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        (self.start..length).indices(length)
+    }
+}
+
+impl Selection for RangeTo<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        (0..self.end).indices(length)
+    }
+}
+
+impl Selection for RangeToInclusive<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        (0..=self.end).indices(length)
+    }
+}
+
+impl Selection for RangeFull {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        Ok((0..length).collect())
+    }
+}
+
+impl Selection for &[usize] {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        if let Some(&index) = self.iter().find(|&&index| index >= length) {
+            return Err(MultiSeqAlignError::IndexOutOfBounds { index, length });
+        }
+        Ok(self.to_vec())
+    }
+}
+
+impl<const N: usize> Selection for &[usize; N] {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        self[..].indices(length)
+    }
+}
+
+impl Selection for Vec<usize> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        self.as_slice().indices(length)
+    }
+}
+
+/// Boolean mask, `true` for the selected rows or columns
+impl Selection for &[bool] {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        if self.len() != length {
+            return Err(MultiSeqAlignError::WrongMaskLength {
+                expected_length: length,
+                found_length: self.len(),
+            });
+        }
+        Ok(self
+            .iter()
+            .enumerate()
+            .filter(|&(_, &selected)| selected)
+            .map(|(index, _)| index)
+            .collect())
+    }
+}
+
+impl<const N: usize> Selection for &[bool; N] {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        self[..].indices(length)
+    }
+}
+
+impl Selection for Vec<bool> {
+    fn indices(self, length: usize) -> Result<Vec<usize>, MultiSeqAlignError> {
+        self.as_slice().indices(length)
+    }
+}
+
+/// Sub-alignments
+impl<T> Alignment<T>
+where
+    T: Clone,
+{
+    /// Returns a new `Alignment` made of the selected `columns` of `self`, in the order of the selection
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a column is out of bounds, or if a mask does not have one value per column.
+    pub fn select_columns<S>(&self, columns: S) -> Result<Self, MultiSeqAlignError>
+    where
+        S: Selection,
+    {
+        let columns = columns.indices(self.length)?;
+        Ok(self.with_columns(&columns))
+    }
+
+    /// Returns a new `Alignment` made of the selected `rows` of `self`, in the order of the selection, keeping their identifiers and descriptions
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a row is out of bounds, if a mask does not have one value per sequence, or if a named sequence is selected more than once.
+    pub fn select_rows<S>(&self, rows: S) -> Result<Self, MultiSeqAlignError>
+    where
+        S: Selection,
+    {
+        let rows = rows.indices(self.n_sequences)?;
+
+        let mut identifiers = BTreeSet::new();
+        for &row in &rows {
+            if let Some(identifier) = &self.identifiers[row] {
+                if !identifiers.insert(identifier) {
+                    return Err(MultiSeqAlignError::DuplicateIdentifier {
+                        identifier: identifier.clone(),
+                    });
+                }
+            }
+        }
+        Ok(self.with_rows(&rows))
+    }
+
+    /// Returns a new `Alignment` made of the selected `columns` of the selected `rows` of `self`, see [`select_rows()`] and [`select_columns()`]
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a selection is invalid.
+    ///
+    /// [`select_rows()`]: #method.select_rows
+    /// [`select_columns()`]: #method.select_columns
+    pub fn select<R, C>(&self, rows: R, columns: C) -> Result<Self, MultiSeqAlignError>
+    where
+        R: Selection,
+        C: Selection,
+    {
+        let columns = columns.indices(self.length)?;
+        Ok(self.select_rows(rows)?.with_columns(&columns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn alignment() -> Alignment<u8> {
+        let mut align = Alignment::with_sequences(&[
+            b"ACGTACGT".to_vec(),
+            b"AC-TAC-T".to_vec(),
+            b"TTGTACCA".to_vec(),
+        ])
+        .unwrap();
+        align.set_identifier(0, Some("first")).unwrap();
+        align.set_description(0, Some("a description"));
+        align
+    }
+
+    fn rows(align: &Alignment<u8>) -> Vec<Vec<u8>> {
+        align
+            .iter_sequences()
+            .map(|sequence| sequence.into_iter().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!((2..5).indices(8), Ok(vec![2, 3, 4]));
+        assert_eq!((2..=4).indices(8), Ok(vec![2, 3, 4]));
+        assert_eq!((6..).indices(8), Ok(vec![6, 7]));
+        assert_eq!((..2).indices(8), Ok(vec![0, 1]));
+        assert_eq!((..=1).indices(8), Ok(vec![0, 1]));
+        assert_eq!((..).indices(3), Ok(vec![0, 1, 2]));
+        assert_eq!((8..8).indices(8), Ok(vec![]));
+        assert_eq!(
+            (6..9).indices(8),
+            Err(MultiSeqAlignError::InvalidRange {
+                start: 6,
+                end: 9,
+                length: 8
+            })
+        );
+        assert_eq!(
+            (5..=8).indices(8),
+            Err(MultiSeqAlignError::InvalidRange {
+                start: 5,
+                end: 9,
+                length: 8
+            })
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 5..3;
+        assert!(reversed.indices(8).is_err());
+        assert!((9..).indices(8).is_err());
+    }
+
+    #[test]
+    fn indices_and_masks() {
+        assert_eq!((&[3, 1, 1]).indices(4), Ok(vec![3, 1, 1]));
+        assert_eq!(vec![0].indices(1), Ok(vec![0]));
+        assert_eq!(
+            (&[0, 4]).indices(4),
+            Err(MultiSeqAlignError::IndexOutOfBounds {
+                index: 4,
+                length: 4
+            })
+        );
+        assert_eq!((&[true, false, true]).indices(3), Ok(vec![0, 2]));
+        assert_eq!(
+            vec![true].indices(3),
+            Err(MultiSeqAlignError::WrongMaskLength {
+                expected_length: 3,
+                found_length: 1
+            })
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let align = alignment();
+        let selected = align.select_columns(&[7, 0, 0]).unwrap();
+
+        assert_eq!(
+            rows(&selected),
+            vec![b"TAA".to_vec(), b"TAA".to_vec(), b"ATT".to_vec()]
+        );
+        assert_eq!(selected.index_of("first"), Some(0));
+        assert_eq!(align.select_columns(..).unwrap(), align);
+    }
+
+    #[test]
+    fn rows_selection() {
+        let align = alignment();
+        let selected = align.select_rows(vec![2, 0]).unwrap();
+
+        assert_eq!(
+            rows(&selected),
+            vec![b"TTGTACCA".to_vec(), b"ACGTACGT".to_vec()]
+        );
+        assert_eq!(selected.index_of("first"), Some(1));
+        assert_eq!(selected.description(1), Some("a description"));
+        assert!(align.select_rows(&[1, 1]).is_ok());
+        assert_eq!(
+            align.select_rows(&[0, 0]),
+            Err(MultiSeqAlignError::DuplicateIdentifier {
+                identifier: "first".to_string()
+            })
+        );
+        assert!(align.select_rows(3..).is_ok());
+        assert!(align.select_rows(4..).is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let align = alignment();
+
+        assert_eq!(
+            rows(&align.select(&[false, true, true], 1..4).unwrap()),
+            vec![b"C-T".to_vec(), b"TGT".to_vec()]
+        );
+        assert_eq!(*align.select(0..0, 1..4).unwrap().n_sequences(), 0);
+        assert!(align.select(0..1, &[8]).is_err());
+    }
+}
@@ -0,0 +1,496 @@
+/*! Guide trees
+
+Trees built from pairwise distances by neighbor joining (Saitou & Nei 1987, unrooted, the last three nodes joined at the root) or UPGMA (rooted and ultrametric), with branch lengths. A [`Tree`] is written in the Newick format by [`to_newick()`] or `Display`.
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::distance::Model;
+let mut align = Alignment::with_sequences(&[
+    b"ACGTACGT".to_vec(),
+    b"ACGTACGA".to_vec(),
+    b"ACGAACCA".to_vec(),
+])
+.unwrap();
+align
+    .set_identifier(0, Some("human"))
+    .unwrap()
+    .set_identifier(1, Some("chimp"))
+    .unwrap();
+
+// sequences without identifier are labelled with their row index
+let tree = align.upgma_tree(Model::PDistance).unwrap();
+assert_eq!(tree.to_newick(), "((human:0.0625,chimp:0.0625):0.09375,2:0.15625);");
+```
+
+[`Tree`]: struct.Tree.html
+[`to_newick()`]: struct.Tree.html#method.to_newick
+*/
+
+use crate::distance::Model;
+use crate::errors::MultiSeqAlignError;
+use crate::matrix::SymmetricMatrix;
+use crate::Alignment;
+use std::fmt;
+
+/// Node of a tree
+#[derive(Clone, PartialEq, Debug)]
+pub struct Node {
+    /// Label of a leaf
+    label: Option<String>,
+    /// Length of the branch to the parent, `None` for the root
+    branch_length: Option<f64>,
+    /// Indices of the children
+    children: Vec<usize>,
+}
+
+impl Node {
+    /// Returns a leaf labelled `label`
+    const fn leaf(label: String) -> Self {
+        Self {
+            label: Some(label),
+            branch_length: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the label of a leaf, `None` for internal nodes
+    #[must_use]
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the length of the branch to the parent, `None` for the root
+    #[must_use]
+    pub const fn branch_length(&self) -> Option<f64> {
+        self.branch_length
+    }
+
+    /// Returns the indices of the children in the tree
+    #[must_use]
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Returns `true` if the node has no children
+    #[must_use]
+    pub const fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Tree with branch lengths, the leaves being the first nodes, in the order of the distance matrix
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tree {
+    /// Leaves then internal nodes, in the order they were joined
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    /// Returns the neighbor joining tree of `distances`, the leaves being labelled `labels`
+    ///
+    /// Negative branch lengths are set to 0.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is not one label per row of the matrix, or if a distance is not finite.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn neighbor_joining<S>(
+        distances: &SymmetricMatrix<f64>,
+        labels: &[S],
+    ) -> Result<Self, MultiSeqAlignError>
+    where
+        S: AsRef<str>,
+    {
+        let (mut tree, mut clusters) = Clusters::new(distances, labels)?;
+
+        while clusters.active.len() > 3 {
+            let r = clusters.active.len() as f64;
+            let sums: Vec<f64> = clusters
+                .active
+                .iter()
+                .map(|&a| {
+                    clusters
+                        .active
+                        .iter()
+                        .map(|&b| clusters.distances[a][b])
+                        .sum()
+                })
+                .collect();
+
+            let (x, y) =
+                clusters.closest(|a, b, distance| (r - 2.0).mul_add(distance, -sums[a] - sums[b]));
+            let distance = clusters.distances[clusters.active[x]][clusters.active[y]];
+            let length_x = 0.5_f64.mul_add(distance, (sums[x] - sums[y]) / (2.0 * (r - 2.0)));
+            clusters.join(
+                &mut tree,
+                x,
+                y,
+                [length_x, distance - length_x],
+                |dx, dy| (dx + dy - distance) / 2.0,
+            );
+        }
+
+        let active = clusters.active.clone();
+        let d = |a: usize, b: usize| clusters.distances[active[a]][active[b]];
+        let lengths = match active.len() {
+            2 => vec![d(0, 1) / 2.0; 2],
+            3 => vec![
+                (d(0, 1) + d(0, 2) - d(1, 2)) / 2.0,
+                (d(0, 1) + d(1, 2) - d(0, 2)) / 2.0,
+                (d(0, 2) + d(1, 2) - d(0, 1)) / 2.0,
+            ],
+            _ => return Ok(tree),
+        };
+        tree.add_node(
+            active.iter().map(|&slot| clusters.nodes[slot]).collect(),
+            &lengths,
+        );
+        Ok(tree)
+    }
+
+    /// Returns the UPGMA tree of `distances`, the leaves being labelled `labels`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is not one label per row of the matrix, or if a distance is not finite.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn upgma<S>(
+        distances: &SymmetricMatrix<f64>,
+        labels: &[S],
+    ) -> Result<Self, MultiSeqAlignError>
+    where
+        S: AsRef<str>,
+    {
+        let (mut tree, mut clusters) = Clusters::new(distances, labels)?;
+        let mut sizes = vec![1_usize; labels.len()];
+        let mut heights = vec![0.0; labels.len()];
+
+        while clusters.active.len() > 1 {
+            let (x, y) = clusters.closest(|_, _, distance| distance);
+            let (slot_x, slot_y) = (clusters.active[x], clusters.active[y]);
+            let height = clusters.distances[slot_x][slot_y] / 2.0;
+            let (size_x, size_y) = (sizes[slot_x] as f64, sizes[slot_y] as f64);
+
+            clusters.join(
+                &mut tree,
+                x,
+                y,
+                [height - heights[slot_x], height - heights[slot_y]],
+                |dx, dy| size_x.mul_add(dx, size_y * dy) / (size_x + size_y),
+            );
+            sizes[slot_x] += sizes[slot_y];
+            heights[slot_x] = height;
+        }
+
+        Ok(tree)
+    }
+
+    /// Adds an internal node joining `children` with branches of `lengths` (negative lengths set to 0), returning its index
+    fn add_node(&mut self, children: Vec<usize>, lengths: &[f64]) -> usize {
+        for (&child, &length) in children.iter().zip(lengths) {
+            self.nodes[child].branch_length = Some(length.max(0.0));
+        }
+        self.nodes.push(Node {
+            label: None,
+            branch_length: None,
+            children,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Returns the index of the root, `None` if the tree is empty
+    #[must_use]
+    pub const fn root(&self) -> Option<usize> {
+        self.nodes.len().checked_sub(1)
+    }
+
+    /// Returns the nodes, the leaves first
+    #[must_use]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node at `index`, `None` if out of the tree
+    #[must_use]
+    pub fn node(&self, index: usize) -> Option<&Node> {
+        self.nodes.get(index)
+    }
+
+    /// Returns an iterator over the leaves
+    pub fn leaves(&self) -> impl Iterator<Item = &Node> {
+        self.nodes.iter().filter(|node| node.is_leaf())
+    }
+
+    /// Returns the tree in the Newick format, labels being quoted when needed
+    #[must_use]
+    pub fn to_newick(&self) -> String {
+        self.to_string()
+    }
+
+    /// Writes the subtree of `node` in the Newick format
+    fn write_newick(&self, f: &mut fmt::Formatter<'_>, node: usize) -> fmt::Result {
+        let node = &self.nodes[node];
+        if !node.is_leaf() {
+            write!(f, "(")?;
+            for (index, &child) in node.children.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                self.write_newick(f, child)?;
+            }
+            write!(f, ")")?;
+        }
+        if let Some(label) = &node.label {
+            write!(f, "{}", quote(label))?;
+        }
+        if let Some(length) = node.branch_length {
+            #[allow(clippy::uninlined_format_args)]
+            write!(f, ":{}", length)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(root) = self.root() {
+            self.write_newick(f, root)?;
+        }
+        write!(f, ";")
+    }
+}
+
+/// Returns `label` between single quotes if it contains characters with a meaning in Newick, including underscores which unquoted labels read as blanks
+fn quote(label: &str) -> String {
+    if label.is_empty() || label.contains(|c: char| "()[]':;,_".contains(c) || c.is_whitespace()) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label.to_string()
+    }
+}
+
+/// Clusters being joined, with their distances
+struct Clusters {
+    /// Distances between the clusters, by slot
+    distances: Vec<Vec<f64>>,
+    /// Node of the cluster in each slot
+    nodes: Vec<usize>,
+    /// Slots of the clusters left
+    active: Vec<usize>,
+}
+
+impl Clusters {
+    /// Returns the tree of the leaves and a cluster per leaf, after checking the inputs
+    fn new<S>(
+        distances: &SymmetricMatrix<f64>,
+        labels: &[S],
+    ) -> Result<(Tree, Self), MultiSeqAlignError>
+    where
+        S: AsRef<str>,
+    {
+        let size = *distances.size();
+        if labels.len() != size {
+            return Err(MultiSeqAlignError::WrongNumberOfNames {
+                n_sequences: size,
+                found: labels.len(),
+            });
+        }
+
+        let mut full = vec![vec![0.0; size]; size];
+        for (row, values) in full.iter_mut().enumerate() {
+            for (column, value) in distances.row(row).enumerate() {
+                if !value.is_finite() {
+                    return Err(MultiSeqAlignError::InvalidDistance { row, column });
+                }
+                values[column] = *value;
+            }
+        }
+
+        let tree = Tree {
+            nodes: labels
+                .iter()
+                .map(|label| Node::leaf(label.as_ref().to_string()))
+                .collect(),
+        };
+        let clusters = Self {
+            distances: full,
+            nodes: (0..size).collect(),
+            active: (0..size).collect(),
+        };
+        Ok((tree, clusters))
+    }
+
+    /// Returns the positions in `active` of the pair minimising `criterion(a, b, distance)`, `a` and `b` being positions in `active`, the first pair winning ties
+    fn closest<F>(&self, criterion: F) -> (usize, usize)
+    where
+        F: Fn(usize, usize, f64) -> f64,
+    {
+        let mut best = (0, 1);
+        let mut minimum = f64::INFINITY;
+        for a in 0..self.active.len() {
+            for b in a + 1..self.active.len() {
+                let value = criterion(a, b, self.distances[self.active[a]][self.active[b]]);
+                if value < minimum {
+                    minimum = value;
+                    best = (a, b);
+                }
+            }
+        }
+        best
+    }
+
+    /// Joins the clusters at positions `x` and `y` of `active` in a new node of `tree`, the new cluster taking the slot of `x` with distances `distance(dx, dy)` to the others
+    fn join<F>(&mut self, tree: &mut Tree, x: usize, y: usize, lengths: [f64; 2], distance: F)
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let (slot_x, slot_y) = (self.active[x], self.active[y]);
+        let node = tree.add_node(vec![self.nodes[slot_x], self.nodes[slot_y]], &lengths);
+
+        for &slot in &self.active {
+            if slot != slot_x && slot != slot_y {
+                let new = distance(self.distances[slot_x][slot], self.distances[slot_y][slot]);
+                self.distances[slot_x][slot] = new;
+                self.distances[slot][slot_x] = new;
+            }
+        }
+        self.nodes[slot_x] = node;
+        self.active.remove(y);
+    }
+}
+
+/// Trees of the sequences, labelled with their identifier or their row index
+impl<T> Alignment<T>
+where
+    T: Clone + Into<u8>,
+{
+    /// Returns the labels of the sequences: their identifier, or their row index
+    fn tree_labels(&self) -> Vec<String> {
+        self.identifiers()
+            .enumerate()
+            .map(|(row, identifier)| identifier.map_or_else(|| row.to_string(), String::from))
+            .collect()
+    }
+
+    /// Returns the neighbor joining tree of the distances between the sequences of `self` under `model`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a distance is saturated or cannot be computed.
+    pub fn nj_tree(&self, model: Model) -> Result<Tree, MultiSeqAlignError> {
+        Tree::neighbor_joining(self.distance_matrix(model).distances(), &self.tree_labels())
+    }
+
+    /// Returns the UPGMA tree of the distances between the sequences of `self` under `model`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a distance is saturated or cannot be computed.
+    pub fn upgma_tree(&self, model: Model) -> Result<Tree, MultiSeqAlignError> {
+        Tree::upgma(self.distance_matrix(model).distances(), &self.tree_labels())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn matrix(size: usize, values: &[f64]) -> SymmetricMatrix<f64> {
+        let mut matrix = SymmetricMatrix::new(size, 0.0);
+        let mut values = values.iter();
+        for i in 0..size {
+            for j in i + 1..size {
+                matrix[(i, j)] = *values.next().unwrap();
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn neighbor_joining() {
+        // distances between a, b, c, d and e, row after row
+        let distances = matrix(5, &[5.0, 9.0, 9.0, 8.0, 10.0, 10.0, 9.0, 8.0, 7.0, 3.0]);
+        let tree = Tree::neighbor_joining(&distances, &["a", "b", "c", "d", "e"]).unwrap();
+
+        assert_eq!(tree.to_newick(), "(((a:2,b:3):3,c:4):2,d:2,e:1);");
+        assert_eq!(tree.leaves().count(), 5);
+        assert_eq!(tree.root(), Some(7));
+    }
+
+    #[test]
+    fn upgma() {
+        let distances = matrix(3, &[2.0, 6.0, 6.0]);
+        let tree = Tree::upgma(&distances, &["a", "b", "c"]).unwrap();
+
+        assert_eq!(tree.to_newick(), "((a:1,b:1):2,c:3);");
+        let root = tree.node(tree.root().unwrap()).unwrap();
+        assert_eq!(root.children(), &[3, 2]);
+        assert_eq!(root.branch_length(), None);
+    }
+
+    #[test]
+    fn small_trees() {
+        let distances = matrix(2, &[1.0]);
+        assert_eq!(
+            Tree::neighbor_joining(&distances, &["a", "b"])
+                .unwrap()
+                .to_newick(),
+            "(a:0.5,b:0.5);"
+        );
+
+        let single = SymmetricMatrix::new(1, 0.0);
+        assert_eq!(Tree::upgma(&single, &["a"]).unwrap().to_newick(), "a;");
+        let empty = SymmetricMatrix::new(0, 0.0);
+        assert_eq!(
+            Tree::neighbor_joining(&empty, &[] as &[&str])
+                .unwrap()
+                .to_newick(),
+            ";"
+        );
+    }
+
+    #[test]
+    fn quoted_labels() {
+        let distances = matrix(2, &[2.0]);
+        let tree = Tree::upgma(&distances, &["Homo sapiens", "it's"]).unwrap();
+
+        assert_eq!(tree.to_newick(), "('Homo sapiens':1,'it''s':1);");
+        let tree = Tree::upgma(&distances, &["Homo_sapiens", "Bos"]).unwrap();
+        assert_eq!(tree.to_newick(), "('Homo_sapiens':1,Bos:1);");
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let mut distances = matrix(2, &[1.0]);
+        let error = Tree::upgma(&distances, &["a"]).err().unwrap();
+        let expected = MultiSeqAlignError::WrongNumberOfNames {
+            n_sequences: 2,
+            found: 1,
+        };
+        assert_eq!(error, expected);
+
+        distances[(0, 1)] = f64::INFINITY;
+        let error = Tree::upgma(&distances, &["a", "b"]).err().unwrap();
+        let expected = MultiSeqAlignError::InvalidDistance { row: 0, column: 1 };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn alignment_trees() {
+        let align = Alignment::with_sequences(&[
+            b"ACGTACGTAC".to_vec(),
+            b"ACGTACGTAA".to_vec(),
+            b"ACGAACCTAA".to_vec(),
+            b"TCGAACCTAA".to_vec(),
+        ])
+        .unwrap();
+
+        let tree = align.nj_tree(Model::PDistance).unwrap();
+        let labels: Vec<_> = tree.leaves().filter_map(Node::label).collect();
+        assert_eq!(labels, vec!["0", "1", "2", "3"]);
+
+        let saturated = Alignment::with_sequences(&[b"AC".to_vec(), b"CA".to_vec()]).unwrap();
+        assert!(saturated.upgma_tree(Model::JukesCantor).is_err());
+    }
+}
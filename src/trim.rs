@@ -0,0 +1,541 @@
+/*! Column trimming
+
+Removes the gappy or poorly conserved columns of an alignment before building a phylogeny, like trimAl (Capella-Gutiérrez et al. 2009, *trimAl: a tool for automated alignment trimming in large-scale phylogenetic analyses*, Bioinformatics 25(15):1972–1973):
+
+- [`Mode::GapThreshold`]: keeps the columns whose fraction of gaps is at most a threshold
+- [`Mode::Gappyout`]: chooses the gap threshold at the point where the slope of the sorted gap fractions increases the most
+- [`Mode::Strict`]: keeps the columns passing the gappyout threshold whose conservation is at least the mean minus one standard deviation, then removes the kept columns with fewer than two kept columns among their four closest neighbours
+- [`Mode::Conservation`]: keeps the columns whose conservation score is at least a cutoff
+
+Conservation is Valdar's score by default, see [`Trim::conservation()`] to use another one. The trimmed alignment comes with the indices of the kept columns in the original alignment.
+
+Uninformative columns and sequences can also be removed in place, each method returning the removed indices: see [`remove_gap_columns()`], [`remove_invariant_columns()`] and [`remove_gappy_sequences()`].
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::trim::Mode;
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN--".to_vec(),
+    b"ALK-ITAN--".to_vec(),
+    b"ALRYITATW-".to_vec(),
+    b"VLKHITANWK".to_vec(),
+])
+.unwrap();
+
+let trimmed = align.trim(Mode::GapThreshold(0.25)).unwrap();
+assert_eq!(trimmed.columns(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+assert_eq!(trimmed.alignment().nth_sequence(2).unwrap(), b"ALRYITAT".iter().collect::<Vec<_>>());
+
+let (gappyout, columns) = align.trim(Mode::Gappyout).unwrap().into_parts();
+assert_eq!(*gappyout.length(), 8);
+assert_eq!(columns, trimmed.columns());
+```
+
+[`Mode::GapThreshold`]: enum.Mode.html#variant.GapThreshold
+[`Mode::Gappyout`]: enum.Mode.html#variant.Gappyout
+[`Mode::Strict`]: enum.Mode.html#variant.Strict
+[`Mode::Conservation`]: enum.Mode.html#variant.Conservation
+[`Trim::conservation()`]: struct.Trim.html#method.conservation
+[`remove_gap_columns()`]: ../struct.Alignment.html#method.remove_gap_columns
+[`remove_invariant_columns()`]: ../struct.Alignment.html#method.remove_invariant_columns
+[`remove_gappy_sequences()`]: ../struct.Alignment.html#method.remove_gappy_sequences
+*/
+
+use crate::conservation::{Conservation, Method};
+use crate::errors::MultiSeqAlignError;
+use crate::{utils, Alignment};
+
+/// Columns kept by the trimming
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mode {
+    /// Columns whose fraction of gaps is at most the threshold
+    GapThreshold(f64),
+    /// Columns whose number of gaps is at most the automated gappyout threshold
+    Gappyout,
+    /// Gappyout columns with an automated conservation cutoff, without isolated columns
+    Strict,
+    /// Columns whose conservation score is at least the cutoff
+    Conservation(f64),
+}
+
+/// Trims the columns of alignments
+#[derive(Clone, PartialEq, Debug)]
+pub struct Trim {
+    /// Columns kept
+    mode: Mode,
+    /// Conservation score of the columns
+    conservation: Conservation,
+}
+
+impl Trim {
+    /// Returns a `Trim` by `mode`, scoring the conservation with Valdar's score
+    #[must_use]
+    pub const fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            conservation: Conservation::new(Method::Valdar),
+        }
+    }
+
+    /// Sets the conservation score used by [`Mode::Strict`] and [`Mode::Conservation`]
+    ///
+    /// [`Mode::Strict`]: enum.Mode.html#variant.Strict
+    /// [`Mode::Conservation`]: enum.Mode.html#variant.Conservation
+    #[must_use]
+    pub fn conservation(mut self, conservation: Conservation) -> Self {
+        self.conservation = conservation;
+        self
+    }
+
+    /// Returns the indices of the columns of `alignment` kept by the trimming, in increasing order
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the conservation is weighted by a number of weights different from the number of sequences.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn columns<T>(&self, alignment: &Alignment<T>) -> Result<Vec<usize>, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+    {
+        let gaps = gaps(alignment);
+        let columns = match self.mode {
+            Mode::GapThreshold(threshold) => {
+                let n_sequences = alignment.n_sequences.max(1) as f64;
+                keep(&gaps, |_, gaps| gaps as f64 / n_sequences <= threshold)
+            }
+            Mode::Gappyout => {
+                let cutoff = gappyout(&gaps, alignment.n_sequences);
+                keep(&gaps, |_, gaps| gaps <= cutoff)
+            }
+            Mode::Strict => {
+                let cutoff = gappyout(&gaps, alignment.n_sequences);
+                let scores = self.conservation.compute(alignment)?;
+                let passing: Vec<f64> = gaps
+                    .iter()
+                    .zip(&scores)
+                    .filter(|&(&gaps, _)| gaps <= cutoff)
+                    .map(|(_, &score)| score)
+                    .collect();
+                let minimum = strict_cutoff(&passing);
+                let kept: Vec<bool> = gaps
+                    .iter()
+                    .zip(&scores)
+                    .map(|(&gaps, &score)| gaps <= cutoff && score >= minimum)
+                    .collect();
+                keep(&gaps, |column, _| {
+                    kept[column] && has_kept_neighbours(&kept, column)
+                })
+            }
+            Mode::Conservation(cutoff) => {
+                let scores = self.conservation.compute(alignment)?;
+                keep(&gaps, |column, _| scores[column] >= cutoff)
+            }
+        };
+        Ok(columns)
+    }
+
+    /// Returns the trimmed `alignment` and the indices of its columns in `alignment`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the conservation is weighted by a number of weights different from the number of sequences.
+    pub fn compute<T>(&self, alignment: &Alignment<T>) -> Result<Trimmed<T>, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+    {
+        let columns = self.columns(alignment)?;
+        Ok(Trimmed {
+            alignment: alignment.with_columns(&columns),
+            columns,
+        })
+    }
+}
+
+/// Trimmed alignment
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Trimmed<T> {
+    /// Alignment made of the kept columns
+    alignment: Alignment<T>,
+    /// Index of each kept column in the original alignment
+    columns: Vec<usize>,
+}
+
+impl<T> Trimmed<T> {
+    /// Returns the trimmed alignment, which keeps the identifiers and descriptions of the sequences
+    #[must_use]
+    pub const fn alignment(&self) -> &Alignment<T> {
+        &self.alignment
+    }
+
+    /// Returns the index in the original alignment of each column of the trimmed alignment
+    #[must_use]
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Returns the trimmed alignment and the index in the original alignment of each of its columns
+    #[must_use]
+    pub fn into_parts(self) -> (Alignment<T>, Vec<usize>) {
+        (self.alignment, self.columns)
+    }
+}
+
+/// Returns the number of gaps of each column
+fn gaps<T>(alignment: &Alignment<T>) -> Vec<usize>
+where
+    T: Clone + Into<u8>,
+{
+    let mut gaps = vec![0; alignment.length];
+    for row in alignment.sequences.chunks_exact(alignment.length.max(1)) {
+        for (gaps, residue) in gaps.iter_mut().zip(row) {
+            *gaps += usize::from(utils::is_gap(residue.clone().into()));
+        }
+    }
+    gaps
+}
+
+/// Returns the indices of the columns for which `predicate(column, gaps)` is true
+fn keep<P>(gaps: &[usize], predicate: P) -> Vec<usize>
+where
+    P: Fn(usize, usize) -> bool,
+{
+    gaps.iter()
+        .enumerate()
+        .filter(|&(column, &gaps)| predicate(column, gaps))
+        .map(|(column, _)| column)
+        .collect()
+}
+
+/// Returns the largest number of gaps of the columns kept by gappyout
+///
+/// The distinct numbers of gaps, in increasing order, are plotted against the fraction of columns having at most that many gaps. The threshold is the point after which the slope of that curve is multiplied the most. All the columns are kept when no point has a positive slope on both sides.
+#[allow(clippy::cast_precision_loss)]
+fn gappyout(gaps: &[usize], n_sequences: usize) -> usize {
+    let mut histogram = vec![0_usize; n_sequences + 1];
+    for &gaps in gaps {
+        histogram[gaps] += 1;
+    }
+
+    // slope of the curve before each distinct number of gaps, starting from the origin
+    let length = gaps.len() as f64;
+    let mut previous = 0;
+    let mut slopes = Vec::new();
+    for (gaps, &count) in histogram
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0)
+    {
+        let slope = ((gaps - previous) as f64 / n_sequences as f64) / (count as f64 / length);
+        slopes.push((gaps, slope));
+        previous = gaps;
+    }
+
+    let mut cutoff = (n_sequences, 0.0);
+    for pair in slopes.windows(2) {
+        let ((gaps, before), (_, after)) = (pair[0], pair[1]);
+        if before > 0.0 && after / before > cutoff.1 {
+            cutoff = (gaps, after / before);
+        }
+    }
+    cutoff.0
+}
+
+/// Returns the mean minus one standard deviation of the conservation `scores`
+#[allow(clippy::cast_precision_loss)]
+fn strict_cutoff(scores: &[f64]) -> f64 {
+    if scores.is_empty() {
+        return 0.0;
+    }
+    let n = scores.len() as f64;
+    let mean = scores.iter().sum::<f64>() / n;
+    let variance = scores
+        .iter()
+        .map(|score| (score - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    mean - variance.sqrt()
+}
+
+/// Returns whether at least two of the four closest neighbours of `column` are kept
+fn has_kept_neighbours(kept: &[bool], column: usize) -> bool {
+    let start = column.saturating_sub(2);
+    let end = (column + 3).min(kept.len());
+    (start..end)
+        .filter(|&neighbour| neighbour != column && kept[neighbour])
+        .count()
+        >= 2
+}
+
+impl<T> Alignment<T> {
+    /// Returns `self` trimmed by `mode`, with the indices of the kept columns, see [`Trim`] for the other options
+    ///
+    /// # Errors
+    ///
+    /// Never returns an error with the default conservation score, the `Result` matches [`Trim::compute()`].
+    ///
+    /// [`Trim`]: trim/struct.Trim.html
+    /// [`Trim::compute()`]: trim/struct.Trim.html#method.compute
+    pub fn trim(&self, mode: Mode) -> Result<Trimmed<T>, MultiSeqAlignError>
+    where
+        T: Clone + Into<u8>,
+    {
+        Trim::new(mode).compute(self)
+    }
+}
+
+/// Removal of uninformative columns and sequences
+impl<T> Alignment<T>
+where
+    T: Clone + Into<u8>,
+{
+    /// Removes the columns made of gaps only, and returns their indices
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let mut align = Alignment::with_sequences(&[
+    ///     b"AL-HITAN".to_vec(),
+    ///     b"VL--ITAN".to_vec(),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(align.remove_gap_columns(), vec![2]);
+    /// assert_eq!(align.nth_sequence(1).unwrap(), b"VL-ITAN".iter().collect::<Vec<_>>());
+    /// ```
+    pub fn remove_gap_columns(&mut self) -> Vec<usize> {
+        let n_sequences = self.n_sequences;
+        self.remove_columns(|column| {
+            column
+                .iter()
+                .filter(|&&residue| utils::is_gap(residue))
+                .count()
+                == n_sequences
+        })
+    }
+
+    /// Removes the columns with at most one distinct residue (gaps excluded, case ignored), including the columns made of gaps only, and returns their indices
+    ///
+    /// The remaining columns are the variable sites expected by models with an ascertainment bias correction.
+    pub fn remove_invariant_columns(&mut self) -> Vec<usize> {
+        self.remove_columns(|column| {
+            let mut residues = column
+                .iter()
+                .filter(|&&residue| !utils::is_gap(residue))
+                .map(u8::to_ascii_uppercase);
+            residues
+                .next()
+                .is_none_or(|first| residues.all(|residue| residue == first))
+        })
+    }
+
+    /// Removes the sequences whose fraction of gaps and `unknown` symbols (case ignored) is above `threshold`, and returns their indices
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// let mut align = Alignment::with_named_sequences(
+    ///     &["full", "fragment", "unknown"],
+    ///     &[b"ACGTACGT".to_vec(), b"ACG-----".to_vec(), b"ACNNN?NT".to_vec()],
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(align.remove_gappy_sequences(0.5, b"N?"), vec![1, 2]);
+    /// assert_eq!(align.identifiers().collect::<Vec<_>>(), vec![Some("full")]);
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    pub fn remove_gappy_sequences(&mut self, threshold: f64, unknown: &[u8]) -> Vec<usize> {
+        let unknown: Vec<u8> = unknown.iter().map(u8::to_ascii_uppercase).collect();
+        let length = self.length.max(1) as f64;
+        let (mut kept, mut removed) = (Vec::new(), Vec::new());
+        for row in 0..self.n_sequences {
+            let missing = self.sequences[row * self.length..(row + 1) * self.length]
+                .iter()
+                .map(|residue| residue.clone().into())
+                .filter(|&residue: &u8| {
+                    utils::is_gap(residue) || unknown.contains(&residue.to_ascii_uppercase())
+                })
+                .count();
+            if missing as f64 / length > threshold {
+                removed.push(row);
+            } else {
+                kept.push(row);
+            }
+        }
+
+        if !removed.is_empty() {
+            *self = self.with_rows(&kept);
+        }
+        removed
+    }
+
+    /// Removes the columns for which `predicate` is true, and returns their indices
+    fn remove_columns<P>(&mut self, predicate: P) -> Vec<usize>
+    where
+        P: Fn(&[u8]) -> bool,
+    {
+        let (mut kept, mut removed) = (Vec::new(), Vec::new());
+        let mut column = Vec::with_capacity(self.n_sequences);
+        for index in 0..self.length {
+            column.clear();
+            column.extend(
+                (0..self.n_sequences)
+                    .map(|row| self.sequences[row * self.length + index].clone().into()),
+            );
+            if predicate(&column) {
+                removed.push(index);
+            } else {
+                kept.push(index);
+            }
+        }
+
+        if !removed.is_empty() {
+            *self = self.with_columns(&kept);
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn alignment() -> Alignment<u8> {
+        Alignment::with_named_sequences(
+            &["a", "b", "c", "d"],
+            &[
+                b"MALKHITAN--".to_vec(),
+                b"-ALK-ITAN--".to_vec(),
+                b"-ALRYITATW-".to_vec(),
+                b"-VLKHITANWK".to_vec(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn gap_threshold() {
+        let align = alignment();
+
+        assert_eq!(
+            align.trim(Mode::GapThreshold(0.5)).unwrap().columns(),
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(
+            align.trim(Mode::GapThreshold(0.0)).unwrap().columns(),
+            &[1, 2, 3, 5, 6, 7, 8]
+        );
+        assert_eq!(
+            align.trim(Mode::GapThreshold(1.0)).unwrap().columns().len(),
+            11
+        );
+    }
+
+    #[test]
+    fn gappyout_knee() {
+        // 50 columns without gaps, 10 with 1 gap, 5 with 2 gaps and 3 with 9 gaps out of 10
+        let mut gaps = vec![0; 50];
+        gaps.extend(vec![1; 10]);
+        gaps.extend(vec![2; 5]);
+        gaps.extend(vec![9; 3]);
+
+        assert_eq!(gappyout(&gaps, 10), 2);
+        assert_eq!(gappyout(&[0, 0, 3], 3), 3);
+        assert_eq!(gappyout(&[], 0), 0);
+    }
+
+    #[test]
+    fn gappyout_columns() {
+        let trimmed = alignment().trim(Mode::Gappyout).unwrap();
+
+        assert_eq!(trimmed.columns(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            trimmed.alignment().sequence_named("d").unwrap(),
+            b"VLKHITAN".iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn conservation() {
+        let align = alignment();
+        let scores = Conservation::new(Method::Valdar).compute(&align).unwrap();
+        let cutoff = scores[3];
+
+        let trimmed = align.trim(Mode::Conservation(cutoff)).unwrap();
+        for &column in trimmed.columns() {
+            assert!(scores[column] >= cutoff);
+        }
+        assert!(trimmed.columns().contains(&3));
+
+        let weighted = Trim::new(Mode::Conservation(cutoff))
+            .conservation(Conservation::new(Method::Valdar).weights(vec![1.0; 2]));
+        assert!(weighted.compute(&align).is_err());
+    }
+
+    #[test]
+    fn strict() {
+        let trimmed = alignment().trim(Mode::Strict).unwrap();
+        let gappyout = alignment().trim(Mode::Gappyout).unwrap();
+
+        for column in trimmed.columns() {
+            assert!(gappyout.columns().contains(column));
+        }
+        assert!(!trimmed.columns().is_empty());
+    }
+
+    #[test]
+    fn isolated_columns() {
+        let kept = [true, false, false, true, false, true, true];
+
+        assert!(!has_kept_neighbours(&kept, 0));
+        assert!(!has_kept_neighbours(&kept, 3));
+        assert!(has_kept_neighbours(&kept, 4));
+        assert!(has_kept_neighbours(&kept, 5));
+        assert!(!has_kept_neighbours(&kept, 6));
+    }
+
+    #[test]
+    fn gap_columns() {
+        let mut align = alignment().with_rows(&[0, 1]);
+
+        assert_eq!(align.remove_gap_columns(), vec![9, 10]);
+        assert_eq!(*align.length(), 9);
+        assert_eq!(align.remove_gap_columns(), Vec::<usize>::new());
+        assert_eq!(
+            align.sequence_named("b").unwrap(),
+            b"-ALK-ITAN".iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invariant_columns() {
+        let mut align =
+            Alignment::with_sequences(&[b"AcG-T".to_vec(), b"aCT-T".to_vec(), b"-CG-A".to_vec()])
+                .unwrap();
+
+        assert_eq!(align.remove_invariant_columns(), vec![0, 1, 3]);
+        assert_eq!(
+            align.nth_sequence(0).unwrap(),
+            b"GT".iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn gappy_sequences() {
+        let mut align = alignment();
+
+        assert_eq!(align.remove_gappy_sequences(0.25, b""), vec![1]);
+        assert_eq!(*align.n_sequences(), 3);
+        assert_eq!(align.index_of("d"), Some(2));
+        assert_eq!(align.index_of("b"), None);
+
+        let mut align = alignment();
+        assert_eq!(align.remove_gappy_sequences(0.5, b"x"), Vec::<usize>::new());
+        assert_eq!(align, alignment());
+        assert_eq!(align.remove_gappy_sequences(0.2, b"mk"), vec![0, 1, 3]);
+        assert_eq!(align.identifiers().collect::<Vec<_>>(), vec![Some("c")]);
+        assert_eq!(*align.length(), 11);
+    }
+}
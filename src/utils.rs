@@ -2,18 +2,34 @@ use crate::errors::MultiSeqAlignError;
 
 #[inline]
 pub fn first_sequence_length<T>(sequences: &[Vec<T>]) -> usize {
-    match sequences.get(0) {
-        Some(seq) => seq.len(),
-        None => 0_usize,
-    }
+    sequences.first().map_or(0_usize, Vec::len)
 }
 
-// Returns a tuple of vector of indices and vector of found lengths
+// Returns `true` if `residue` is a gap symbol (`-` or `.`)
+#[inline]
+pub const fn is_gap(residue: u8) -> bool {
+    matches!(residue, b'-' | b'.')
+}
+
+// Returns an error listing the found lengths of the sequences of unexpected length
 #[inline]
 pub fn check_unequal_lengths<T>(
     seqs: &[Vec<T>],
     expected: usize,
 ) -> Result<(), MultiSeqAlignError> {
+    check_unequal_named_lengths::<T, &str>(seqs, &[], expected)
+}
+
+// Same as `check_unequal_lengths`, also reporting the identifiers of the sequences of unexpected length
+#[inline]
+pub fn check_unequal_named_lengths<T, S>(
+    seqs: &[Vec<T>],
+    identifiers: &[S],
+    expected: usize,
+) -> Result<(), MultiSeqAlignError>
+where
+    S: AsRef<str>,
+{
     let mismatches: (Vec<usize>, Vec<usize>) = seqs
         .iter()
         .enumerate()
@@ -32,10 +48,58 @@ pub fn check_unequal_lengths<T>(
         Err(MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
             expected_length: expected,
             found_lengths: mismatches.1,
+            identifiers: mismatches
+                .0
+                .iter()
+                .filter_map(|&index| identifiers.get(index))
+                .map(|id| id.as_ref().to_string())
+                .collect(),
         })
     }
 }
 
+// Returns an error unless there is one finite, non-negative weight per sequence
+#[inline]
+pub fn check_weights(weights: &[f64], n_sequences: usize) -> Result<(), MultiSeqAlignError> {
+    if weights.len() != n_sequences {
+        return Err(MultiSeqAlignError::WrongNumberOfWeights {
+            n_sequences,
+            found: weights.len(),
+        });
+    }
+
+    if let Some(row) = weights
+        .iter()
+        .position(|&weight| !weight.is_finite() || weight < 0.0)
+    {
+        return Err(MultiSeqAlignError::InvalidWeight { row });
+    }
+
+    Ok(())
+}
+
+// Asserts that `found` and `expected` have the same length and differ by less than 1e-9 at each index
+#[cfg(test)]
+#[track_caller]
+pub fn assert_close(found: &[f64], expected: &[f64]) {
+    assert_close_within(found, expected, 1e-9);
+}
+
+// Asserts that `found` and `expected` have the same length and differ by less than `tolerance` at each index
+#[cfg(test)]
+#[track_caller]
+pub fn assert_close_within(found: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(found.len(), expected.len());
+    for (found, expected) in found.iter().zip(expected) {
+        assert!(
+            (found - expected).abs() < tolerance,
+            "{} != {}",
+            found,
+            expected
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,6 +113,7 @@ mod tests {
         let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
             expected_length: 3,
             found_lengths: vec![4],
+            identifiers: vec![],
         };
         assert_eq!(error, expected);
     }
@@ -69,6 +134,48 @@ mod tests {
         let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
             expected_length: 3,
             found_lengths: vec![4, 5],
+            identifiers: vec![],
+        };
+        assert_eq!(error, expected);
+    }
+
+    #[test]
+    fn invalid_weights() {
+        assert_eq!(check_weights(&[0.0, 1.5], 2), Ok(()));
+        assert_eq!(
+            check_weights(&[1.0], 2),
+            Err(MultiSeqAlignError::WrongNumberOfWeights {
+                n_sequences: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            check_weights(&[1.0, -0.5], 2),
+            Err(MultiSeqAlignError::InvalidWeight { row: 1 })
+        );
+        assert_eq!(
+            check_weights(&[f64::NAN, 1.0], 2),
+            Err(MultiSeqAlignError::InvalidWeight { row: 0 })
+        );
+        assert_eq!(
+            check_weights(&[1.0, f64::INFINITY], 2),
+            Err(MultiSeqAlignError::InvalidWeight { row: 1 })
+        );
+    }
+
+    #[test]
+    fn unequal_named_lengths() {
+        let error = crate::utils::check_unequal_named_lengths(
+            &[b"ELK".to_vec(), b"ILKS".to_vec(), b"ILK".to_vec()],
+            &["seq1", "seq2", "seq3"],
+            3,
+        )
+        .err()
+        .unwrap();
+        let expected = MultiSeqAlignError::MultipleSequencesOfDifferentLengths {
+            expected_length: 3,
+            found_lengths: vec![4],
+            identifiers: vec!["seq2".to_string()],
         };
         assert_eq!(error, expected);
     }
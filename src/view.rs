@@ -0,0 +1,508 @@
+/*! Borrowed views
+
+Views borrow the residues of an [`Alignment`] without allocating:
+
+- a row is a plain `&[T]` slice (see [`row()`] and [`iter_rows()`])
+- a column is a [`ColumnView`], striding over the rows (see [`column()`] and [`iter_columns()`])
+- a block of consecutive rows and columns is a [`SubView`] (see [`view()`])
+
+[`iter_rows()`] and [`iter_columns()`] yield the same residues, in the same order, as [`iter_sequences()`] and [`iter_positions()`], which collect each sequence or position in a new `Vec`. The allocating iterators are kept, but the views can be used instead wherever a borrowed slice or view is enough.
+
+Views compare, hash and print by their residues: two columns (or two blocks) holding equal residues are equal, whatever the layout of the alignments they borrow from.
+
+```rust
+# use multi_seq_align::Alignment;
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"VLK-ITAN".to_vec(),
+    b"ALRYITAT".to_vec(),
+])
+.unwrap();
+
+assert_eq!(align.row(1).unwrap(), b"VLK-ITAN");
+
+let column = align.column(2).unwrap();
+assert_eq!(column.len(), 3);
+assert_eq!(column[2], b'R');
+assert_eq!(column.iter().filter(|&&residue| residue == b'K').count(), 2);
+
+let block = align.view(1.., 2..5).unwrap();
+assert_eq!(block.row(0).unwrap(), b"K-I");
+assert_eq!(block[(1, 1)], b'Y');
+for column in block.iter_columns() {
+    assert_eq!(column.len(), 2);
+}
+```
+
+[`Alignment`]: ../struct.Alignment.html
+[`row()`]: ../struct.Alignment.html#method.row
+[`iter_rows()`]: ../struct.Alignment.html#method.iter_rows
+[`column()`]: ../struct.Alignment.html#method.column
+[`iter_columns()`]: ../struct.Alignment.html#method.iter_columns
+[`iter_sequences()`]: ../struct.Alignment.html#method.iter_sequences
+[`iter_positions()`]: ../struct.Alignment.html#method.iter_positions
+[`view()`]: ../struct.Alignment.html#method.view
+[`ColumnView`]: struct.ColumnView.html
+[`SubView`]: struct.SubView.html
+*/
+
+use crate::errors::MultiSeqAlignError;
+use crate::Alignment;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::StepBy;
+use std::ops::{Bound, Index, Range, RangeBounds};
+use std::slice::Iter;
+
+/// Residues of a column, borrowed from the flat buffer of an alignment
+#[derive(Clone, Copy)]
+pub struct ColumnView<'a, T> {
+    /// Residues from the first to the last residue of the column
+    data: &'a [T],
+    /// Distance between two residues of the column
+    stride: usize,
+}
+
+impl<'a, T> ColumnView<'a, T> {
+    /// Returns the view of the `len` residues of `data` starting at `start`, `stride` residues apart
+    fn new(data: &'a [T], start: usize, stride: usize, len: usize) -> Self {
+        let data = if len == 0 {
+            &data[..0]
+        } else {
+            &data[start..=start + (len - 1) * stride]
+        };
+        Self {
+            data,
+            stride: stride.max(1),
+        }
+    }
+
+    /// Returns the number of residues of the column
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        if self.data.is_empty() {
+            0
+        } else {
+            (self.data.len() - 1) / self.stride + 1
+        }
+    }
+
+    /// Returns `true` if the column has no residue
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the residue of the `index`-th row of the column, `None` if out of bounds
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        self.data.get(index.checked_mul(self.stride)?)
+    }
+
+    /// Returns an iterator over the residues of the column
+    pub fn iter(&self) -> StepBy<Iter<'a, T>> {
+        self.data.iter().step_by(self.stride)
+    }
+}
+
+impl<T: PartialEq> PartialEq for ColumnView<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ColumnView<'_, T> {}
+
+impl<T: Hash> Hash for ColumnView<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for residue in self {
+            residue.hash(state);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ColumnView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<usize> for ColumnView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of the column")
+    }
+}
+
+impl<'a, T> IntoIterator for ColumnView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = StepBy<Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &ColumnView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = StepBy<Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Block of consecutive rows and columns, borrowed from the flat buffer of an alignment
+#[derive(Clone)]
+pub struct SubView<'a, T> {
+    /// Residues of the alignment
+    data: &'a [T],
+    /// Length of the alignment
+    stride: usize,
+    /// Rows of the view
+    rows: Range<usize>,
+    /// Columns of the view
+    columns: Range<usize>,
+}
+
+impl<'a, T> SubView<'a, T> {
+    /// Returns the number of rows of the view
+    #[must_use]
+    pub fn n_sequences(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the number of columns of the view
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the `index`-th row of the view, `None` if out of bounds
+    #[must_use]
+    pub fn row(&self, index: usize) -> Option<&'a [T]> {
+        if index >= self.n_sequences() {
+            return None;
+        }
+        let start = (self.rows.start + index) * self.stride;
+        Some(&self.data[start + self.columns.start..start + self.columns.end])
+    }
+
+    /// Returns the `index`-th column of the view, `None` if out of bounds
+    #[must_use]
+    pub fn column(&self, index: usize) -> Option<ColumnView<'a, T>> {
+        if index >= self.length() {
+            return None;
+        }
+        Some(ColumnView::new(
+            self.data,
+            self.rows.start * self.stride + self.columns.start + index,
+            self.stride,
+            self.n_sequences(),
+        ))
+    }
+
+    /// Returns the residue at `row` and `column` of the view, `None` if out of bounds
+    #[must_use]
+    pub fn get(&self, row: usize, column: usize) -> Option<&'a T> {
+        self.row(row)?.get(column)
+    }
+
+    /// Returns an iterator over the rows of the view
+    #[must_use]
+    pub fn iter_rows(&self) -> impl ExactSizeIterator<Item = &'a [T]> {
+        let (data, stride, columns) = (self.data, self.stride, self.columns.clone());
+        self.rows
+            .clone()
+            .map(move |row| &data[row * stride + columns.start..row * stride + columns.end])
+    }
+
+    /// Returns an iterator over the columns of the view
+    #[must_use]
+    pub fn iter_columns(&self) -> impl ExactSizeIterator<Item = ColumnView<'a, T>> {
+        let (data, stride, n_sequences) = (self.data, self.stride, self.n_sequences());
+        let first = self.rows.start * stride;
+        self.columns
+            .clone()
+            .map(move |column| ColumnView::new(data, first + column, stride, n_sequences))
+    }
+}
+
+impl<T: PartialEq> PartialEq for SubView<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.n_sequences() == other.n_sequences()
+            && self.length() == other.length()
+            && self.iter_rows().eq(other.iter_rows())
+    }
+}
+
+impl<T: Eq> Eq for SubView<'_, T> {}
+
+impl<T: Hash> Hash for SubView<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.n_sequences().hash(state);
+        self.length().hash(state);
+        for row in self.iter_rows() {
+            row.hash(state);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SubView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter_rows()).finish()
+    }
+}
+
+impl<T> Index<(usize, usize)> for SubView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &T {
+        self.get(row, column).expect("index out of the view")
+    }
+}
+
+/// Returns the indices within `range`, out of `length` rows or columns
+fn bounds<R>(range: &R, length: usize) -> Result<Range<usize>, MultiSeqAlignError>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => length,
+    };
+    if start > end || end > length {
+        return Err(MultiSeqAlignError::InvalidRange { start, end, length });
+    }
+    Ok(start..end)
+}
+
+/// Borrowed views
+impl<T> Alignment<T> {
+    /// Returns the residues of the sequence at `index` as a slice of the alignment, `None` if out of bounds
+    #[must_use]
+    pub fn row(&self, index: usize) -> Option<&[T]> {
+        if index >= self.n_sequences {
+            return None;
+        }
+        Some(&self.sequences[index * self.length..(index + 1) * self.length])
+    }
+
+    /// Returns the buffer the columns are read from, the offset between two columns and the distance between two residues of a column
+    fn column_layout(&self) -> (&[T], usize, usize) {
+        self.transposed()
+            .map_or((self.sequences.as_slice(), 1, self.length), |columns| {
+                (columns, self.n_sequences, 1)
+            })
+    }
+
+    /// Returns a view of the residues at `position`, `None` if out of bounds
+    #[must_use]
+    pub fn column(&self, position: usize) -> Option<ColumnView<'_, T>> {
+        if position >= self.length {
+            return None;
+        }
+        let (data, offset, stride) = self.column_layout();
+        Some(ColumnView::new(
+            data,
+            position * offset,
+            stride,
+            self.n_sequences,
+        ))
+    }
+
+    /// Returns a view of the `rows` and `columns` of `self`
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a range is out of bounds.
+    pub fn view<R, C>(&self, rows: R, columns: C) -> Result<SubView<'_, T>, MultiSeqAlignError>
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        Ok(SubView {
+            data: &self.sequences,
+            stride: self.length,
+            rows: bounds(&rows, self.n_sequences)?,
+            columns: bounds(&columns, self.length)?,
+        })
+    }
+
+    /// Returns an iterator over the sequences of `self` as slices, a borrowing alternative to [`iter_sequences()`] which collects each of them in a new `Vec`
+    ///
+    /// [`iter_sequences()`]: #method.iter_sequences
+    #[must_use]
+    pub fn iter_rows(&self) -> impl ExactSizeIterator<Item = &[T]> {
+        let length = self.length;
+        (0..self.n_sequences)
+            .map(move |index| &self.sequences[index * length..(index + 1) * length])
+    }
+
+    /// Returns an iterator over the positions of `self` as column views, a borrowing alternative to [`iter_positions()`] which collects each of them in a new `Vec`
+    ///
+    /// [`iter_positions()`]: #method.iter_positions
+    #[must_use]
+    pub fn iter_columns(&self) -> impl ExactSizeIterator<Item = ColumnView<'_, T>> {
+        let n_sequences = self.n_sequences;
+        let (data, offset, stride) = self.column_layout();
+        (0..self.length)
+            .map(move |position| ColumnView::new(data, position * offset, stride, n_sequences))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn alignment() -> Alignment<u8> {
+        Alignment::with_sequences(&[b"ACGTA".to_vec(), b"AC-TT".to_vec(), b"TTGTC".to_vec()])
+            .unwrap()
+    }
+
+    #[test]
+    fn rows() {
+        let align = alignment();
+
+        assert_eq!(align.row(2), Some(&b"TTGTC"[..]));
+        assert_eq!(align.row(3), None);
+        assert_eq!(
+            align.iter_rows().collect::<Vec<_>>(),
+            vec![&b"ACGTA"[..], &b"AC-TT"[..], &b"TTGTC"[..]]
+        );
+        assert_eq!(align.iter_rows().len(), 3);
+    }
+
+    #[test]
+    fn columns() {
+        let align = alignment();
+        let column = align.column(4).unwrap();
+
+        assert_eq!(column.len(), 3);
+        assert_eq!(column.iter().copied().collect::<Vec<_>>(), b"ATC".to_vec());
+        assert_eq!(
+            column.iter().rev().copied().collect::<Vec<_>>(),
+            b"CTA".to_vec()
+        );
+        assert_eq!(column.get(1), Some(&b'T'));
+        assert_eq!(column.get(3), None);
+        assert_eq!(column[0], b'A');
+        assert!(align.column(5).is_none());
+
+        let positions: Vec<Vec<u8>> = align
+            .iter_columns()
+            .map(|column| column.into_iter().copied().collect())
+            .collect();
+        let expected: Vec<Vec<u8>> = align
+            .iter_positions()
+            .map(|position| position.into_iter().copied().collect())
+            .collect();
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn sub_view() {
+        let align = alignment();
+        let view = align.view(1..=2, 1..4).unwrap();
+
+        assert_eq!(view.n_sequences(), 2);
+        assert_eq!(view.length(), 3);
+        assert_eq!(view.row(0), Some(&b"C-T"[..]));
+        assert_eq!(view.row(2), None);
+        assert_eq!(view.get(1, 2), Some(&b'T'));
+        assert_eq!(view.get(1, 3), None);
+        assert_eq!(view[(1, 0)], b'T');
+        assert_eq!(
+            view.column(1).unwrap().iter().copied().collect::<Vec<_>>(),
+            b"-G".to_vec()
+        );
+        assert_eq!(
+            view.iter_rows().collect::<Vec<_>>(),
+            vec![&b"C-T"[..], &b"TGT"[..]]
+        );
+        assert_eq!(
+            view.iter_columns()
+                .map(|column| column.iter().copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![b"CT".to_vec(), b"-G".to_vec(), b"TT".to_vec()]
+        );
+    }
+
+    #[test]
+    fn empty_views() {
+        let align = alignment();
+        let view = align.view(3.., ..).unwrap();
+
+        assert_eq!(view.n_sequences(), 0);
+        assert!(view.column(4).unwrap().is_empty());
+        assert_eq!(view.iter_rows().len(), 0);
+
+        let empty = Alignment::<u8>::new(4);
+        assert!(empty.column(3).unwrap().is_empty());
+        assert_eq!(empty.iter_columns().len(), 4);
+    }
+
+    #[test]
+    fn compare_by_residues() {
+        use crate::layout::Layout;
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<H: Hash>(value: &H) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let align = alignment();
+        let mut transposed = alignment();
+        transposed.set_layout(Layout::ColumnMajor);
+
+        // the same column, strided over the rows or contiguous in the column-major copy
+        let (column, other) = (align.column(1).unwrap(), transposed.column(1).unwrap());
+        assert_eq!(column, other);
+        assert_eq!(hash(&column), hash(&other));
+        assert_eq!(format!("{:?}", column), "[67, 67, 84]");
+        assert_ne!(column, align.column(2).unwrap());
+        assert_eq!(
+            align.column(1).unwrap(),
+            align.view(.., 1..2).unwrap().column(0).unwrap()
+        );
+
+        // the same residues in different blocks
+        let block = align.view(0..1, 0..2).unwrap();
+        let other = align.view(1..2, 0..2).unwrap();
+        assert_eq!(block, other);
+        assert_eq!(hash(&block), hash(&other));
+        assert_eq!(format!("{:?}", block), "[[65, 67]]");
+        assert_ne!(block, align.view(0..1, 0..1).unwrap());
+        assert_eq!(align.view(.., 4..4).unwrap(), align.view(.., 0..0).unwrap());
+        assert_ne!(
+            align.view(0..1, 0..0).unwrap(),
+            align.view(0..2, 0..0).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let align = alignment();
+
+        assert_eq!(
+            align.view(0..4, ..),
+            Err(MultiSeqAlignError::InvalidRange {
+                start: 0,
+                end: 4,
+                length: 3
+            })
+        );
+        assert!(align.view(.., 2..=5).is_err());
+    }
+}
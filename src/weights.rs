@@ -0,0 +1,257 @@
+/*! Sequence weights
+
+Redundant sequences dominate the statistics of an alignment. Weighting them down gives each family of sequences its share:
+
+- [`henikoff_weights()`]: position-based weights of Henikoff & Henikoff (1994), each column sharing a weight of 1 equally between its residue types, then equally between the sequences with each type
+- [`cluster_weights()`]: the inverse of the number of sequences at least [`DEFAULT_IDENTITY`] identical to each sequence (itself included), as in PSICOV or plmDCA
+- [`neff()`]: the effective number of sequences, the sum of the cluster weights
+
+Gaps are either counted as a 21st residue type or ignored (see [`Gaps`]). The weights can be given to [`Consensus`], [`Conservation`] or [`Profile`].
+
+```rust
+# use multi_seq_align::Alignment;
+# use multi_seq_align::consensus::Consensus;
+# use multi_seq_align::weights::{Gaps, DEFAULT_IDENTITY};
+let align = Alignment::with_sequences(&[
+    b"ALKHITAN".to_vec(),
+    b"ALKHITAN".to_vec(),
+    b"ALKHITAT".to_vec(),
+    b"VLR-ISGT".to_vec(),
+])
+.unwrap();
+
+let weights = align.cluster_weights(DEFAULT_IDENTITY, Gaps::Count);
+assert_eq!(weights, vec![1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 1.0]);
+assert_eq!(align.neff(DEFAULT_IDENTITY, Gaps::Count), 2.0);
+
+let consensus = Consensus::new().weights(weights).compute(&align).unwrap();
+assert_eq!(consensus, b"XLXHIXXT".to_vec());
+```
+
+[`henikoff_weights()`]: ../struct.Alignment.html#method.henikoff_weights
+[`cluster_weights()`]: ../struct.Alignment.html#method.cluster_weights
+[`neff()`]: ../struct.Alignment.html#method.neff
+[`DEFAULT_IDENTITY`]: constant.DEFAULT_IDENTITY.html
+[`Gaps`]: enum.Gaps.html
+[`Consensus`]: ../consensus/struct.Consensus.html
+[`Conservation`]: ../conservation/struct.Conservation.html
+[`Profile`]: ../profile/struct.Profile.html
+*/
+
+use crate::{utils, Alignment};
+
+/// Default identity above which two sequences belong to the same cluster
+pub const DEFAULT_IDENTITY: f64 = 0.8;
+
+/// Handling of the gaps (`-` or `.`) when weighting sequences
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Gaps {
+    /// Gaps are a residue type like the others, and two gaps are identical
+    #[default]
+    Count,
+    /// Gaps are skipped: only the columns where sequences have residues are compared
+    Ignore,
+}
+
+/// Returns the residue of a sequence in uppercase, gaps being `-`
+const fn symbol(residue: u8) -> u8 {
+    if utils::is_gap(residue) {
+        b'-'
+    } else {
+        residue.to_ascii_uppercase()
+    }
+}
+
+/// Sequence weighting
+impl<T> Alignment<T>
+where
+    T: Clone + Into<u8>,
+{
+    /// Returns the symbols of `self`, in uppercase and with `-` for gaps
+    fn normalized_symbols(&self) -> Vec<u8> {
+        self.sequences
+            .iter()
+            .map(|residue| symbol(residue.clone().into()))
+            .collect()
+    }
+
+    /// Returns the position-based weights of Henikoff & Henikoff, summing up to the number of sequences
+    ///
+    /// With [`Gaps::Ignore`], gaps are not a residue type and the weight of a sequence is divided by its number of residues, like in HMMER. A sequence made of gaps only has a weight of 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::weights::Gaps;
+    /// let align = Alignment::with_sequences(&[b"AA".to_vec(), b"AA".to_vec(), b"CC".to_vec()]).unwrap();
+    ///
+    /// assert_eq!(align.henikoff_weights(Gaps::Count), vec![0.75, 0.75, 1.5]);
+    /// ```
+    ///
+    /// [`Gaps::Ignore`]: weights/enum.Gaps.html#variant.Ignore
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn henikoff_weights(&self, gaps: Gaps) -> Vec<f64> {
+        let symbols = self.normalized_symbols();
+        let mut weights = vec![0.0; self.n_sequences];
+
+        for column in 0..self.length {
+            let mut counts = [0_usize; 256];
+            for row in 0..self.n_sequences {
+                counts[usize::from(symbols[row * self.length + column])] += 1;
+            }
+            if gaps == Gaps::Ignore {
+                counts[usize::from(b'-')] = 0;
+            }
+            let types = counts.iter().filter(|&&count| count > 0).count();
+
+            for (row, weight) in weights.iter_mut().enumerate() {
+                let count = counts[usize::from(symbols[row * self.length + column])];
+                if count > 0 {
+                    *weight += 1.0 / (types * count) as f64;
+                }
+            }
+        }
+
+        if gaps == Gaps::Ignore {
+            for (row, weight) in weights.iter_mut().enumerate() {
+                let residues = symbols[row * self.length..(row + 1) * self.length]
+                    .iter()
+                    .filter(|&&symbol| symbol != b'-')
+                    .count();
+                if residues > 0 {
+                    *weight /= residues as f64;
+                }
+            }
+        }
+
+        let total: f64 = weights.iter().sum();
+        if total > 0.0 {
+            for weight in &mut weights {
+                *weight *= self.n_sequences as f64 / total;
+            }
+        }
+        weights
+    }
+
+    /// Returns the inverse of the number of sequences (itself included) whose identity with each sequence is at least `threshold`, between 0 and 1
+    ///
+    /// The identity of two sequences is their fraction of identical columns. With [`Gaps::Ignore`], it is computed over the columns where both sequences have a residue, two sequences without such columns being 0% identical.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use multi_seq_align::Alignment;
+    /// # use multi_seq_align::weights::Gaps;
+    /// let align = Alignment::with_sequences(&[b"AAAA".to_vec(), b"AAAC".to_vec(), b"CCCC".to_vec()]).unwrap();
+    ///
+    /// assert_eq!(align.cluster_weights(0.75, Gaps::Count), vec![0.5, 0.5, 1.0]);
+    /// assert_eq!(align.cluster_weights(0.8, Gaps::Count), vec![1.0, 1.0, 1.0]);
+    /// ```
+    ///
+    /// [`Gaps::Ignore`]: weights/enum.Gaps.html#variant.Ignore
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cluster_weights(&self, threshold: f64, gaps: Gaps) -> Vec<f64> {
+        let symbols = self.normalized_symbols();
+        let mut neighbours = vec![1_usize; self.n_sequences];
+
+        for i in 0..self.n_sequences {
+            let a = &symbols[i * self.length..(i + 1) * self.length];
+            for j in i + 1..self.n_sequences {
+                let b = &symbols[j * self.length..(j + 1) * self.length];
+                if identity(a, b, gaps) >= threshold {
+                    neighbours[i] += 1;
+                    neighbours[j] += 1;
+                }
+            }
+        }
+
+        neighbours
+            .iter()
+            .map(|&neighbours| 1.0 / neighbours as f64)
+            .collect()
+    }
+
+    /// Returns the effective number of sequences at an identity `threshold`, the sum of the [`cluster_weights()`]
+    ///
+    /// [`cluster_weights()`]: #method.cluster_weights
+    #[must_use]
+    pub fn neff(&self, threshold: f64, gaps: Gaps) -> f64 {
+        self.cluster_weights(threshold, gaps).iter().sum()
+    }
+}
+
+/// Returns the fraction of identical columns of two sequences of symbols
+#[allow(clippy::cast_precision_loss)]
+fn identity(a: &[u8], b: &[u8], gaps: Gaps) -> f64 {
+    let (identical, compared) = a
+        .iter()
+        .zip(b)
+        .filter(|&(&x, &y)| gaps == Gaps::Count || (x != b'-' && y != b'-'))
+        .fold((0_usize, 0_usize), |(identical, compared), (x, y)| {
+            (identical + usize::from(x == y), compared + 1)
+        });
+
+    if compared > 0 {
+        identical as f64 / compared as f64
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::assert_close;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn henikoff() {
+        let align = Alignment::with_sequences(&[b"AAC".to_vec(), b"Aa-".to_vec(), b"CC-".to_vec()])
+            .unwrap();
+
+        // third column: 2 types (C, gap), gaps shared by 2 sequences
+        let expected = [0.25 + 0.25 + 0.5, 0.25 + 0.25 + 0.25, 0.5 + 0.5 + 0.25];
+        let total: f64 = expected.iter().sum();
+        let weights = align.henikoff_weights(Gaps::Count);
+        for (&weight, expected) in weights.iter().zip(&expected) {
+            assert_close(&[weight], &[expected * 3.0 / total]);
+        }
+
+        // the third column only counts for the first sequence, which has 3 residues
+        let expected = [(0.25 + 0.25 + 1.0) / 3.0, 0.5 / 2.0, 1.0 / 2.0];
+        let total: f64 = expected.iter().sum();
+        let weights = align.henikoff_weights(Gaps::Ignore);
+        for (&weight, expected) in weights.iter().zip(&expected) {
+            assert_close(&[weight], &[expected * 3.0 / total]);
+        }
+    }
+
+    #[test]
+    fn gaps_only_sequence() {
+        let align = Alignment::with_sequences(&[b"AC".to_vec(), b"--".to_vec()]).unwrap();
+
+        assert_eq!(align.henikoff_weights(Gaps::Ignore), vec![2.0, 0.0]);
+        assert_eq!(align.cluster_weights(0.5, Gaps::Ignore), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn gapped_identity() {
+        assert_close(&[identity(b"AA--", b"AAC-", Gaps::Count)], &[0.75]);
+        assert_close(&[identity(b"AA--", b"AAC-", Gaps::Ignore)], &[1.0]);
+
+        let align = Alignment::with_sequences(&[b"AA--".to_vec(), b"AAC-".to_vec()]).unwrap();
+        assert_close(&[align.neff(0.8, Gaps::Count)], &[2.0]);
+        assert_close(&[align.neff(0.8, Gaps::Ignore)], &[1.0]);
+    }
+
+    #[test]
+    fn empty_alignment() {
+        let align = Alignment::<u8>::with_sequences(&[]).unwrap();
+
+        assert_eq!(align.henikoff_weights(Gaps::Count), Vec::<f64>::new());
+        assert_close(&[align.neff(DEFAULT_IDENTITY, Gaps::Count)], &[0.0]);
+    }
+}